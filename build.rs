@@ -0,0 +1,36 @@
+//! Records build information, printed by `exomat version --verbose`
+
+use std::process::Command;
+
+/// Returns the trimmed stdout of `program args`, or "unknown" if it failed.
+fn command_output(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|stdout| stdout.trim().to_string())
+        .filter(|stdout| !stdout.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+
+    println!(
+        "cargo:rustc-env=EXOMAT_GIT_DESCRIBE={}",
+        command_output("git", &["describe", "--always", "--dirty", "--tags"])
+    );
+    println!(
+        "cargo:rustc-env=EXOMAT_RUSTC_VERSION={}",
+        command_output(&rustc, &["--version"])
+    );
+    println!(
+        "cargo:rustc-env=EXOMAT_TARGET={}",
+        std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string())
+    );
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+}
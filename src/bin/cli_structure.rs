@@ -1,6 +1,15 @@
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 use clap_verbosity_flag::{InfoLevel, Verbosity};
+use exomat::experiment::markers::DEFAULT_MARKER_PATTERN;
+use exomat::experiment::run_options::WorkDir;
+use exomat::experiment::table_options::{parse_delimiter, ColumnCase, DEFAULT_JSON_DEPTH};
+use exomat::harness::env::Constraint;
+use exomat::harness::skeleton::{parse_template_var, Runner};
+use exomat::harness::table::aggregate::Statistic;
+use exomat::helper::affinity::CpuSet;
+use exomat::helper::archivist::{Compression, SymlinkMode};
+use exomat::LogFormat;
 use std::path::PathBuf;
 
 /// Tools for running experiments
@@ -21,6 +30,10 @@ pub struct Cli {
 
     #[command(flatten)]
     pub verbose: Verbosity<InfoLevel>,
+
+    /// Format of log messages, "human" or "json" (one JSON object per line)
+    #[arg(long, global = true, default_value = "human", value_parser = str::parse::<LogFormat>)]
+    pub log_format: LogFormat,
 }
 
 #[derive(Debug, Subcommand)]
@@ -34,6 +47,9 @@ pub enum Commands {
     ///   |    \-> run.sh [EMPTY, EXECUTABLE]
     ///   \-> envs/
     ///        \-> 0.env [EMPTY]
+    ///
+    /// Custom templates can be defined by creating the desired structure in
+    /// `$HOME/.config/exomat/template`, it is copied into `template/` instead.
     #[command(verbatim_doc_comment)]
     Skeleton {
         /// Path to the experiment.
@@ -42,6 +58,25 @@ pub enum Commands {
         /// Automatically creates parent directories.
         #[clap()]
         experiment: PathBuf,
+
+        /// Replace `{{KEY}}` with VALUE in all created template files and the README.
+        ///
+        /// Can be given multiple times. Placeholders without a value are kept and
+        /// reported as warning.
+        #[arg(long, value_name = "KEY=VALUE", value_parser = parse_template_var)]
+        template_var: Vec<(String, String)>,
+
+        /// Regenerate the skeleton of an existing experiment.
+        ///
+        /// Overwrites the run script in `template/` and creates everything that is
+        /// missing. Env files, the README and all other files in `template/` are kept.
+        #[arg(long)]
+        force: bool,
+
+        /// Interpreter of the run script: sh (`run.sh`), python (`run.py`) or
+        /// fish (`run.fish`).
+        #[arg(long, value_name = "RUNNER", default_value = "sh", value_parser = str::parse::<Runner>)]
+        runner: Runner,
     },
 
     /// Handles env files in the current directory according to the template.
@@ -65,6 +100,9 @@ pub enum Commands {
         /// > The order of files created does not necessarily represent reality
         ///
         /// Aborts if the variable is already defined or if it's reserved by the exomat (see README).
+        ///
+        /// A value `@FILE` reads the values from FILE, one per line (`@@` for a literal `@`).
+        /// `START..END[:STEP]` expands to all integers from START to END (inclusive).
         #[arg(short = 'a', long, num_args = 2..)]
         add: Vec<Vec<String>>,
 
@@ -104,6 +142,154 @@ pub enum Commands {
         /// > The order of these files does not necessarily represent reality
         #[arg(short = 'r', long, num_args = 1..)]
         remove: Vec<Vec<String>>,
+
+        /// Adds a variable (first arg) with values (remaining args) that varies
+        /// together with all other `--zip` variables.
+        ///
+        /// Instead of every combination, the n-th values of all zipped variables
+        /// form one combination, which is then combined with every .env file like
+        /// `--add`. All zipped variables need the same number of values.
+        ///
+        /// For example, `exomat env --zip WIDTH 640 1280 --zip HEIGHT 480 720`
+        /// creates two .env files, with `WIDTH=640`, `HEIGHT=480` and
+        /// `WIDTH=1280`, `HEIGHT=720`.
+        ///
+        /// Zipped variables are added after all other edits. Note that a later
+        /// `--append` or `--remove` combines all values of all variables again.
+        #[arg(short = 'z', long, num_args = 2..)]
+        zip: Vec<Vec<String>>,
+
+        /// Removes every environment matching CONSTRAINT, after all other edits.
+        ///
+        /// A constraint compares variables with `==` and `!=`, combined with `&&`
+        /// and `||` (`&&` binds stronger). May be given multiple times.
+        ///
+        /// For example, `exomat env --add MODE cpu gpu --add GPU_COUNT 0 4 --exclude
+        /// 'MODE==cpu && GPU_COUNT!=0'` creates three instead of four .env files.
+        #[arg(long, value_name = "CONSTRAINT")]
+        exclude: Vec<Constraint>,
+
+        /// Sets the order of all variables in every .env file in the directory.
+        ///
+        /// Every variable has to be listed exactly once. The order is used when
+        /// writing .env files and when printing the table of environments.
+        /// Variables added later on are appended at the end.
+        ///
+        /// For example, `exomat env --reorder BAZ FOO` writes `BAZ` before `FOO`
+        /// into every .env file.
+        #[arg(long, num_args = 1.., conflicts_with_all = ["add", "append", "remove", "zip", "exclude"])]
+        reorder: Vec<String>,
+
+        /// Removes all .env files and creates a single empty `0.env`.
+        ///
+        /// This resets the environments to the state of a new skeleton. Has to be
+        /// confirmed with `--yes`.
+        #[arg(long, conflicts_with_all = ["add", "append", "remove", "zip", "exclude", "reorder"])]
+        clear: bool,
+
+        /// Removes .env files that are identical to another one.
+        ///
+        /// Files are compared by their variables and values only. Of every group of
+        /// identical files, the first one (by file name) is kept. The remaining files
+        /// are renumbered from `0.env`.
+        #[arg(long, conflicts_with_all = ["add", "append", "remove", "zip", "exclude", "reorder", "clear"])]
+        dedup: bool,
+
+        /// Replaces all environments by the combinations of columns in a table.
+        ///
+        /// Takes the path to a CSV file (e.g. written by `exomat make-table`), followed
+        /// by the columns to import. Every distinct combination of values in these
+        /// columns becomes one .env file.
+        ///
+        /// For example, `exomat env --from-table best.csv THREADS SIZE` re-creates
+        /// exactly the configurations listed in `best.csv`.
+        #[arg(
+            long,
+            num_args = 2..,
+            value_names = ["TABLE", "COLUMN"],
+            conflicts_with_all = ["add", "append", "remove", "zip", "exclude", "reorder", "clear"]
+        )]
+        from_table: Vec<String>,
+
+        /// Replaces all environments by the ones described in a TOML grid file.
+        ///
+        /// Variables in the `[product]` table are combined like with `--add`, e.g.
+        /// `THREADS = [1, 2, 4]`. Variables in one `[coupled.NAME]` table vary
+        /// together, the n-th values of all of them form one tuple, e.g.
+        /// `BACKEND = ["cuda", "rocm"]` and `DRIVER = ["nvidia", "amdgpu"]`. Every
+        /// tuple is combined with every combination of the product variables.
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["add", "append", "remove", "zip", "exclude", "reorder", "clear", "from_table"]
+        )]
+        grid_file: Option<PathBuf>,
+
+        /// List variables in the order they are written in the .env files.
+        ///
+        /// Without any operation, the table of environments lists variables sorted
+        /// by name. With this flag, they are listed in their authored order instead
+        /// (see `--reorder`).
+        #[arg(long, conflicts_with_all = ["add", "append", "remove", "zip", "exclude", "reorder", "clear", "from_table"])]
+        preserve_order: bool,
+
+        /// Confirms `--clear`.
+        #[arg(long, requires = "clear")]
+        yes: bool,
+
+        /// Do not abort on the first failed operation.
+        ///
+        /// Every variable is edited on its own. Operations that fail (e.g. adding
+        /// a variable that is already defined) are skipped, all valid operations are
+        /// applied. All failed operations are reported at the end and exomat exits
+        /// with an error.
+        #[arg(long)]
+        continue_on_error: bool,
+
+        /// Abort if the edit would result in more than N .env files.
+        ///
+        /// Adding or appending values multiplies the number of environments. Without
+        /// this limit, only a warning is printed if the number grows a lot.
+        #[arg(long, value_name = "N")]
+        max_matrix: Option<u64>,
+
+        /// Print the number of environments instead of editing anything.
+        ///
+        /// If edits like `--add` or `--append` are given as well, also prints the
+        /// number of environments they would result in. No .env file is written
+        /// or removed.
+        #[arg(long, conflicts_with_all = ["reorder", "clear", "from_table", "grid_file", "preserve_order", "continue_on_error"])]
+        count: bool,
+
+        /// Accept lower case letters in the names of new variables.
+        ///
+        /// By default, names have to consist of upper case letters, digits and `_`
+        /// (and must not start with a digit), following POSIX conventions. With
+        /// this flag, `exomat env --add batch_size 8` is accepted as well.
+        #[arg(long)]
+        allow_lowercase: bool,
+    },
+
+    /// Combines the environments of two experiment sources.
+    ///
+    /// Replaces all .env files of DEST with every distinct environment of SRC1 and
+    /// SRC2. DEST may be one of the sources.
+    MergeEnvs {
+        /// First experiment source, its environments are listed first.
+        src1: PathBuf,
+
+        /// Second experiment source.
+        src2: PathBuf,
+
+        /// Experiment source to write the merged environments to.
+        #[arg(long, value_name = "DEST")]
+        into: PathBuf,
+
+        /// Value for variables that are only set in one of the sources.
+        ///
+        /// Without this, such variables are an error.
+        #[arg(long, value_name = "VALUE")]
+        fill: Option<String>,
     },
 
     /// Execute an experiment from an experiment directory
@@ -118,7 +304,7 @@ pub enum Commands {
         /// Start a trial run of the experiment.
         ///
         /// Executes one run of an experiment with one env combination. The resulting
-        /// experiment series directory can then be found in `/tmp`.
+        /// experiment series directory can then be found in `/tmp` (see `--trial-dir`).
         ///
         /// The exomat will then report on:
         /// - exit code of `run.sh`
@@ -129,10 +315,73 @@ pub enum Commands {
         #[arg(short = 't', long, default_value_t = false)]
         trial: bool,
 
+        /// Directory in which the trial series directory is created.
+        ///
+        /// Must be an existing, writable directory. Defaults to the temp dir of
+        /// the system, which is `$TMPDIR` if set and `/tmp` otherwise.
+        #[arg(long, value_name = "DIR", requires = "trial")]
+        trial_dir: Option<PathBuf>,
+
+        /// Only check `run.sh` for syntax errors, without running anything.
+        ///
+        /// Uses the no-exec mode (`-n`) of the shell given in the shebang. Scripts
+        /// for other interpreters than POSIX shells are not checked.
+        #[arg(long, default_value_t = false, conflicts_with = "trial")]
+        syntax_check: bool,
+
+        /// Execute the experiment twice and compare the outputs of both series.
+        ///
+        /// The second series is written next to the first one, with the suffix
+        /// `-verify`. Reports every output that differs and fails if there is any.
+        /// Outputs that look like timing information (`time`, `duration`, `date`,
+        /// ...) are not compared, unless `--include-timing` is given.
+        #[arg(long, default_value_t = false, conflicts_with_all = ["trial", "syntax_check"])]
+        verify_reproducible: bool,
+
+        /// Also compare timing outputs with `--verify-reproducible`.
+        #[arg(long, default_value_t = false, requires = "verify_reproducible")]
+        include_timing: bool,
+
+        /// Only create the series directory, without executing any run.
+        ///
+        /// Every run directory contains its run script and environment, ready to
+        /// be executed separately (e.g. on another machine or by a scheduler).
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["trial", "syntax_check", "verify_reproducible"]
+        )]
+        no_exec: bool,
+
+        /// Continue the interrupted experiment series in SERIES_DIR.
+        ///
+        /// Only runs that are missing or did not finish are executed, in the existing
+        /// series directory. A run counts as finished if its directory contains
+        /// `.exomat_run`, `run.sh`, `stdout.log` and `stderr.log`, which are written
+        /// once the run has finished.
+        #[arg(
+            long,
+            value_name = "SERIES_DIR",
+            conflicts_with_all = ["trial", "syntax_check", "verify_reproducible", "no_exec", "output"]
+        )]
+        resume: Option<PathBuf>,
+
+        /// Print which runs would be executed in which order, without executing them.
+        ///
+        /// Lists the env file and repetition of every run. No directories are
+        /// created. Use `--seed` to print the order of a later real run.
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["trial", "syntax_check", "verify_reproducible", "no_exec", "resume"]
+        )]
+        dry_run: bool,
+
         /// Output folder.
         ///
         /// Sets a specific output directory instead of `[experiment]-YYYY-MM-DD-HH-MM-SS`.
-        #[arg(short = 'o', long)]
+        /// Falls back to `$EXOMAT_OUTPUT` if not given.
+        #[arg(short = 'o', long, env = "EXOMAT_OUTPUT")]
         output: Option<PathBuf>,
 
         /// Number of runs per experiment.
@@ -140,8 +389,195 @@ pub enum Commands {
         /// This defines the number of directories inside the `[output]/runs/`
         /// directory. Each repetition will create a folder in the format `run_[env_name]_rep[Number]`.
         /// This format cannot be customized.
-        #[arg(short = 'r', long, default_value_t = 1)]
+        ///
+        /// Falls back to `$EXOMAT_REPETITIONS` if not given.
+        #[arg(short = 'r', long, env = "EXOMAT_REPETITIONS", default_value_t = 1)]
         repetitions: u64,
+
+        /// Print every command executed by `run.sh` (like `set -x`).
+        ///
+        /// The trace is written to stderr and ends up in `stderr.log`. Only
+        /// works for scripts using a POSIX shell (sh, bash, dash, zsh, ksh)
+        /// in their shebang, other scripts are run without tracing.
+        #[arg(long, default_value_t = false)]
+        shell_trace: bool,
+
+        /// Print a summary of all output keys after the experiment has finished.
+        ///
+        /// Lists every out_ file found in the experiment series, together with the
+        /// number of runs that produced a value for it.
+        #[arg(long, default_value_t = false)]
+        list_outputs: bool,
+
+        /// Write the progress to `[output]/.exomat_checkpoint` every N finished runs.
+        ///
+        /// The checkpoint lists every finished run with its status, so the progress
+        /// of long experiments is not lost if the exomat crashes. 0 disables checkpoints.
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        checkpoint_interval: u64,
+
+        /// Do not print a message for every successful run.
+        ///
+        /// Messages like "finished successfully" are logged at debug level instead,
+        /// warnings and errors are still shown. Use `-v` to show them anyway.
+        #[arg(long, default_value_t = false)]
+        quiet_runs: bool,
+
+        /// Notify when all runs have finished.
+        ///
+        /// Rings the terminal bell and reports how many runs succeeded and failed.
+        /// Builds with the `desktop-notify` feature also show a desktop notification.
+        #[arg(long, default_value_t = false)]
+        notify: bool,
+
+        /// How symlinks in the experiment source are copied into the series.
+        ///
+        /// - preserve: link to the same target, so linked data is not duplicated
+        /// - follow: copy the linked files and directories
+        /// - skip: leave out all symlinks
+        #[arg(long, default_value = "preserve", value_parser = str::parse::<SymlinkMode>)]
+        symlinks: SymlinkMode,
+
+        /// Give every run its own, private temp directory.
+        ///
+        /// `$TMPDIR`, `$TMP` and `$TEMP` point to this directory while the run is
+        /// executed, so runs do not interfere through shared files in `/tmp`.
+        /// The directory is removed after the run, unless the run failed.
+        #[arg(long, default_value_t = false)]
+        isolate_tmp: bool,
+
+        /// Also keep the private temp directories of successful runs.
+        #[arg(long, default_value_t = false, requires = "isolate_tmp")]
+        keep_tmp: bool,
+
+        /// Reuse the outputs of runs that were already executed in CACHE_DIR.
+        ///
+        /// CACHE_DIR is searched for runs (e.g. in older series) with exactly the
+        /// same environment, including the repetition. Instead of executing such a
+        /// run again, its outputs are copied.
+        #[arg(long, value_name = "CACHE_DIR")]
+        skip_cached: Option<PathBuf>,
+
+        /// Kill runs that take longer than SECONDS.
+        ///
+        /// All processes started by the run are killed as well. The run is recorded
+        /// with an `out_exomat_timeout` file containing `true`, then the remaining
+        /// runs are executed.
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+
+        /// Execute all remaining runs after a run failed.
+        ///
+        /// The exit code of every failed run is recorded in `out_exit_code`. Once all
+        /// runs have finished, exomat still exits with an error if any run failed.
+        #[arg(long, default_value_t = false)]
+        keep_going: bool,
+
+        /// Abort the series once N runs have failed, despite `--keep-going`.
+        ///
+        /// Stops large sweeps early if the experiment is broken and every run fails.
+        #[arg(long, value_name = "N", requires = "keep_going")]
+        max_failures: Option<usize>,
+
+        /// Shuffle the order of the runs with SEED.
+        ///
+        /// Without a seed a random one is chosen and written to `.exomat_seed` in
+        /// the series directory, so the same order can be used again.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Only execute runs of env files matching PATTERN.
+        ///
+        /// PATTERN is matched against the env file name, `*` matches any number of
+        /// characters, `?` a single one, e.g. `--env-filter '3.env'` or
+        /// `--env-filter '*high*'`. Fails if no env file matches.
+        #[arg(long, value_name = "PATTERN")]
+        env_filter: Option<String>,
+
+        /// Do not shuffle the runs, execute them sorted by env file and repetition.
+        ///
+        /// E.g. `0.env` repetition 0 and 1, then `1.env` repetition 0 and 1, ...
+        #[arg(long, default_value_t = false, conflicts_with = "seed")]
+        ordered: bool,
+
+        /// Execute a failed run up to N more times before it counts as failed.
+        ///
+        /// All attempts are executed in the same run directory, timed out runs
+        /// are not retried.
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        retries: usize,
+
+        /// Only retry runs that exited with CODE, can be given multiple times.
+        ///
+        /// Without this, every non-zero exit code is retried, e.g. use
+        /// `--retries 3 --retry-on-code 75` to only retry temporary failures.
+        #[arg(long, value_name = "CODE", requires = "retries")]
+        retry_on_code: Vec<i32>,
+
+        /// Execute N warmup runs per env file before all other runs.
+        ///
+        /// Warmup runs are written to `warmup_*` instead of `run_*` directories, so
+        /// `make-table` ignores their outputs (e.g. to avoid cold caches).
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        warmup: u64,
+
+        /// Write the command every run is started with to `command.txt` in its directory.
+        ///
+        /// Lists the program, arguments, working directory and all variables set
+        /// by the exomat (env file and exomat variables).
+        #[arg(long, default_value_t = false)]
+        capture_command_metadata: bool,
+
+        /// Do not write the value of VAR to `command.txt`, can be given multiple times.
+        #[arg(long, value_name = "VAR", requires = "capture_command_metadata")]
+        redact: Vec<String>,
+
+        /// Compress `stdout.log`, `stderr.log` and `exomat.log` of the series with
+        /// gzip once all runs are done.
+        ///
+        /// `make-table` and `--resume` read the compressed logs as well. Requires `gzip`.
+        #[arg(long, default_value_t = false)]
+        compress_logs: bool,
+
+        /// POST a JSON summary of the series to URL once it has finished or was aborted.
+        ///
+        /// The payload contains the fields `series`, `succeeded`, `failed`, `timed_out`,
+        /// `duration_s` and `aborted`. Requires `curl`, failing to send it is only a warning.
+        #[arg(long, value_name = "URL")]
+        notify_url: Option<String>,
+
+        /// Execute CMD with `sh -c` once the series has finished or was aborted.
+        ///
+        /// The summary is available in $EXOMAT_SERIES, $EXOMAT_SUCCEEDED, $EXOMAT_FAILED,
+        /// $EXOMAT_DURATION and $EXOMAT_ABORTED, e.g. for `notify-send`. A failing
+        /// command is only a warning.
+        #[arg(long, value_name = "CMD")]
+        notify_command: Option<String>,
+
+        /// Pin every run to the given cores, e.g. `0-7` or `0,2,4` (like `taskset -c`).
+        ///
+        /// All processes started by the run script inherit the affinity.
+        /// Only supported on Linux, elsewhere this only prints a warning.
+        #[arg(long, value_name = "CPUS", value_parser = str::parse::<CpuSet>)]
+        cpu_affinity: Option<CpuSet>,
+
+        /// Start every run with nice value N, from -20 (highest) to 19 (lowest priority).
+        ///
+        /// Keeps an interactive session responsive during long sweeps. Negative values
+        /// require privileges, without them the runs keep the default priority.
+        #[arg(long, value_name = "N", allow_negative_numbers = true,
+              value_parser = clap::value_parser!(i32).range(-20..=19))]
+        nice: Option<i32>,
+
+        /// Working directory of the run scripts.
+        ///
+        /// - run: the run directory
+        /// - source: the experiment source, so relative paths like `../build/foobar` keep working
+        ///
+        /// out_ files are always collected from the run directory, with `source` scripts have
+        /// to write them to $EXP_RUN_DIR (`exomat_out` does this already).
+        #[arg(long, default_value = "run", value_parser = str::parse::<WorkDir>)]
+        workdir: WorkDir,
     },
 
     /// Parses values from multiple output files into one file.
@@ -150,7 +586,245 @@ pub enum Commands {
     ///
     /// For correct parsing: location / name of your output files need to conform to
     /// this format: ./runs/run_*/out_*
-    MakeTable {},
+    MakeTable {
+        /// Combine the results of multiple series into `merged.csv` in the current directory.
+        ///
+        /// The table contains all columns of any series, columns a series does
+        /// not have are filled with `--na-value`. Rows are written series by series.
+        #[arg(
+            long,
+            value_name = "SERIES",
+            num_args = 1..,
+            conflicts_with_all = ["baseline", "pivot", "wide_per_rep", "aggregate"]
+        )]
+        merge: Vec<PathBuf>,
+
+        /// CSV table to compare the results against, e.g. from a previous `make-table`.
+        ///
+        /// Requires at least one `--gate`. Exits with an error if any gate fails.
+        #[arg(long, requires = "gate")]
+        baseline: Option<PathBuf>,
+
+        /// Regression rule in the format `KEY:[+-]LIMIT[%]`, checked against `--baseline`.
+        ///
+        /// Compares the mean of column KEY in this series against the mean in the
+        /// baseline. `+` (default) fails if the mean grew by more than LIMIT, `-`
+        /// fails if it shrunk by more than LIMIT. LIMIT is relative to the baseline
+        /// if it ends with `%`, otherwise absolute.
+        /// For example: `--gate latency:+10% --gate throughput:-5%`
+        ///
+        /// The verdict is written to `[series]_gate.csv`.
+        #[arg(long, requires = "baseline")]
+        gate: Vec<String>,
+
+        /// Additionally write a pivot table to `[series]_pivot.csv`.
+        ///
+        /// Every value of ROW becomes a row, every value of COL a column. Each cell
+        /// holds the values of VALUE recorded for this combination, aggregated
+        /// by `--agg`. All three may be output keys or environment variables.
+        /// For example: `--pivot THREADS SIZE runtime`
+        #[arg(long, num_args = 3, value_names = ["ROW", "COL", "VALUE"])]
+        pivot: Vec<String>,
+
+        /// How to aggregate multiple values in one cell of `--pivot`.
+        #[arg(
+            long,
+            requires = "pivot",
+            default_value = "mean",
+            value_parser = ["mean", "median", "min", "max", "count"]
+        )]
+        agg: String,
+
+        /// Additionally write a table with one row per environment to `[series]_wide.csv`.
+        ///
+        /// Every repetition gets its own columns, e.g. `latency_rep0`, `latency_rep1`.
+        /// Repetitions missing for an environment are filled with `--na-value`.
+        #[arg(long, default_value_t = false)]
+        wide_per_rep: bool,
+
+        /// Additionally write a table with one row per environment and statistics over
+        /// its repetitions to `[series]_aggregate.csv`.
+        ///
+        /// Every statistic (mean, std, min or max) becomes a column per numeric
+        /// output, e.g. `--aggregate mean,std` results in `runtime_mean` and
+        /// `runtime_std`. Non-numeric outputs are kept if they are the same in all
+        /// repetitions of an environment, and dropped otherwise.
+        #[arg(long, value_name = "STATS", value_delimiter = ',', value_parser = str::parse::<Statistic>)]
+        aggregate: Vec<Statistic>,
+
+        /// Value written for outputs that a run did not produce.
+        ///
+        /// Also used for empty cells of `--pivot`, `--wide-per-rep`, `--aggregate` and `--merge`.
+        #[arg(
+            long,
+            visible_alias = "na-string",
+            value_name = "STRING",
+            default_value = "NA"
+        )]
+        na_value: String,
+
+        /// Write empty cells for outputs that a run did not produce.
+        ///
+        /// Same as `--na-value ""`.
+        #[arg(long, default_value_t = false, conflicts_with = "na_value")]
+        na_empty: bool,
+
+        /// Flatten JSON objects in `out_*.json` files into one column per key.
+        ///
+        /// `out_result.json` containing `{"latency": 5, "cpu": {"cores": [1, 2]}}`
+        /// results in the columns `result.latency`, `result.cpu.cores.0` and
+        /// `result.cpu.cores.1`. Files that contain no JSON object are kept as is.
+        #[arg(long, default_value_t = false)]
+        explode_json: bool,
+
+        /// Number of nesting levels flattened by `--explode-json`.
+        ///
+        /// Values nested deeper are written as JSON.
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JSON_DEPTH, requires = "explode_json")]
+        json_depth: usize,
+
+        /// Also collect outputs that runs printed to stdout as marker lines.
+        ///
+        /// By default, a marker looks like `::exomat-output latency=5 unit=ms::`.
+        /// PATTERN changes this syntax, `{}` stands for the `key=value` pairs, e.g.
+        /// `--from-markers 'RESULT: {}'`. Values can be quoted like in a shell.
+        /// Keys printed multiple times (or also written as out_ file) get multiple
+        /// values.
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            num_args = 0..=1,
+            default_missing_value = DEFAULT_MARKER_PATTERN
+        )]
+        from_markers: Option<String>,
+
+        /// Keep the file extension in column names, e.g. `out_latency.txt` becomes `latency.txt`.
+        ///
+        /// By default, the extension is removed (`latency`). Outputs that end up
+        /// with the same column name within a run get a numbered suffix
+        /// (`latency_2`) and a warning.
+        #[arg(long, default_value_t = false)]
+        keep_extension: bool,
+
+        /// Removing the file extension from column names is the default now.
+        #[arg(
+            long,
+            default_value_t = false,
+            hide = true,
+            conflicts_with = "keep_extension"
+        )]
+        strip_extension: bool,
+
+        /// Case of the column names derived from outputs.
+        ///
+        /// - keep: use names as they are
+        /// - lower: convert names to lower case
+        /// - upper: convert names to upper case
+        #[arg(long, value_name = "CASE", default_value = "keep", value_parser = str::parse::<ColumnCase>)]
+        column_case: ColumnCase,
+
+        /// Field delimiter of all written tables, e.g. `;` or `tab` (also `\t`) for a TSV file.
+        ///
+        /// Has to be a single byte. Values containing the delimiter are quoted.
+        #[arg(long, value_name = "CHAR", default_value = ",", value_parser = parse_delimiter)]
+        delimiter: u8,
+
+        /// Add the environment variables of each run as columns (default).
+        ///
+        /// Outputs with the same name as a variable replace it.
+        #[arg(long, default_value_t = false, conflicts_with = "outputs_only")]
+        with_env: bool,
+
+        /// Only write outputs (out_ files and markers) to the table, no environment variables.
+        #[arg(long, default_value_t = false)]
+        outputs_only: bool,
+
+        /// Write `[series].csv` run by run, keeping only one run in memory at a time.
+        ///
+        /// For series with many runs or large outputs. Rows of a run with
+        /// multiple lines are written next to each other.
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["merge", "baseline", "pivot", "wide_per_rep", "aggregate"]
+        )]
+        streaming: bool,
+
+        /// Write `[series].csv` (or `merged.csv` with `--merge`) to stdout instead of a file.
+        ///
+        /// Log messages are printed to stderr then, so the table can be piped into
+        /// other tools.
+        #[arg(long, default_value_t = false)]
+        stdout: bool,
+
+        /// Also collect out_ files in subdirectories of a run.
+        ///
+        /// The directories become part of the column name, e.g.
+        /// `node0/out_power` results in the column `node0_power`.
+        #[arg(long, default_value_t = false)]
+        recursive: bool,
+    },
+
+    /// Prints an overview of an experiment series, without changing anything.
+    ///
+    /// Lists the number of runs (succeeded, failed and not executed), environments
+    /// and repetitions, and whether `make-table` has been run already.
+    Status {
+        /// Experiment series directory (or any directory inside of it).
+        ///
+        /// Uses the series containing the current directory by default.
+        #[clap()]
+        series: Option<PathBuf>,
+    },
+
+    /// Checks an experiment for problems, without running it.
+    ///
+    /// Reports all problems at once: a missing `.exomat_source`, a missing or
+    /// not executable run script in `template/`, no env files in `envs/`, and
+    /// invalid or reserved variable names. Exits with an error if there are any.
+    Validate {
+        /// Path to the experiment source folder.
+        ///
+        /// Uses the experiment containing the current directory by default.
+        #[clap()]
+        experiment: Option<PathBuf>,
+    },
+
+    /// Packs an experiment series into `[series].tar.gz` next to it.
+    ///
+    /// The archive contains all files of the series including marker files and
+    /// permissions, unpacking it results in the same series. Requires `tar`.
+    Archive {
+        /// Experiment series directory (or any directory inside of it).
+        ///
+        /// Uses the series containing the current directory by default.
+        #[clap()]
+        series: Option<PathBuf>,
+
+        /// Compression of the archive: gzip (`.tar.gz`) or zstd (`.tar.zst`,
+        /// requires `zstd`).
+        #[arg(long, value_name = "COMPRESSION", default_value = "gzip", value_parser = str::parse::<Compression>)]
+        compression: Compression,
+
+        /// Delete the series directory after it was archived.
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Deletes the series directories of the experiment containing the current directory.
+    ///
+    /// Only directories next to the experiment source folder that are named
+    /// `[experiment]-*` and contain `.exomat_series` are deleted. They are listed
+    /// first, deleting them requires `--yes`.
+    Clean {
+        /// Only list the series directories, without deleting anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Confirms deleting all listed series directories.
+        #[arg(long, conflicts_with = "dry_run")]
+        yes: bool,
+    },
 
     /// Generate exomat autocompletions
     ///
@@ -165,4 +839,76 @@ pub enum Commands {
         /// Tries to use current shell by default.
         shell: Option<Shell>,
     },
+
+    /// Print version information
+    ///
+    /// Same as `exomat --version`. With `--verbose`, also prints build information
+    /// (git commit, rustc, target, ...). Please include it in bug reports.
+    Version,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_fork::rusty_fork_test;
+
+    /// Parses `exomat run [args]` and returns output and repetitions
+    fn parse_run(args: &[&str]) -> (Option<PathBuf>, u64) {
+        let cli = Cli::try_parse_from(["exomat", "run", "Foo"].iter().chain(args)).unwrap();
+        match cli.subcommand {
+            Commands::Run {
+                output,
+                repetitions,
+                ..
+            } => (output, repetitions),
+            _ => panic!("parsed wrong subcommand"),
+        }
+    }
+
+    #[test]
+    fn run_ordered_conflicts_with_seed() {
+        let err =
+            Cli::try_parse_from(["exomat", "run", "Foo", "--ordered", "--seed", "1"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        assert!(Cli::try_parse_from(["exomat", "run", "Foo", "--ordered"]).is_ok());
+    }
+
+    #[test]
+    fn version_verbose() {
+        let cli = Cli::try_parse_from(["exomat", "version", "--verbose"]).unwrap();
+        assert!(matches!(cli.subcommand, Commands::Version));
+        assert!(cli.verbose.log_level_filter() > log::LevelFilter::Info);
+
+        let cli = Cli::try_parse_from(["exomat", "version"]).unwrap();
+        assert_eq!(cli.verbose.log_level_filter(), log::LevelFilter::Info);
+    }
+
+    rusty_fork_test! {
+        #[test]
+        fn run_defaults_without_env() {
+            std::env::remove_var("EXOMAT_OUTPUT");
+            std::env::remove_var("EXOMAT_REPETITIONS");
+
+            assert_eq!(parse_run(&[]), (None, 1));
+        }
+
+        #[test]
+        fn run_env_fallback() {
+            std::env::set_var("EXOMAT_OUTPUT", "from_env");
+            std::env::set_var("EXOMAT_REPETITIONS", "7");
+
+            assert_eq!(parse_run(&[]), (Some(PathBuf::from("from_env")), 7));
+        }
+
+        #[test]
+        fn run_cli_overrides_env() {
+            std::env::set_var("EXOMAT_OUTPUT", "from_env");
+            std::env::set_var("EXOMAT_REPETITIONS", "7");
+
+            assert_eq!(
+                parse_run(&["-o", "from_cli", "-r", "3"]),
+                (Some(PathBuf::from("from_cli")), 3)
+            );
+        }
+    }
 }
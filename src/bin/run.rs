@@ -2,13 +2,34 @@ use indicatif::MultiProgress;
 use std::path::PathBuf;
 
 use crate::Result;
-use exomat::experiment::{ExperimentSource, FileReader};
+use exomat::experiment::{ExperimentSource, FileReader, RunOptions};
+
+/// What `exomat run` does with the experiment
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunMode {
+    /// execute all runs
+    Full,
+    /// execute a single run in `dir`, or a temporary directory
+    Trial { dir: Option<PathBuf> },
+    /// only check the syntax of the run script
+    SyntaxCheck,
+    /// execute all runs twice and compare the outputs
+    VerifyReproducible { include_timing: bool },
+    /// only create the series and run directories
+    NoExec,
+    /// only print the order in which runs would be executed
+    DryRun,
+    /// execute the unfinished runs of an existing series
+    Resume { series: PathBuf },
+}
 
 pub fn main(
     experiment: PathBuf,
-    trial: bool,
+    mode: RunMode,
     output: Option<PathBuf>,
     repetitions: u64,
+    env_filter: Option<String>,
+    options: RunOptions,
     log_handler: MultiProgress,
 ) -> Result<()> {
     let mut src = ExperimentSource::parse(&experiment)?;
@@ -16,9 +37,31 @@ pub fn main(
         &experiment,
         repetitions,
     ));
+    if let Some(pattern) = env_filter {
+        src.filter_envs(&pattern)?;
+    }
 
-    match trial {
-        false => exomat::harness::run::experiment(&src, output, log_handler, false),
-        true => exomat::harness::run::trial(&src, log_handler),
+    match mode {
+        RunMode::Full => {
+            exomat::harness::run::experiment(&src, output, log_handler, false, &options)
+        }
+        RunMode::Trial { dir } => {
+            exomat::harness::run::trial(&src, dir.as_deref(), log_handler, &options)
+        }
+        RunMode::SyntaxCheck => exomat::harness::run::syntax_check(&src),
+        RunMode::NoExec => exomat::harness::run::provision(&src, output),
+        RunMode::DryRun => exomat::harness::run::dry_run(&src, &options),
+        RunMode::Resume { series } => {
+            exomat::harness::run::resume(&src, &series, log_handler, &options)
+        }
+        RunMode::VerifyReproducible { include_timing } => {
+            exomat::harness::run::verify_reproducible(
+                &src,
+                output,
+                log_handler,
+                &options,
+                include_timing,
+            )
+        }
     }
 }
@@ -0,0 +1,67 @@
+use crate::Result;
+use exomat::harness::skeleton::custom_template_dir;
+
+/// Prints the version of exomat.
+///
+/// With `verbose`, also prints build information that is useful for bug reports.
+pub fn main(verbose: bool) -> Result<()> {
+    match verbose {
+        false => println!("exomat {}", env!("CARGO_PKG_VERSION")),
+        true => println!("{}", verbose_info()),
+    }
+
+    Ok(())
+}
+
+/// Returns the version together with build and runtime information.
+fn verbose_info() -> String {
+    let executable = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "unknown".to_string());
+    // same lookup as `exomat skeleton`
+    let template = custom_template_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "built-in".to_string());
+
+    [
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ("git commit", env!("EXOMAT_GIT_DESCRIBE").to_string()),
+        ("rustc", env!("EXOMAT_RUSTC_VERSION").to_string()),
+        ("target", env!("EXOMAT_TARGET").to_string()),
+        ("executable", executable),
+        ("skeleton template", template),
+    ]
+    .iter()
+    .map(|(key, value)| format!("{key}: {value}"))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exomat::harness::skeleton::CUSTOM_TEMPLATE_DIR;
+    use rusty_fork::rusty_fork_test;
+    use tempfile::TempDir;
+
+    rusty_fork_test! {
+        #[test]
+        fn verbose_version() {
+            let home = TempDir::new().unwrap();
+            std::env::set_var("HOME", home.path());
+
+            let info = verbose_info();
+            assert!(info.starts_with(&format!("version: {}\n", env!("CARGO_PKG_VERSION"))));
+            assert!(info.contains("\ngit commit: "));
+            assert!(info.contains("\nrustc: "));
+            assert!(info.contains("\ntarget: "));
+            assert!(info.ends_with("\nskeleton template: built-in"));
+
+            // the custom template of `exomat skeleton` is reported if it exists
+            let custom = home.path().join(CUSTOM_TEMPLATE_DIR);
+            std::fs::create_dir_all(&custom).unwrap();
+            let info = verbose_info();
+            assert!(info.ends_with(&format!("\nskeleton template: {}", custom.display())));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Outputs of previously executed Experiment Runs, that can be reused instead of
+//! executing a run again
+
+use log::debug;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::helper::{archivist::canonicalize, errors::Result, fs_names::*};
+
+/// Index of all Experiment Runs below a directory, by the hash of their environment
+/// (see `ExperimentRun::env_hash()`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunCache {
+    runs: HashMap<String, PathBuf>,
+}
+
+impl RunCache {
+    /// Searches `cache_dir` recursively for Experiment Runs, skipping everything
+    /// inside of `exclude` (e.g. the series that is about to be executed).
+    ///
+    /// Runs without [RUN_ENV_HASH] (created by older exomat versions) and warmup
+    /// runs are ignored.
+    /// If multiple runs have the same hash, the first one found is used.
+    ///
+    /// ## Errors
+    /// - Returns a `ReaderError` if `cache_dir` does not exist
+    /// - Returns an `IoError` if a directory could not be read
+    pub fn index(cache_dir: &Path, exclude: &Path) -> Result<Self> {
+        let mut cache = RunCache::default();
+        let exclude = canonicalize(exclude).unwrap_or_else(|_| exclude.to_path_buf());
+        cache.index_dir(&canonicalize(cache_dir)?, &exclude)?;
+
+        debug!(
+            "found {} cached runs in {}",
+            cache.runs.len(),
+            cache_dir.display()
+        );
+        Ok(cache)
+    }
+
+    /// Returns the directory of a cached run with `env_hash`, if there is any.
+    pub fn get(&self, env_hash: &str) -> Option<&PathBuf> {
+        self.runs.get(env_hash)
+    }
+
+    /// Number of runs in this cache.
+    pub fn len(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Returns `true` if the cache does not contain any runs.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    fn index_dir(&mut self, dir: &Path, exclude: &Path) -> Result<()> {
+        if dir.starts_with(exclude) {
+            return Ok(());
+        }
+
+        if dir.join(MARKER_RUN).is_file() {
+            // outputs of warmup runs are not representative
+            let warmup = dir
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(SERIES_WARMUP_PREFIX));
+            if warmup {
+                return Ok(());
+            }
+
+            if let Ok(hash) = std::fs::read_to_string(dir.join(RUN_ENV_HASH)) {
+                self.runs
+                    .entry(hash.trim().to_string())
+                    .or_insert(dir.to_path_buf());
+            }
+            return Ok(());
+        }
+
+        let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        subdirs.sort();
+
+        for subdir in subdirs {
+            self.index_dir(&subdir, exclude)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::env::Environment;
+    use tempfile::TempDir;
+
+    #[test]
+    fn env_hash_ignores_order() {
+        let env = |vars: &[(&str, &str)]| {
+            Environment::from_env_list(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+
+        let hash = env(&[("A", "1"), ("B", "2")]).content_hash();
+        assert_eq!(hash, env(&[("B", "2"), ("A", "1")]).content_hash());
+        assert_eq!(hash.len(), 16);
+        assert_ne!(hash, env(&[("A", "1"), ("B", "3")]).content_hash());
+        assert_ne!(
+            env(&[("A", "BC")]).content_hash(),
+            env(&[("AB", "C")]).content_hash()
+        );
+    }
+
+    #[test]
+    fn index_cached_runs() {
+        let tmpdir = TempDir::new().unwrap();
+        for (run, hash) in [
+            ("old/runs/run_0_rep0", Some("aaaa")),
+            ("old/runs/run_1_rep0", None),
+            ("new/runs/run_0_rep0", Some("bbbb")),
+        ] {
+            let dir = tmpdir.path().join(run);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join(MARKER_RUN), "").unwrap();
+            if let Some(hash) = hash {
+                std::fs::write(dir.join(RUN_ENV_HASH), hash).unwrap();
+            }
+        }
+
+        let cache = RunCache::index(tmpdir.path(), &tmpdir.path().join("new")).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("aaaa").unwrap().ends_with("old/runs/run_0_rep0"));
+        assert!(cache.get("bbbb").is_none());
+
+        assert!(RunCache::index(&tmpdir.path().join("missing"), tmpdir.path()).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Progress of an Experiment Series that is written to disk while it is running
+
+use chrono::Local;
+use std::path::Path;
+
+use crate::experiment::experiment_run::RunStatus;
+use crate::helper::{archivist::write_atomic, errors::Result, fs_names::SERIES_CHECKPOINT};
+
+/// Records which Experiment Runs of an Experiment Series have finished.
+///
+/// Written to `[series]/.exomat_checkpoint` every `interval` finished runs, so
+/// the progress of long running experiments survives a crash of the exomat.
+/// The file lists the number of runs, followed by one line per finished run:
+/// ```notest
+/// total: 4
+/// finished: 2
+/// updated: 2025-01-01T12:00:00+01:00
+/// run_0_rep0: Success
+/// run_1_rep0: Fail("exit status: 1")
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    total: usize,
+    interval: u64,
+    finished: Vec<(String, RunStatus)>,
+}
+
+impl Checkpoint {
+    /// Creates an empty Checkpoint for `total` runs, that is written every
+    /// `interval` finished runs. An `interval` of 0 never writes anything.
+    pub fn new(total: usize, interval: u64) -> Self {
+        Checkpoint {
+            total,
+            interval,
+            finished: vec![],
+        }
+    }
+
+    /// Records that the run called `run_name` finished with `status`.
+    ///
+    /// Writes the checkpoint into `series_dir` if this completes an interval.
+    ///
+    /// ## Errors
+    /// - Returns an `IoError` if the checkpoint could not be written
+    pub fn finish_run(
+        &mut self,
+        run_name: &str,
+        status: RunStatus,
+        series_dir: &Path,
+    ) -> Result<()> {
+        self.finished.push((run_name.to_string(), status));
+
+        match self.interval > 0 && (self.finished.len() as u64).is_multiple_of(self.interval) {
+            true => self.write(series_dir),
+            false => Ok(()),
+        }
+    }
+
+    /// Writes the checkpoint to `series_dir/.exomat_checkpoint`.
+    ///
+    /// The file is replaced atomically, so it is never left half written.
+    /// Does nothing if this checkpoint is never written (`interval` is 0).
+    ///
+    /// ## Errors
+    /// - Returns an `IoError` if the checkpoint could not be written
+    pub fn write(&self, series_dir: &Path) -> Result<()> {
+        match self.interval {
+            0 => Ok(()),
+            _ => write_atomic(&series_dir.join(SERIES_CHECKPOINT), &self.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "total: {}", self.total)?;
+        writeln!(f, "finished: {}", self.finished.len())?;
+        writeln!(f, "updated: {}", Local::now().to_rfc3339())?;
+
+        for (run_name, status) in &self.finished {
+            writeln!(f, "{run_name}: {status:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn checkpoint_written_every_interval() {
+        let tmpdir = TempDir::new().unwrap();
+        let file = tmpdir.path().join(SERIES_CHECKPOINT);
+
+        let mut checkpoint = Checkpoint::new(3, 2);
+        checkpoint
+            .finish_run("run_0_rep0", RunStatus::Success, tmpdir.path())
+            .unwrap();
+        assert!(!file.exists());
+
+        checkpoint
+            .finish_run(
+                "run_1_rep0",
+                RunStatus::Fail("exit status: 1".to_string()),
+                tmpdir.path(),
+            )
+            .unwrap();
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert!(content.starts_with("total: 3\nfinished: 2\n"));
+        assert!(content.contains("run_0_rep0: Success\n"));
+        assert!(content.contains("run_1_rep0: Fail(\"exit status: 1\")\n"));
+
+        // never written without interval
+        let tmpdir = TempDir::new().unwrap();
+        let mut checkpoint = Checkpoint::new(1, 0);
+        checkpoint
+            .finish_run("run_0_rep0", RunStatus::Success, tmpdir.path())
+            .unwrap();
+        checkpoint.write(tmpdir.path()).unwrap();
+        assert!(!tmpdir.path().join(SERIES_CHECKPOINT).exists());
+    }
+}
@@ -1,18 +1,62 @@
-use super::experiment_traits::{FileReader, FileWriter, Runner};
-use crate::experiment::out_file::{Observation, OutFile, OutList};
+use super::experiment_traits::{read_dir_entries, FileReader, FileWriter, Runner};
+use crate::experiment::markers::parse_markers;
+use crate::experiment::out_file::{Observation, OutFile, OutList, OUTPATH_PREFIX, OUT_PREFIX};
+use crate::experiment::run_options::WorkDir;
+use crate::experiment::{RunOptions, TableOptions};
 use crate::harness::env::{Environment, ExomatEnvironment};
 
 use crate::helper::{
-    archivist::{create_harness_dir, create_harness_file},
+    affinity::pin_command,
+    archivist::{canonicalize, create_harness_dir, create_harness_file},
     errors::{Error, Result},
     fs_names::*,
+    priority::nice_command,
+    shebang::posix_shell,
+    shell_words::split,
 };
 
 use log::warn;
-use log::{debug, error, info, trace};
+use log::{debug, error, info, log, trace, Level};
 use std::collections::HashMap;
+use std::io::Read;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Shell helper functions, that are available to every run script via `$EXOMAT_LIB`
+const RUN_LIB_CONTENT: &str = include_str!("../harness/exomat_lib.sh");
+
+/// Variables pointing to the private temp directory of a run, see `RunOptions::isolate_tmp`
+const TMP_ENV_VARS: [&str; 3] = ["TMPDIR", "TMP", "TEMP"];
+
+/// out_ file written into the directory of a run that was killed after `RunOptions::timeout`
+pub const TIMEOUT_OUT_FILE: &str = "out_exomat_timeout";
+
+/// out_ file containing the exit code of every run (128 + signal if it was killed)
+pub const EXIT_CODE_OUT_FILE: &str = "out_exit_code";
+
+/// Replaces the values of redacted variables in [RUN_COMMAND_FILE]
+const REDACTED: &str = "<redacted>";
+
+/// out_ file containing the wall-clock time (in seconds) it took to execute a run
+pub const DURATION_OUT_FILE: &str = "out_exomat_duration";
+
+/// Files written by exomat when creating a run (besides the run script, see
+/// `is_run_file()`), everything else is a result of the run
+const EXOMAT_RUN_FILES: [&str; 5] = [
+    MARKER_RUN,
+    RUN_ENV_FILE,
+    RUN_LIB_FILE,
+    RUN_ENV_HASH,
+    RUN_COMMAND_FILE,
+];
+
+/// Interval in which a run with a timeout is checked for completion
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Describes the current state of an Experiment Run
 #[derive(Clone, Debug, PartialEq)]
@@ -23,6 +67,8 @@ pub enum RunStatus {
     Success,
     /// Run produced an error
     Fail(String),
+    /// Run did not finish within `RunOptions::timeout` and was killed
+    Timeout,
 }
 
 /// Container for an Experiment Run
@@ -35,12 +81,14 @@ pub enum RunStatus {
 #[derive(Clone, Debug, PartialEq)]
 pub struct ExperimentRun {
     run_sh: String,
+    run_file: String,
     run_name: String,
     env: Environment,
     exomat_env: ExomatEnvironment,
     out_files: OutList,
     status: RunStatus,
     location: Option<PathBuf>,
+    options: RunOptions,
 }
 
 impl ExperimentRun {
@@ -48,12 +96,14 @@ impl ExperimentRun {
     ///
     /// The following values will be set:
     /// - `run_sh`: `run_sh`
+    /// - `run_file`: RUN_RUN_FILE
     /// - `run_name`: built from `environment.0`, `exomat_environment.repetition` and `rep_format_length`
     /// - `env`: `environment.1`
     /// - `exomat_env`: `exomat_environment`
     /// - `out_files`: None
     /// - `status`: RunStatus::Unknown
     /// - `location`: None
+    /// - `options`: default RunOptions
     ///
     /// ## Panics
     /// - panics if `rep_format_length` is <= 0
@@ -73,7 +123,7 @@ impl ExperimentRun {
             .any(|k| environment.1.contains_env_var(k)));
 
         let dir_name = format!(
-            "run_{}_rep{:0length$}",
+            "{SERIES_RUN_PREFIX}{}_rep{:0length$}",
             environment.0.file_prefix().unwrap().display(),
             exomat_environment.repetition,
             length = rep_format_length
@@ -82,12 +132,14 @@ impl ExperimentRun {
         trace!("Created new Experiment Run \"{dir_name}\"");
         Self {
             run_sh: run_sh.to_string(),
+            run_file: RUN_RUN_FILE.to_string(),
             run_name: dir_name,
             env: environment.1.clone(),
             exomat_env: exomat_environment.clone(),
             out_files: OutList::new(),
             status: RunStatus::Unknown,
             location: None,
+            options: RunOptions::default(),
         }
     }
 
@@ -106,6 +158,17 @@ impl ExperimentRun {
         &self.run_name
     }
 
+    /// Turns this Experiment Run into a warmup run, that is executed like any
+    /// other run but named [SERIES_WARMUP_PREFIX] instead of [SERIES_RUN_PREFIX],
+    /// so its outputs are not collected.
+    ///
+    /// Has to be called before the run is written to disk.
+    pub fn set_warmup(&mut self) {
+        if let Some(name) = self.run_name.strip_prefix(SERIES_RUN_PREFIX) {
+            self.run_name = format!("{SERIES_WARMUP_PREFIX}{name}");
+        }
+    }
+
     /// Returns the current repetition
     pub fn repetition(&self) -> &u64 {
         &self.exomat_env.repetition
@@ -134,6 +197,21 @@ impl ExperimentRun {
         &self.out_files
     }
 
+    /// Returns the hash of the environment written to [RUN_ENV_FILE], including
+    /// the serializable exomat envs (see `Environment::content_hash()`).
+    ///
+    /// Runs with the same hash are executed with the same variables.
+    ///
+    /// The position of the run ([ExomatEnvironment::ORDER_ENV_VARS]) is ignored,
+    /// so the hash does not depend on the order of the runs.
+    pub fn env_hash(&self) -> String {
+        let mut envs = self.serializable_envs();
+        for var in ExomatEnvironment::ORDER_ENV_VARS {
+            envs.remove_env(var);
+        }
+        envs.content_hash()
+    }
+
     // ========================= setter ========================================
 
     /// Inserts `new_out` at the end of `self.out_files`.
@@ -143,8 +221,79 @@ impl ExperimentRun {
         self.out_files.push(new_out);
     }
 
+    /// Sets the position of this run in the execution order of its series,
+    /// exposed to the run script as `RUN_INDEX` and `RUN_TOTAL`.
+    pub fn set_run_position(&mut self, index: usize, total: usize) {
+        self.exomat_env.run_index = index;
+        self.exomat_env.run_total = total;
+    }
+
+    /// Replaces the options used when executing this run.
+    pub fn set_options(&mut self, options: RunOptions) {
+        self.options = options;
+    }
+
+    /// Replaces the file name of the run script, e.g. `run.py` instead of [RUN_RUN_FILE].
+    pub fn set_run_file_name(&mut self, name: &str) {
+        self.run_file = name.to_string();
+    }
+
+    /// Copies the outputs of the already executed run in `cached_run_dir` into the
+    /// directory of this run, instead of executing it. Sets the status to Success.
+    ///
+    /// Every file of `cached_run_dir` is copied, except for the files exomat
+    /// writes itself when creating a run.
+    ///
+    /// ## Errors
+    /// - Returns a `HarnessRunError` if the run has not been serialized yet
+    /// - Returns an `IoError` if a file could not be copied
+    pub fn reuse_outputs(&mut self, cached_run_dir: &Path) -> Result<()> {
+        let run_folder = self
+            .location
+            .clone()
+            .ok_or_else(|| Error::HarnessRunError {
+                experiment: self.run_name.clone(),
+                err: "Experiment Run has not been written to disk yet".to_string(),
+            })?;
+
+        for entry in std::fs::read_dir(cached_run_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if !entry.path().is_file() || is_exomat_run_file(&name) {
+                continue;
+            }
+
+            std::fs::copy(entry.path(), run_folder.join(name))?;
+        }
+
+        self.status = RunStatus::Success;
+        Ok(())
+    }
+
+    /// Returns `true` if `exp_run_dir` contains a run that has been executed.
+    ///
+    /// All run directories are created before the first run is executed, so a
+    /// run only counts as executed if its directory contains [MARKER_RUN], a run
+    /// script (see `find_run_file()`), [RUN_STDOUT_LOG] and [RUN_STDERR_LOG].
+    /// The logs are written once the run has finished, so a run that was
+    /// interrupted after writing some of its results is not complete.
+    pub fn is_complete(exp_run_dir: &Path) -> bool {
+        exp_run_dir.join(MARKER_RUN).is_file()
+            && find_run_file(exp_run_dir).is_some()
+            && exp_run_dir.join(RUN_STDOUT_LOG).is_file()
+            && exp_run_dir.join(RUN_STDERR_LOG).is_file()
+    }
+
     // ========================= helper ========================================
 
+    /// Returns the variables written to [RUN_ENV_FILE]: the variables of this run
+    /// and the serializable exomat envs.
+    fn serializable_envs(&self) -> Environment {
+        let mut serializable_envs = self.env.clone();
+        serializable_envs.extend_envs(&self.exomat_env.to_environment_serializable());
+        serializable_envs
+    }
+
     /// Generates an ExperimentRun from `outlist`.
     ///
     /// Sets an empty Environemnt.
@@ -152,15 +301,41 @@ impl ExperimentRun {
     pub fn from_out_list_unchecked(outlist: &OutList) -> Self {
         ExperimentRun {
             run_sh: String::new(),
+            run_file: RUN_RUN_FILE.to_string(),
             run_name: TEST_RUN_REP_DIR0.to_string(),
             env: Environment::new(),
-            exomat_env: ExomatEnvironment {
-                exp_src_dir: PathBuf::new(),
-                repetition: 1,
-            },
+            exomat_env: ExomatEnvironment::new(&PathBuf::new(), 1),
             out_files: outlist.clone(),
             status: RunStatus::Unknown,
             location: None,
+            options: RunOptions::default(),
+        }
+    }
+
+    /// Builds the command that executes the run script at `run_file`.
+    ///
+    /// The script is executed directly, unless shell tracing is requested and
+    /// the script uses a POSIX shell. Then it is passed to that shell with `-x`.
+    /// Scripts with any other interpreter can't be traced, which is only warned about.
+    fn build_command(&self, run_file: &Path) -> Command {
+        if !self.options.shell_trace {
+            return Command::new(run_file);
+        }
+
+        match posix_shell(&self.run_sh) {
+            Some(shell) => {
+                debug!("tracing {} with {shell} -x", self.run_name);
+                let mut command = Command::new(shell);
+                command.arg("-x").arg(run_file);
+                command
+            }
+            None => {
+                warn!(
+                    "{}: shell tracing is only supported for POSIX shell scripts, running without it",
+                    self.run_name
+                );
+                Command::new(run_file)
+            }
         }
     }
 
@@ -200,13 +375,47 @@ impl ExperimentRun {
         Ok(observation)
     }
 
+    /// Creates a private temp directory for this run.
+    ///
+    /// The directory is removed once the returned `TempDir` is dropped.
+    ///
+    /// ## Errors
+    /// - Returns a `HarnessRunError` if the directory could not be created
+    fn create_tmp_dir(&self, exp_name: &str) -> Result<TempDir> {
+        tempfile::Builder::new()
+            .prefix(&format!("exomat_{}_", self.run_name))
+            .tempdir()
+            .map_err(|e| Error::HarnessRunError {
+                experiment: exp_name.to_string(),
+                err: format!("Cannot create temp dir for {}: {e}", self.run_name),
+            })
+    }
+
+    /// Level for log messages that are expected for every successful run.
+    ///
+    /// Demoted to debug if `quiet_runs` is set, so only anomalies show up on the console.
+    fn routine_log_level(&self) -> Level {
+        match self.options.quiet_runs {
+            true => Level::Debug,
+            false => Level::Info,
+        }
+    }
+
+    /// Returns `true` if a run that exited with `code` after `attempt` retries
+    /// is to be executed again, see `RunOptions::retries`.
+    fn should_retry(&self, code: i32, attempt: usize) -> bool {
+        attempt < self.options.retries
+            && (self.options.retry_on_codes.is_empty()
+                || self.options.retry_on_codes.contains(&code))
+    }
+
     /// Produce log output based on exit_status and err_log content.
     ///
     /// - exit_status:
-    ///    - **success**  : log info
+    ///    - **success**  : log info (debug with `quiet_runs`)
     ///    - **failed**   : log error (don't evaluate err_log after)
     /// - err_log:
-    ///    - **empty**    : log info
+    ///    - **empty**    : log info (debug with `quiet_runs`)
     ///    - **not empty**: log warning
     ///
     /// ## Errors
@@ -217,11 +426,16 @@ impl ExperimentRun {
         exit_status: std::process::ExitStatus,
         err_log: &str,
     ) -> Result<()> {
+        let routine = self.routine_log_level();
+
         if exit_status.success() {
-            info!("{run_name} finished successfully with {exit_status}");
+            log!(
+                routine,
+                "{run_name} finished successfully with {exit_status}"
+            );
 
             if err_log.is_empty() {
-                info!("{run_name} did not produce stderr output");
+                log!(routine, "{run_name} did not produce stderr output");
             } else {
                 warn!("{run_name} produced stderr output");
             }
@@ -243,12 +457,22 @@ impl ExperimentRun {
 impl Runner for ExperimentRun {
     type Item = (String, String);
 
-    /// Executes [RUN_RUN_FILE] script found in `run_folder`.
+    /// Executes the run script (e.g. [RUN_RUN_FILE]) found in `run_folder`.
     ///
     /// 1. read envs from `run_folder/RUN_ENV_FILE`
     /// 2. add `exomat_envs` (overwrites envs with the same name)
-    /// 3. run `run_folder/RUN_RUN_FILE` with these envs
-    /// 4. log run results
+    /// 3. run the run script with these envs, killing it after
+    ///    `options.timeout` (this writes [TIMEOUT_OUT_FILE] instead of failing),
+    ///    the stdout and stderr of the run are written to [RUN_STDOUT_LOG] and
+    ///    [RUN_STDERR_LOG] (and returned, for the logs of the series). The command is
+    ///    written to [RUN_COMMAND_FILE] before if `options.capture_command` is set.
+    ///    A failed run is
+    ///    executed again up to `options.retries` times, see `should_retry()`.
+    ///    The out_ files of the failed attempt are removed before.
+    /// 4. write the exit code of the run to [EXIT_CODE_OUT_FILE] and the time
+    ///    it took to [DURATION_OUT_FILE]
+    /// 5. log run results, a failed run does not cause an error if
+    ///    `options.keep_going` is set
     ///     - Appends any stderr/stdout output into their respective log file in the
     ///       parent series directory of `run_folder`.
     ///     - Exomat output will **not** automatically be duplicated to the log file
@@ -257,7 +481,7 @@ impl Runner for ExperimentRun {
     /// ## Errors and Panics
     /// - Returns a `HarnessRunError` if the run has not been serialized yet
     /// - Returns a `HarnessRunError` if the script could not be executed
-    /// - Returns a `HarnessRunError` if there is no [RUN_RUN_FILE] in `run_folder`
+    /// - Returns a `HarnessRunError` if there is no run script in `run_folder`
     /// - Returns a `HarnessRunError` if there is no [RUN_ENV_FILE] in `run_folder`
     fn execute(&mut self, exp_name: &str) -> Result<Self::Item> {
         trace!("{exp_name}: Checking run directory {}", self.run_name);
@@ -273,7 +497,10 @@ impl Runner for ExperimentRun {
             })?;
 
         debug!("checking if all files exist in run");
-        for file in REQUIRED_RUN_FILES {
+        for file in REQUIRED_RUN_FILES
+            .into_iter()
+            .chain([self.run_file.as_str()])
+        {
             if !run_folder.join(file).is_file() {
                 return Err(Error::HarnessRunError {
                     experiment: exp_name.to_string(),
@@ -283,35 +510,138 @@ impl Runner for ExperimentRun {
         }
 
         debug!("reading run environment");
-        let mut all_envs = self.exomat_env.to_environment_full();
+        let mut all_envs = self.exomat_env.to_environment_full()?;
         all_envs.extend_envs(&self.env);
+        all_envs.add_env(
+            ExomatEnvironment::LIB_ENV_VAR.to_string(),
+            run_folder.join(RUN_LIB_FILE).display().to_string(),
+        );
+        all_envs.add_env(
+            ExomatEnvironment::RUN_DIR_ENV_VAR.to_string(),
+            run_folder.display().to_string(),
+        );
+
+        let args = match all_envs.get_env_val(ExomatEnvironment::ARGS_ENV_VAR) {
+            Some(line) => split(line).map_err(|e| Error::HarnessRunError {
+                experiment: exp_name.to_string(),
+                err: format!("Cannot parse {}: {e}", ExomatEnvironment::ARGS_ENV_VAR),
+            })?,
+            None => vec![],
+        };
+
+        let tmp_dir = match self.options.isolate_tmp {
+            true => Some(self.create_tmp_dir(exp_name)?),
+            false => None,
+        };
+        if let Some(dir) = &tmp_dir {
+            for var in TMP_ENV_VARS {
+                all_envs.add_env(var.to_string(), dir.path().display().to_string());
+            }
+        }
 
         trace!("{exp_name}: Starting execution of {}", self.run_name);
 
+        let workdir = match self.options.workdir {
+            WorkDir::Run => run_folder.clone(),
+            WorkDir::Source => canonicalize(&self.exomat_env.exp_src_dir)?,
+        };
+
         // execute command with envs and collect any output in child
-        let run = Command::new(run_folder.join(RUN_RUN_FILE))
+        let mut command = self.build_command(&run_folder.join(&self.run_file));
+        command
+            .args(args)
             .stderr(Stdio::piped())
             .stdout(Stdio::piped())
             .envs(all_envs.to_env_map())
-            .current_dir(&run_folder)
-            .output()
-            .map_err(|e| Error::HarnessRunError {
-                experiment: exp_name.to_string(),
-                err: e.to_string(),
-            })?;
+            .current_dir(&workdir);
+        if let Some(cpus) = &self.options.cpu_affinity {
+            pin_command(&mut command, cpus);
+        }
+        if let Some(nice) = self.options.nice {
+            nice_command(&mut command, nice);
+        }
+
+        if self.options.capture_command {
+            let metadata = command_metadata(&command, &self.options.redact);
+            std::fs::write(run_folder.join(RUN_COMMAND_FILE), metadata)?;
+        }
+
+        let mut attempt = 0;
+        let (run, timed_out, duration) =
+            loop {
+                let start = Instant::now();
+                let (run, timed_out) = output_in_group(&mut command, self.options.timeout)
+                    .map_err(|e| Error::HarnessRunError {
+                        experiment: exp_name.to_string(),
+                        err: e.to_string(),
+                    })?;
+                let duration = start.elapsed();
+
+                let code = exit_code(run.status);
+                if timed_out || run.status.success() || !self.should_retry(code, attempt) {
+                    if attempt > 0 && run.status.success() {
+                        info!("{} succeeded after {} attempts", self.run_name, attempt + 1);
+                    }
+                    break (run, timed_out, duration);
+                }
+
+                attempt += 1;
+                warn!(
+                    "{} exited with code {code}, retrying ({attempt}/{})",
+                    self.run_name, self.options.retries
+                );
+                // outputs of the failed attempt must not end up in the results
+                remove_outputs(&run_folder)?;
+            };
 
         trace!("{exp_name}: Finished run {}", run_folder.display());
         debug!("reading logs");
         let stdout = String::from_utf8_lossy(&run.stdout).to_string();
+        // kept per run, so outputs printed as markers can be collected later
+        std::fs::write(run_folder.join(RUN_STDOUT_LOG), &run.stdout)?;
+        std::fs::write(run_folder.join(RUN_STDERR_LOG), &run.stderr)?;
         let stderr = String::from_utf8_lossy(&run.stderr).to_string();
+        std::fs::write(
+            run_folder.join(EXIT_CODE_OUT_FILE),
+            exit_code(run.status).to_string(),
+        )?;
+
+        // warn if the measured duration replaces an output of the run
+        let duration_file = run_folder.join(DURATION_OUT_FILE);
+        if duration_file.exists() {
+            warn!(
+                "in {}: {DURATION_OUT_FILE} is written by exomat, overwriting the one created by {}",
+                self.run_name, self.run_file
+            );
+        }
+        std::fs::write(duration_file, format!("{:.3}", duration.as_secs_f64()))?;
 
         debug!("updating run status");
-        match run.status.success() {
-            true => self.status = RunStatus::Success,
-            false => self.status = RunStatus::Fail(run.status.to_string()),
+        match (timed_out, run.status.success()) {
+            (true, _) => self.status = RunStatus::Timeout,
+            (false, true) => self.status = RunStatus::Success,
+            (false, false) => self.status = RunStatus::Fail(run.status.to_string()),
         };
 
-        self.log_run_result(
+        // keep temp dir of failed runs for debugging
+        if let Some(dir) = tmp_dir {
+            if self.options.keep_tmp || !run.status.success() {
+                info!("{}: kept temp dir {}", self.run_name, dir.keep().display());
+            }
+        }
+
+        // a timeout does not abort the series, it is recorded as output instead
+        if timed_out {
+            warn!(
+                "{} did not finish within {}s and was killed",
+                self.run_name,
+                self.options.timeout.unwrap_or_default().as_secs_f64()
+            );
+            std::fs::write(run_folder.join(TIMEOUT_OUT_FILE), "true")?;
+            return Ok((stdout, stderr));
+        }
+
+        let result = self.log_run_result(
             &run_folder
                 .file_stem()
                 .expect("run folder name inaccessable")
@@ -319,7 +649,13 @@ impl Runner for ExperimentRun {
                 .to_string(),
             run.status,
             &stderr,
-        )?;
+        );
+
+        // the failure has already been logged and its exit code recorded
+        match result {
+            Err(_) if self.options.keep_going => (),
+            result => result?,
+        }
 
         Ok((stdout, stderr))
     }
@@ -342,8 +678,9 @@ impl FileWriter for ExperimentRun {
     ///     |-> ...
     ///     \-> dir
     ///       |-> .exomat_run
-    ///       |-> RUN_RUN_FILE     (copy of SRC_RUN_FILE)
-    ///       \-> RUN_ENV_FILE     (copy of env_file)
+    ///       |-> self.run_file (copy of the run script of the source, e.g. SRC_RUN_FILE)
+    ///       |-> RUN_ENV_FILE     (copy of env_file)
+    ///       \-> RUN_ENV_HASH     (hash of RUN_ENV_FILE, see `env_hash()`)
     /// ```
     ///
     /// If no Errors occured, the path to the created experiment run folder will be returned.
@@ -356,14 +693,17 @@ impl FileWriter for ExperimentRun {
         create_harness_dir(&exp_run_dir.to_path_buf())?;
         create_harness_file(&exp_run_dir.join(MARKER_RUN))?;
 
-        debug!("copy ruh.sh and [env].env to runs_dir");
-        let mut run_file = self.create_executable(&exp_run_dir.join(RUN_RUN_FILE))?;
-        self.write_to_file(&mut run_file, &self.run_sh.as_bytes())?;
+        debug!("copy {} and [env].env to runs_dir", self.run_file);
+        let mut run_file = self.create_executable(&exp_run_dir.join(&self.run_file))?;
+        self.write_to_file(&mut run_file, self.run_sh.as_bytes())?;
+
+        debug!("write shell helper functions to runs_dir");
+        std::fs::write(exp_run_dir.join(RUN_LIB_FILE), RUN_LIB_CONTENT)?;
 
         debug!("write envs to file (including exomat envs)");
-        let mut serializable_envs = self.env.clone();
-        serializable_envs.extend_envs(&self.exomat_env.to_environment_serializable());
-        serializable_envs.to_file(&exp_run_dir.join(RUN_ENV_FILE))?;
+        self.serializable_envs()
+            .to_file(&exp_run_dir.join(RUN_ENV_FILE))?;
+        std::fs::write(exp_run_dir.join(RUN_ENV_HASH), self.env_hash())?;
 
         trace!("Persisted Experiment Run at {}", exp_run_dir.display());
         debug!("update run location");
@@ -386,7 +726,7 @@ impl std::fmt::Display for ExperimentRun {
                 false => "set"
             },
             self.env,
-            self.exomat_env.to_environment_full(),
+            self.exomat_env,
             self.status,
             self.out_files,
         )
@@ -416,18 +756,46 @@ impl FileReader for ExperimentRun {
     ///
     /// This function might **Panic** if reading/writing failed.
     fn parse(exp_run_dir: &Path) -> Result<Self::Item> {
+        Self::parse_with(exp_run_dir, &TableOptions::default())
+    }
+}
+
+impl ExperimentRun {
+    /// Parses an Experiment Run directory into an ExperimentRun, see `parse()`.
+    ///
+    /// JSON objects in `out_*.json` files are flattened into multiple out_ files
+    /// before balancing, if `options.explode_json` is set. If `options.markers` is
+    /// set, outputs printed to stdout as markers (see `markers::parse_markers()`)
+    /// are added to the values of the out_ files. With `options.recursive`, out_
+    /// files in subdirectories are collected as well, see `nested_var_name()`.
+    pub fn parse_with(exp_run_dir: &Path, options: &TableOptions) -> Result<Self> {
         debug!("reading environment");
-        let env = Environment::from_file(&exp_run_dir.join(RUN_ENV_FILE)).unwrap_or_else(|_| {
-            warn!("No environment found in run {}", exp_run_dir.display());
-            Environment::new()
-        });
+        let mut env =
+            Environment::from_file(&exp_run_dir.join(RUN_ENV_FILE)).unwrap_or_else(|_| {
+                warn!("No environment found in run {}", exp_run_dir.display());
+                Environment::new()
+            });
+        // the position of a run is no variable of the experiment
+        for var in ExomatEnvironment::ORDER_ENV_VARS {
+            env.remove_env(var);
+        }
 
         debug!("reading run script");
-        let run_sh = std::fs::read_to_string(exp_run_dir.join(RUN_RUN_FILE))?;
+        let run_file_path =
+            find_run_file(exp_run_dir).unwrap_or_else(|| exp_run_dir.join(RUN_RUN_FILE));
+        let run_sh = std::fs::read_to_string(&run_file_path)?;
 
         trace!("Reading out_ files of Run {}", exp_run_dir.display());
         let mut out_list: OutList = OutList::default();
-        let contained_files = <ExperimentRun as FileReader>::find_all_files(exp_run_dir);
+        let mut contained_files = <ExperimentRun as FileReader>::find_all_files(exp_run_dir)?;
+        if options.recursive {
+            contained_files.extend(find_nested_files(exp_run_dir)?);
+        }
+        // sorted, so name collisions are always resolved the same way
+        contained_files.sort();
+
+        // column name -> output it was derived from
+        let mut columns: HashMap<String, String> = HashMap::new();
 
         for file in contained_files {
             debug!("checking file {}", file.display());
@@ -435,27 +803,69 @@ impl FileReader for ExperimentRun {
                 Err(Error::Empty(e)) => return Err(Error::Empty(e)), // this means the name is invalid
                 Err(_) => continue,
                 Ok(outfile) => {
-                    // warn if out file shadows env var
-                    if env.contains_env_var(outfile.var_name()) {
-                        warn!(
-                            "in {}: out_{} shadows input environment variable ${}",
-                            outfile.var_name(),
-                            exp_run_dir.display(),
-                            outfile.var_name(),
-                        );
+                    let outfile = match nested_var_name(exp_run_dir, &file, outfile.var_name()) {
+                        Some(name) => OutFile::from(&name, outfile.values().clone()),
+                        None => outfile,
+                    };
+                    let exploded = match options.explode_json {
+                        true => outfile.explode_json(options.json_depth, &options.na_value),
+                        false => None,
+                    };
+                    let is_file_name = exploded.is_none();
+                    let outfiles = exploded.unwrap_or_else(|| vec![outfile]);
+
+                    for outfile in outfiles {
+                        let name = outfile.var_name();
+                        let mut column = options.column_name(name, is_file_name);
+                        if let Some(other) = columns.get(&column).filter(|other| *other != name) {
+                            let suffixed = (2..)
+                                .map(|n| format!("{column}_{n}"))
+                                .find(|suffixed| !columns.contains_key(suffixed))
+                                .expect("unbounded range always has an unused suffix");
+                            warn!(
+                                "in {}: {name} and {other} both result in column {column}, using {suffixed} for {name}",
+                                exp_run_dir.display()
+                            );
+                            column = suffixed;
+                        }
+                        columns.insert(column.clone(), name.clone());
+                        let outfile = OutFile::from(&column, outfile.values().clone());
+
+                        // warn if out file shadows env var
+                        if env.contains_env_var(outfile.var_name()) {
+                            warn!(
+                                "in {}: out_{} shadows input environment variable ${}",
+                                outfile.var_name(),
+                                exp_run_dir.display(),
+                                outfile.var_name(),
+                            );
+                        }
+
+                        // extend existing outlist
+                        if out_list.contains(&outfile) {
+                            let to_extend = out_list
+                                .iter_mut()
+                                .find(|f| f.var_name() == outfile.var_name())
+                                .expect("Could not locate out file to append to");
+
+                            to_extend.extend_values(outfile.values());
+                        } else {
+                            out_list.push(outfile);
+                        }
                     }
+                }
+            }
+        }
 
-                    // extend existing outlist
-                    if out_list.contains(&outfile) {
-                        let to_extend = out_list
-                            .iter_mut()
-                            .find(|f| f.var_name() == outfile.var_name())
-                            .expect("Could not locate out file to append to");
-
-                        to_extend.extend_values(outfile.values());
-                    } else {
-                        out_list.push(outfile);
-                    }
+        if let Some(pattern) = &options.markers {
+            let stdout = std::fs::read(exp_run_dir.join(RUN_STDOUT_LOG))
+                .or_else(|_| std::fs::read(exp_run_dir.join(RUN_STDOUT_LOG_LEGACY)))
+                .unwrap_or_default();
+            for (key, value) in parse_markers(&String::from_utf8_lossy(&stdout), pattern)? {
+                let key = options.column_name(&key, false);
+                match out_list.iter_mut().find(|f| *f.var_name() == key) {
+                    Some(outfile) => outfile.extend_values(&[value]),
+                    None => out_list.push(OutFile::from(&key, vec![value])),
                 }
             }
         }
@@ -501,12 +911,14 @@ impl FileReader for ExperimentRun {
 
         Ok(ExperimentRun {
             run_sh,
+            run_file: file_name_string(&run_file_path),
             run_name,
             env,
             exomat_env,
             out_files: out_balanced,
             status: RunStatus::Unknown,
             location: Some(exp_run_dir.to_path_buf()),
+            options: RunOptions::default(),
         })
     }
 }
@@ -542,16 +954,236 @@ impl<'a> IntoIterator for &'a ExperimentRun {
 
 // ========================== Tests ==========================
 
+/// Returns `true` if the file `name` is written by exomat when creating a run,
+/// see [EXOMAT_RUN_FILES].
+fn is_exomat_run_file(name: &std::ffi::OsStr) -> bool {
+    EXOMAT_RUN_FILES.iter().any(|f| name == *f) || name.to_str().is_some_and(is_run_file)
+}
+
+/// Returns the exit code of a process, or 128 + signal number (like a shell does)
+/// if it was killed by a signal.
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(-1)
+}
+
+/// Describes how `command` is started: program, arguments, working directory and
+/// the variables set for it (sorted by name), one per line.
+///
+/// The values of all variables in `redact` are replaced by [REDACTED]. Variables
+/// inherited from the environment of the exomat are not listed.
+fn command_metadata(command: &Command, redact: &[String]) -> String {
+    let args: Vec<_> = command.get_args().collect();
+    let mut metadata = format!(
+        "program: {}\nargs: {args:?}\ncwd: {}\nenv:\n",
+        Path::new(command.get_program()).display(),
+        command
+            .get_current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default()
+    );
+
+    let mut envs: Vec<_> = command
+        .get_envs()
+        .filter_map(|(var, value)| Some((var.to_string_lossy(), value?.to_string_lossy())))
+        .collect();
+    envs.sort();
+    for (var, value) in envs {
+        let value = match redact.iter().any(|r| *r == var) {
+            true => REDACTED.into(),
+            false => value,
+        };
+        metadata.push_str(&format!("  {var}={value}\n"));
+    }
+
+    metadata
+}
+
+/// Process group of the run that is currently executed, 0 if there is none
+static RUNNING_GROUP: AtomicI32 = AtomicI32::new(0);
+
+/// Kills the process group of the current run, then terminates the exomat
+/// with `signal` as usual.
+///
+/// Only calls async-signal-safe functions, as it is used as signal handler.
+extern "C" fn kill_running_group(signal: libc::c_int) {
+    let group = RUNNING_GROUP.load(Ordering::SeqCst);
+    unsafe {
+        if group > 0 {
+            libc::kill(-group, libc::SIGKILL);
+        }
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+/// Makes sure that interrupting the exomat (e.g. Ctrl-C) also kills the process
+/// group of the current run, which does not receive the signal of the terminal.
+fn install_interrupt_handler() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let handler = kill_running_group as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP] {
+            unsafe { libc::signal(signal, handler) };
+        }
+    });
+}
+
+/// Removes all out_ and outpath_ files in `run_folder`.
+fn remove_outputs(run_folder: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(run_folder)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let is_output = name
+            .to_str()
+            .is_some_and(|name| name.starts_with(OUT_PREFIX) || name.starts_with(OUTPATH_PREFIX));
+        if is_output && entry.file_type()?.is_file() {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns all files in the subdirectories of `run_dir`, at any depth.
+///
+/// Symlinked directories are not followed, they might point outside of the run
+/// (e.g. into the Experiment Source with `run --symlinks`).
+fn find_nested_files(run_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs: Vec<PathBuf> = read_dir_entries(run_dir)?
+        .into_iter()
+        .filter(|(_, metadata)| metadata.is_dir())
+        .map(|(path, _)| path)
+        .collect();
+
+    while let Some(dir) = dirs.pop() {
+        for (path, metadata) in read_dir_entries(&dir)? {
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else if metadata.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns the variable name of an out_ file in a subdirectory of `run_dir`,
+/// the directories relative to `run_dir` joined with `_` in front of `name`.
+///
+/// E.g. `[run_dir]/node0/gpu/out_power` results in `node0_gpu_power`. Returns
+/// `None` for out_ files directly inside of `run_dir`.
+fn nested_var_name(run_dir: &Path, file: &Path, name: &str) -> Option<String> {
+    let dirs = file.parent()?.strip_prefix(run_dir).ok()?;
+    if dirs.as_os_str().is_empty() {
+        return None;
+    }
+
+    let mut parts: Vec<String> = dirs
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    parts.push(name.to_string());
+    Some(parts.join("_"))
+}
+
+/// Executes `command` like `Command::output()`, but kills it after `timeout`.
+///
+/// The command is started in its own process group, so all processes it spawned
+/// are killed along with it, on timeout or if the exomat is interrupted. Returns
+/// the collected output, together with `true` if the command timed out.
+fn output_in_group(
+    command: &mut Command,
+    timeout: Option<Duration>,
+) -> std::io::Result<(Output, bool)> {
+    install_interrupt_handler();
+    let mut child = command.process_group(0).spawn()?;
+    let group = child.id() as libc::pid_t;
+    RUNNING_GROUP.store(group, Ordering::SeqCst);
+
+    // read pipes in the background, so a chatty child does not block on a full pipe
+    let read_pipe = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut buf = vec![];
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout = read_pipe(
+        child
+            .stdout
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let stderr = read_pipe(
+        child
+            .stderr
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    let status = loop {
+        let Some(deadline) = deadline else {
+            break child.wait();
+        };
+
+        if let Some(status) = child.try_wait()? {
+            break Ok(status);
+        }
+
+        if Instant::now() >= deadline {
+            timed_out = true;
+            // negative pid: signal the whole process group
+            unsafe { libc::kill(-group, libc::SIGKILL) };
+            break child.wait();
+        }
+
+        std::thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+    RUNNING_GROUP.store(0, Ordering::SeqCst);
+
+    let output = Output {
+        status: status?,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    };
+    Ok((output, timed_out))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::experiment::table_options::ColumnCase;
     use crate::experiment::{ExperimentRun, ExperimentSeries, ExperimentSource, FileWriter};
     use crate::harness::env::Environment;
     use crate::helper::test_fixtures::{setup_run_dir, setup_run_dir_shadow, setup_series_no_out};
-    use crate::helper::test_helper::populate_src_with_series;
-
+    use crate::helper::test_helper::{create_out_file, populate_src_with_series};
+    use rstest::rstest;
     use tempfile::TempDir;
 
+    #[test]
+    fn run_position_is_no_variable() {
+        let env = Environment::from_env_list(vec![("FOO".to_string(), "1".to_string())]);
+        let exomat_env = ExomatEnvironment::new(&PathBuf::new(), 0);
+        let mut run = ExperimentRun::new("", (&PathBuf::from("0.env"), &env), &exomat_env, 1);
+        let hash = run.env_hash();
+
+        run.set_run_position(6, 120);
+        let envs = run.serializable_envs();
+        assert_eq!(envs.get_env_val("RUN_INDEX"), Some(&"6".to_string()));
+        assert_eq!(envs.get_env_val("RUN_TOTAL"), Some(&"120".to_string()));
+        // the same variables in another order are reused from a cache
+        assert_eq!(run.env_hash(), hash);
+    }
+
     #[test]
     fn build_run_directory_simple() {
         // create base tempdir, to act as parent
@@ -561,7 +1193,7 @@ mod tests {
         // create an experiment source, and an experiment series
         let src_name = "FooSource";
         let ser_name = "FooSeries";
-        let (_, mut ser) = populate_src_with_series(&tmpdir.to_path_buf(), src_name, ser_name);
+        let (_, mut ser) = populate_src_with_series(tmpdir, src_name, ser_name);
 
         // create Experiment Run in ser, equals to one repetition of one environment)
         ser.generate_runs().unwrap();
@@ -589,16 +1221,13 @@ mod tests {
 
         // create an experiment source and set repetition to something higher, to get leading zeros in directory names
         let mut src = ExperimentSource::new();
-        src.set_exomat_envs(ExomatEnvironment {
-            exp_src_dir: tmpdir.join("FooSource"),
-            repetition: 15,
-        });
+        src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("FooSource"), 15));
         src.persist(&tmpdir.join("FooSource")).unwrap();
 
         let mut ser = ExperimentSeries::from_source(&src).unwrap();
         ser.generate_runs().unwrap();
         assert_eq!(ser.runs().len(), 15);
-        ser.persist(&tmpdir.to_path_buf()).unwrap();
+        ser.persist(tmpdir).unwrap();
 
         let runs_dir = ser.location().as_ref().unwrap().join(SERIES_RUNS_DIR);
         assert!(runs_dir.join("run_0_rep00").is_dir());
@@ -655,6 +1284,657 @@ mod tests {
         assert!(run_env.contains_env_var("FOO"));
     }
 
+    #[test]
+    fn test_run_shell_trace() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script("#!/usr/bin/env bash\necho traced_output".to_string());
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            shell_trace: true,
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = ser.runs_mut().first_mut().unwrap();
+        let (out, err) = run.execute("FooSource").unwrap();
+
+        // the command itself is traced on stderr, its output stays on stdout
+        assert!(err.contains("+ echo traced_output"));
+        assert_eq!(out, "traced_output\n");
+    }
+
+    #[test]
+    fn test_run_exomat_lib() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh\n. \"$EXOMAT_LIB\"\nexomat_out answer 42\nexomat_out words two words\nexomat_out answer 43"
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+        ser.runs_mut()
+            .first_mut()
+            .unwrap()
+            .execute("FooSource")
+            .unwrap();
+
+        let collected = ExperimentSeries::parse(&tmpdir.join("FooSeries")).unwrap();
+        let run = collected.runs().first().unwrap();
+        assert_eq!(run.out_var("answer").unwrap(), &vec!["42", "43"]);
+        assert_eq!(run.out_var("words").unwrap()[0], "two words");
+    }
+
+    #[test]
+    fn test_run_dir_env() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh
+[ \"$EXP_RUN_DIR\" = \"$(pwd -P)\" ] && echo \"$EXP_RUN_DIR\" > out_dir"
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+        ser.runs_mut()
+            .first_mut()
+            .unwrap()
+            .execute("FooSource")
+            .unwrap();
+
+        let run_dir = tmpdir
+            .join("FooSeries")
+            .join(SERIES_RUNS_DIR)
+            .join("run_0_rep0");
+        let dir = std::fs::read_to_string(run_dir.join("out_dir")).unwrap();
+        assert_eq!(
+            dir.trim(),
+            run_dir.canonicalize().unwrap().display().to_string()
+        );
+
+        // not serialized, the series can be moved
+        let env = Environment::from_file(&run_dir.join(RUN_ENV_FILE)).unwrap();
+        assert!(!env.contains_env_var(ExomatEnvironment::RUN_DIR_ENV_VAR));
+    }
+
+    #[test]
+    fn test_run_workdir_source() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh
+. \"$EXOMAT_LIB\"
+exomat_out data \"$(cat data/input)\"
+pwd -P > \"$EXP_RUN_DIR/out_cwd\""
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+        std::fs::create_dir(exp_source.join("data")).unwrap();
+        std::fs::write(exp_source.join("data").join("input"), "from source").unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            workdir: "source".parse().unwrap(),
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+        ser.runs_mut()
+            .first_mut()
+            .unwrap()
+            .execute("FooSource")
+            .unwrap();
+
+        let collected = ExperimentSeries::parse(&tmpdir.join("FooSeries")).unwrap();
+        let run = collected.runs().first().unwrap();
+        assert_eq!(run.out_var("data").unwrap(), &vec!["from source"]);
+        assert_eq!(
+            run.out_var("cwd").unwrap()[0],
+            exp_source.canonicalize().unwrap().display().to_string()
+        );
+        assert!(!exp_source.join("out_data").exists());
+    }
+
+    #[test]
+    fn test_run_isolate_tmp() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh\necho \"$TMPDIR\" > out_tmp\n[ \"$TMP\" = \"$TMPDIR\" ] && [ \"$TEMP\" = \"$TMPDIR\" ] && [ -d \"$TMPDIR\" ]"
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            isolate_tmp: true,
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+        for run in ser.runs_mut() {
+            run.execute("FooSource").unwrap();
+        }
+
+        let collected = ExperimentSeries::parse(&tmpdir.join("FooSeries")).unwrap();
+        let tmp_dirs: Vec<&String> = collected
+            .runs()
+            .iter()
+            .map(|run| &run.out_var("tmp").unwrap()[0])
+            .collect();
+
+        // every run has its own temp dir, which is removed afterwards
+        assert_eq!(tmp_dirs.len(), 2);
+        assert_ne!(tmp_dirs[0], tmp_dirs[1]);
+        assert!(tmp_dirs.iter().all(|dir| !Path::new(dir).exists()));
+    }
+
+    #[test]
+    fn test_run_timeout() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        // the forked subshell must be killed as well
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh\n(sleep 1; touch out_orphan) &\necho started\nsleep 30".to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            timeout: Some(Duration::from_millis(200)),
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let start = Instant::now();
+        let run = &mut ser.runs_mut()[0];
+        let (stdout, _) = run.execute("FooSource").unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(run.status(), &RunStatus::Timeout);
+        assert_eq!(stdout, "started\n");
+
+        let run_dir = run.location.clone().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join(TIMEOUT_OUT_FILE)).unwrap(),
+            "true"
+        );
+        // killed by SIGKILL
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join(EXIT_CODE_OUT_FILE)).unwrap(),
+            "137"
+        );
+        std::thread::sleep(Duration::from_millis(1500));
+        assert!(!run_dir.join("out_orphan").exists());
+    }
+
+    #[rstest]
+    #[case(vec![], 1, 3)]
+    #[case(vec![75], 75, 3)]
+    #[case(vec![75], 1, 1)]
+    #[case(vec![75], 0, 1)]
+    fn test_run_retries(
+        #[case] retry_on_codes: Vec<i32>,
+        #[case] code: i32,
+        #[case] attempts: usize,
+    ) {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(format!(
+            "#!/bin/sh
+echo x >> ../attempts
+echo x >> out_attempt
+exit {code}"
+        ));
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            retries: 2,
+            retry_on_codes,
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = &mut ser.runs_mut()[0];
+        assert_eq!(run.execute("FooSource").is_ok(), code == 0);
+
+        let run_dir = run.location.clone().unwrap();
+        let out = std::fs::read_to_string(run_dir.join("../attempts")).unwrap();
+        assert_eq!(out.lines().count(), attempts);
+        // outputs of failed attempts are removed
+        let out = std::fs::read_to_string(run_dir.join("out_attempt")).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert_eq!(
+            std::fs::read_to_string(run_dir.join(EXIT_CODE_OUT_FILE)).unwrap(),
+            code.to_string()
+        );
+    }
+
+    #[test]
+    fn test_run_duration() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        // an out_exomat_duration written by the run is replaced
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh
+echo 99 > out_exomat_duration
+sleep 0.2"
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = &mut ser.runs_mut()[0];
+        run.execute("FooSource").unwrap();
+
+        let run_dir = run.location.clone().unwrap();
+        let duration = std::fs::read_to_string(run_dir.join(DURATION_OUT_FILE)).unwrap();
+        assert_eq!(duration.split_once('.').unwrap().1.len(), 3);
+        let duration: f64 = duration.parse().unwrap();
+        assert!((0.2..10.0).contains(&duration));
+    }
+
+    #[test]
+    fn test_run_own_process_group() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        // field 5 of /proc/<pid>/stat is the process group
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh
+echo $$ > out_pid
+cut -d' ' -f5 /proc/$$/stat > out_group"
+                .to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = &mut ser.runs_mut()[0];
+        run.execute("FooSource").unwrap();
+        assert_eq!(run.status(), &RunStatus::Success);
+
+        // without timeout, the run is still the leader of its own process group
+        let run_dir = run.location.clone().unwrap();
+        let pid = std::fs::read_to_string(run_dir.join("out_pid")).unwrap();
+        let group = std::fs::read_to_string(run_dir.join("out_group")).unwrap();
+        assert_eq!(pid, group);
+    }
+
+    #[test]
+    fn test_collect_markers() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script(
+            "#!/bin/sh\necho 1 > out_latency\necho '::exomat-output latency=2 size=3::'\necho '::exomat-output latency=4::'".to_string(),
+        );
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+        let run = &mut ser.runs_mut()[0];
+        run.execute("FooSource").unwrap();
+        let run_dir = run.location.clone().unwrap();
+        assert!(run_dir.join(RUN_STDOUT_LOG).is_file());
+        assert!(run_dir.join(RUN_STDERR_LOG).is_file());
+
+        // markers are only collected if requested
+        let parsed = ExperimentRun::parse(&run_dir).unwrap();
+        assert_eq!(parsed.out_var("latency"), Some(&vec!["1".to_string()]));
+        assert_eq!(parsed.out_var("size"), None);
+
+        let options = TableOptions {
+            markers: Some(crate::experiment::markers::DEFAULT_MARKER_PATTERN.to_string()),
+            ..Default::default()
+        };
+        let parsed = ExperimentRun::parse_with(&run_dir, &options).unwrap();
+        assert_eq!(
+            parsed.out_var("latency"),
+            Some(&vec!["1".to_string(), "2".to_string(), "4".to_string()])
+        );
+        // single values are repeated to match
+        assert_eq!(parsed.out_var("size"), Some(&vec!["3".to_string(); 3]));
+
+        // runs of older versions kept their stdout in a hidden file
+        std::fs::rename(
+            run_dir.join(RUN_STDOUT_LOG),
+            run_dir.join(RUN_STDOUT_LOG_LEGACY),
+        )
+        .unwrap();
+        let parsed = ExperimentRun::parse_with(&run_dir, &options).unwrap();
+        assert_eq!(parsed.out_var("size"), Some(&vec!["3".to_string(); 3]));
+    }
+
+    #[test]
+    fn test_find_files_error() {
+        let tmpdir = TempDir::new().unwrap();
+        let not_a_dir = tmpdir.path().join("run_0_rep0");
+        std::fs::write(&not_a_dir, "").unwrap();
+
+        let err = <ExperimentRun as FileReader>::find_all_files(&not_a_dir).unwrap_err();
+        assert!(matches!(err, Error::ReaderError { .. }));
+        assert!(err.to_string().contains(&not_a_dir.display().to_string()));
+    }
+
+    #[test]
+    fn test_collect_nested_outputs() {
+        let tmpdir = TempDir::new().unwrap();
+        let run_dir = tmpdir.path();
+        std::fs::write(run_dir.join(RUN_RUN_FILE), "").unwrap();
+        std::fs::write(run_dir.join("out_latency"), "1").unwrap();
+        std::fs::create_dir_all(run_dir.join("node0").join("gpu")).unwrap();
+        std::fs::write(run_dir.join("node0").join("out_latency"), "2").unwrap();
+        std::fs::write(run_dir.join("node0").join("gpu").join("out_power"), "3").unwrap();
+        std::fs::write(run_dir.join("node0").join("notes"), "4").unwrap();
+
+        // flat by default
+        let parsed = ExperimentRun::parse(run_dir).unwrap();
+        assert_eq!(parsed.out_files().len(), 1);
+
+        let options = TableOptions {
+            recursive: true,
+            ..Default::default()
+        };
+        let parsed = ExperimentRun::parse_with(run_dir, &options).unwrap();
+        assert_eq!(parsed.out_files().len(), 3);
+        assert_eq!(parsed.out_var("latency"), Some(&vec!["1".to_string()]));
+        assert_eq!(
+            parsed.out_var("node0_latency"),
+            Some(&vec!["2".to_string()])
+        );
+        assert_eq!(
+            parsed.out_var("node0_gpu_power"),
+            Some(&vec!["3".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_collect_normalized_columns() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        for (name, content) in [
+            (RUN_RUN_FILE, ""),
+            ("out_Latency.txt", "1"),
+            ("out_latency.csv", "2"),
+            ("out_SIZE", "3"),
+        ] {
+            create_out_file(tmpdir, None, name, content);
+        }
+        let run_dir = tmpdir.join(SERIES_RUNS_DIR).join(TEST_RUN_REP_DIR0);
+
+        let parsed = ExperimentRun::parse(&run_dir).unwrap();
+        assert_eq!(parsed.out_var("Latency"), Some(&vec!["1".to_string()]));
+
+        let options = TableOptions {
+            column_case: ColumnCase::Lower,
+            ..Default::default()
+        };
+        let parsed = ExperimentRun::parse_with(&run_dir, &options).unwrap();
+        // colliding columns are numbered in the order of their file names
+        assert_eq!(parsed.out_var("latency"), Some(&vec!["1".to_string()]));
+        assert_eq!(parsed.out_var("latency_2"), Some(&vec!["2".to_string()]));
+        assert_eq!(parsed.out_var("size"), Some(&vec!["3".to_string()]));
+        assert_eq!(parsed.out_var("SIZE"), None);
+    }
+
+    #[test]
+    fn test_collect_same_stem() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        for (name, content) in [
+            (RUN_RUN_FILE, ""),
+            ("out_x", "1"),
+            ("out_x.txt", "2"),
+            ("out_x.csv", "3"),
+        ] {
+            create_out_file(tmpdir, None, name, content);
+        }
+        let run_dir = tmpdir.join(SERIES_RUNS_DIR).join(TEST_RUN_REP_DIR0);
+        let column = |run: &ExperimentRun, name: &str| run.out_var(name).map(|v| v.join(""));
+
+        // by default the extension is removed, collisions are numbered in the
+        // order of the file names
+        let parsed = ExperimentRun::parse(&run_dir).unwrap();
+        assert_eq!(column(&parsed, "x").as_deref(), Some("1"));
+        assert_eq!(column(&parsed, "x_2").as_deref(), Some("3"));
+        assert_eq!(column(&parsed, "x_3").as_deref(), Some("2"));
+        assert_eq!(parsed.out_files().len(), 3);
+
+        // with --keep-extension, the column is the whole name after out_
+        let options = TableOptions {
+            strip_extension: false,
+            ..Default::default()
+        };
+        let parsed = ExperimentRun::parse_with(&run_dir, &options).unwrap();
+        assert_eq!(column(&parsed, "x").as_deref(), Some("1"));
+        assert_eq!(column(&parsed, "x.txt").as_deref(), Some("2"));
+        assert_eq!(column(&parsed, "x.csv").as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_run_quiet_log_level() {
+        let mut run = ExperimentRun::new(
+            "",
+            (&PathBuf::from("0.env"), &Environment::new()),
+            &ExomatEnvironment::new(&PathBuf::from("."), 1),
+            1,
+        );
+        assert_eq!(run.routine_log_level(), Level::Info);
+
+        run.set_options(RunOptions {
+            quiet_runs: true,
+            ..Default::default()
+        });
+        assert_eq!(run.routine_log_level(), Level::Debug);
+    }
+
+    #[test]
+    fn test_run_args_from_env() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script("#!/bin/sh\nfor arg in \"$@\"; do echo \"[$arg]\"; done".to_string());
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.set_envs(HashMap::from([
+            (
+                PathBuf::from("0.env"),
+                Environment::from_env_list(vec![(
+                    "EXOMAT_ARGS".to_string(),
+                    "-n 1 --name 'foo bar'".to_string(),
+                )]),
+            ),
+            (
+                PathBuf::from("1.env"),
+                Environment::from_env_list(vec![(
+                    "EXOMAT_ARGS".to_string(),
+                    "--single\\ word".to_string(),
+                )]),
+            ),
+        ]))
+        .unwrap();
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let mut outputs: Vec<String> = ser
+            .runs_mut()
+            .iter_mut()
+            .map(|run| run.execute("FooSource").unwrap().0)
+            .collect();
+        outputs.sort();
+
+        assert_eq!(
+            outputs,
+            vec!["[--single word]\n", "[-n]\n[1]\n[--name]\n[foo bar]\n"]
+        );
+    }
+
+    #[test]
+    fn test_run_other_run_file() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        // the interpreter is chosen by the shebang, not by the file name
+        let mut src = ExperimentSource::new();
+        src.set_run_file_name("run.py");
+        src.set_run_script("#!/bin/sh\necho 1 > out_x".to_string());
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = &mut ser.runs_mut()[0];
+        run.execute("FooSource").unwrap();
+
+        let run_dir = run.location.clone().unwrap();
+        assert!(run_dir.join("run.py").is_file());
+        assert!(!run_dir.join(RUN_RUN_FILE).exists());
+        assert!(ExperimentRun::is_complete(&run_dir));
+
+        let parsed = ExperimentRun::parse(&run_dir).unwrap();
+        assert_eq!(parsed.out_var("x"), Some(&vec!["1".to_string()]));
+    }
+
+    #[test]
+    fn test_run_capture_command() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path();
+        let exp_source = tmpdir.join("FooSource");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script("#!/bin/sh\ntrue".to_string());
+        src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
+        src.set_envs(HashMap::from([(
+            PathBuf::from("0.env"),
+            Environment::from_env_list(vec![
+                (
+                    "EXOMAT_ARGS".to_string(),
+                    "-n 1 --name 'foo bar'".to_string(),
+                ),
+                ("TOKEN".to_string(), "secret".to_string()),
+            ]),
+        )]))
+        .unwrap();
+        src.persist(&exp_source).unwrap();
+
+        let mut ser = ExperimentSeries::from_source(&src).unwrap();
+        ser.set_run_options(RunOptions {
+            capture_command: true,
+            redact: vec!["TOKEN".to_string()],
+            ..Default::default()
+        });
+        ser.generate_runs().unwrap();
+        ser.persist(&tmpdir.join("FooSeries")).unwrap();
+
+        let run = &mut ser.runs_mut()[0];
+        run.execute("FooSource").unwrap();
+
+        let run_dir = run.location.clone().unwrap().canonicalize().unwrap();
+        let metadata = std::fs::read_to_string(run_dir.join(RUN_COMMAND_FILE)).unwrap();
+        let lines: Vec<&str> = metadata.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!("program: {}", run_dir.join(RUN_RUN_FILE).display())
+        );
+        assert_eq!(lines[1], r#"args: ["-n", "1", "--name", "foo bar"]"#);
+        assert_eq!(lines[2], format!("cwd: {}", run_dir.display()));
+        assert!(lines.contains(&"  TOKEN=<redacted>"));
+        assert!(lines.contains(&"  REPETITION=0"));
+        assert!(!metadata.contains("secret"));
+
+        // the metadata is not a result of the run
+        std::fs::remove_file(run_dir.join(EXIT_CODE_OUT_FILE)).unwrap();
+        std::fs::remove_file(run_dir.join(DURATION_OUT_FILE)).unwrap();
+        std::fs::remove_file(run_dir.join(RUN_STDOUT_LOG)).unwrap();
+        std::fs::remove_file(run_dir.join(RUN_STDERR_LOG)).unwrap();
+        assert!(!ExperimentRun::is_complete(&run_dir));
+    }
+
+    #[test]
+    fn is_complete_partial_run() {
+        let tmpdir = TempDir::new().unwrap();
+        let run_dir = tmpdir.path();
+
+        std::fs::write(run_dir.join(MARKER_RUN), "").unwrap();
+        std::fs::write(run_dir.join(RUN_RUN_FILE), "#!/bin/sh").unwrap();
+        assert!(!ExperimentRun::is_complete(run_dir));
+
+        // interrupted after writing some results
+        std::fs::write(run_dir.join("out_x"), "1").unwrap();
+        std::fs::write(run_dir.join("scratch.tmp"), "").unwrap();
+        assert!(!ExperimentRun::is_complete(run_dir));
+
+        std::fs::write(run_dir.join(RUN_STDOUT_LOG), "").unwrap();
+        assert!(!ExperimentRun::is_complete(run_dir));
+
+        std::fs::write(run_dir.join(RUN_STDERR_LOG), "").unwrap();
+        assert!(ExperimentRun::is_complete(run_dir));
+    }
+
     #[test]
     fn runreader_iter_working() {
         let tmp_run = setup_run_dir();
@@ -1,21 +1,28 @@
 use crate::duplicate_log_to_pipe;
 use crate::experiment::{
-    experiment_run::RunStatus, out_file::OutFile, CsvWriter, ExperimentRun, ExperimentSource,
-    FileReader, FileWriter,
+    experiment_run::RunStatus, experiment_traits::read_dir_entries, out_file::OutFile, CsvWriter,
+    ExperimentRun, ExperimentSource, FileReader, FileWriter, RunOptions, TableOptions,
 };
-use crate::harness::env::{Environment, ExomatEnvironment};
+use crate::harness::env::{Environment, ExomatEnvironment, ValidationRules};
 use crate::helper::{
-    archivist::{copy_harness_dir, create_harness_dir, create_harness_file},
+    archivist::{
+        canonicalize, copy_harness_dir, create_harness_dir, create_harness_file, gunzip_file,
+        gzip_file, read_maybe_gzipped,
+    },
     errors::{Error, Result},
     fs_names::*,
+    host_info::HostInfo,
+    provenance::Provenance,
 };
 
 use chrono::Local;
-use csv::Writer;
+use csv::WriterBuilder;
+use itertools::Itertools;
 use log::{debug, info, trace, warn};
-use rand::seq::SliceRandom;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::collections::HashMap;
 use std::fs::{read_to_string, write};
-use std::io::{PipeReader, Read};
+use std::io::{PipeReader, Read, Write};
 use std::path::{Path, PathBuf};
 
 #[cfg(test)]
@@ -38,6 +45,7 @@ pub struct ExperimentSeries {
     stdout_log: String,
     stderr_log: String,
     exomat_log: PipeReader,
+    options: RunOptions,
 }
 
 impl ExperimentSeries {
@@ -50,6 +58,7 @@ impl ExperimentSeries {
     /// - `stdout_log`: empty String
     /// - `stderr_log`: empty String
     /// - `exomat_log`: empty String
+    /// - `options`: default RunOptions
     ///
     /// ## Errors
     /// - retruns a `HarnessRunError` if source.location is PWD
@@ -75,6 +84,7 @@ impl ExperimentSeries {
             stdout_log: String::new(),
             stderr_log: String::new(),
             exomat_log: duplicate_log_to_pipe()?,
+            options: RunOptions::default(),
         })
     }
 
@@ -88,16 +98,14 @@ impl ExperimentSeries {
 
     /// Return a string describing the overall success of the Experiment Series
     ///
-    /// - If any Experiment Run in self.runs failed, return `Failed. Reason: [...]`
+    /// - If any Experiment Run in self.runs failed or timed out, return `Failed. Reason: [...]`
     /// - If all Experiment Runs were successful, return `Successful`
     /// - If any Experiment Run has not been executed or its status in Unknown, return `Cannot determine run status`
     pub fn series_status(&self) -> String {
-        if let Some(reason) = self.runs.iter().find_map(|run| {
-            if let RunStatus::Fail(reason) = run.status() {
-                Some(reason.as_str())
-            } else {
-                None
-            }
+        if let Some(reason) = self.runs.iter().find_map(|run| match run.status() {
+            RunStatus::Fail(reason) => Some(reason.as_str()),
+            RunStatus::Timeout => Some("timed out"),
+            _ => None,
         }) {
             format!("Failed. Reason: {}", reason)
         } else if self
@@ -111,6 +119,35 @@ impl ExperimentSeries {
         }
     }
 
+    /// Returns a description of the inputs of this series: the experiment name,
+    /// the current time, the number of repetitions, the variables of every env
+    /// file and the names of all run directories, in the order they are executed.
+    ///
+    /// ## Errors
+    /// - Returns an error if the experiment name cannot be determined
+    pub fn manifest(&self) -> Result<serde_json::Value> {
+        let environments: serde_json::Map<String, serde_json::Value> = self
+            .source
+            .envs()
+            .iter()
+            .sorted_by_key(|(env_file, _)| *env_file)
+            .map(|(env_file, env)| {
+                (
+                    env_file.display().to_string(),
+                    serde_json::json!(env.to_env_map()),
+                )
+            })
+            .collect();
+
+        Ok(serde_json::json!({
+            "experiment": self.experiment_name()?,
+            "created": Local::now().to_rfc3339(),
+            "repetitions": self.source.repetitions(),
+            "environments": environments,
+            "runs": self.runs.iter().map(|run| run.run_dir_name()).collect::<Vec<_>>(),
+        }))
+    }
+
     /// Generate Experiment Runs based on the current Experiment Series
     ///
     /// Every defined Environment will be used `self.source.repetitions()` times.
@@ -121,12 +158,39 @@ impl ExperimentSeries {
     ///
     /// ## Errors
     /// - returns an `Empty` Error, if self.path is empty
+    /// - returns a `HarnessRunError` listing all env files that set variables
+    ///   reserved by the exomat
     pub fn generate_runs(&mut self) -> Result<()> {
         if self.path.is_none() {
             return Err(Error::Empty(String::from("Series location not set")));
         }
 
-        if *self.source.repetitions() > 1 {
+        // names are not checked, hand written env files may use any name
+        let rules = ValidationRules {
+            check_names: false,
+            ..Default::default()
+        };
+        let base_env_file = Path::new(SRC_TEMPLATE_DIR).join(SRC_BASE_ENV_FILE);
+        let invalid: Vec<String> = std::iter::once((&base_env_file, self.source.base_env()))
+            .chain(
+                self.source
+                    .envs()
+                    .iter()
+                    .sorted_by_key(|(env_file, _)| *env_file),
+            )
+            .filter_map(|(env_file, env)| {
+                let e = env.validate(&rules).err()?;
+                Some(format!("{}: {e}", env_file.display()))
+            })
+            .collect();
+        if !invalid.is_empty() {
+            return Err(Error::HarnessRunError {
+                experiment: self.experiment_name()?,
+                err: invalid.join("\n"),
+            });
+        }
+
+        if *self.source.repetitions() < 1 {
             warn!("Repetition set to less than 1. No Experiment Runs will be created.");
         }
 
@@ -137,33 +201,168 @@ impl ExperimentSeries {
             repetition: u64,
         ) -> ExperimentRun {
             let exomat_envs = ExomatEnvironment::new(series.source.location(), repetition);
+            // values of the env file win over the shared base env
+            let merged = series.source.with_base_env(env.1);
 
-            ExperimentRun::new(
+            let mut run = ExperimentRun::new(
                 series.source.run_script(),
-                env,
+                (env.0, &merged),
                 &exomat_envs,
                 series.source.repetitions().to_string().len(),
-            )
+            );
+            run.set_options(series.options.clone());
+            run.set_run_file_name(series.source.run_file_name());
+            run
         }
 
-        let mut run_list = Vec::new();
+        let empty = Environment::new();
+        let schedule = self.schedule();
+
+        // warmups of every env file are executed before all recorded runs
+        let env_files = schedule
+            .iter()
+            .map(|(env_file, _)| env_file)
+            .unique()
+            .sorted();
+        let warmups = env_files
+            .cartesian_product(0..self.options.warmup)
+            .map(|(env_file, rep)| {
+                let env = self.source.envs().get(env_file).unwrap_or(&empty);
+                let mut run = generate_run_from(self, (env_file, env), rep);
+                run.set_warmup();
+                run
+            });
+
+        let mut run_list: Vec<ExperimentRun> = warmups
+            .chain(schedule.iter().map(|(env_file, rep)| {
+                let env = self.source.envs().get(env_file).unwrap_or(&empty);
+                generate_run_from(self, (env_file, env), *rep)
+            }))
+            .collect();
+
+        // runs are executed in this order, warmups included
+        let total = run_list.len();
+        for (index, run) in run_list.iter_mut().enumerate() {
+            run.set_run_position(index, total);
+        }
 
+        for (a, b) in case_collisions(run_list.iter().map(|run| run.run_dir_name())) {
+            warn!("Experiment Runs {a} and {b} only differ in case, one of them is lost on case-insensitive filesystems");
+        }
+
+        self.runs.extend(run_list);
+        Ok(())
+    }
+
+    /// Returns the env file and repetition of every Experiment Run, in the order
+    /// they will be executed.
+    ///
+    /// If no Environments are defined, [SRC_ENV_FILE] is used for every repetition.
+    /// With `options.ordered`, runs are sorted by env file, then repetition.
+    /// Otherwise the order is shuffled with `options.seed`. If it is not set, a
+    /// random seed is chosen, logged and stored in `options.seed`.
+    pub fn schedule(&mut self) -> Vec<(PathBuf, u64)> {
         if self.source.envs().is_empty() {
-            for rep in 0..*self.source.repetitions() {
-                // cannot edit self.runs directly here, beucase of the borrow checker :)
-                run_list.push(generate_run_from(
-                    self,
-                    (&PathBuf::from(SRC_ENV_FILE), &Environment::new()),
-                    rep,
-                ));
+            return (0..*self.source.repetitions())
+                .map(|rep| (PathBuf::from(SRC_ENV_FILE), rep))
+                .collect();
+        }
+
+        if self.options.ordered {
+            return self
+                .source
+                .envs()
+                .keys()
+                .sorted()
+                .cartesian_product(0..*self.source.repetitions())
+                .map(|(env_file, rep)| (env_file.clone(), rep))
+                .collect();
+        }
+
+        let seed = match self.options.seed {
+            Some(seed) => seed,
+            None => {
+                let seed = rand::random();
+                info!("Shuffling Experiment Runs with seed {seed}, use --seed {seed} to repeat this order");
+                self.options.seed = Some(seed);
+                seed
             }
-        } else {
-            for (environment, rep) in self.shuffled_environments() {
-                run_list.push(generate_run_from(self, environment, rep));
+        };
+
+        self.shuffled_environments(seed)
+            .into_iter()
+            .map(|((env_file, _), rep)| (env_file.clone(), rep))
+            .collect()
+    }
+
+    /// Continues the Experiment Series in `exp_series_dir`, that was created from
+    /// the same Experiment Source earlier.
+    ///
+    /// Runs that are already complete there (see `ExperimentRun::is_complete()`)
+    /// are removed from this series. All other runs are (re-)created inside of
+    /// `exp_series_dir`, replacing the partial results of an interrupted run.
+    /// The existing stdout and stderr logs are kept, new output is appended.
+    /// Compressed logs (see `compress_logs()`) are decompressed, unless all runs
+    /// are complete.
+    ///
+    /// Returns the number of runs that are already complete.
+    ///
+    /// ## Errors
+    /// - Returns a `HarnessRunError` if `exp_series_dir` is not an experiment series
+    /// - Returns an error if a run could not be created
+    pub fn resume(&mut self, exp_series_dir: &Path) -> Result<usize> {
+        if !exp_series_dir.join(MARKER_SERIES).is_file() {
+            return Err(Error::HarnessRunError {
+                experiment: exp_series_dir.display().to_string(),
+                err: "is not an experiment series directory".to_string(),
+            });
+        }
+
+        let runs_dir = exp_series_dir.join(SERIES_RUNS_DIR);
+        let total = self.runs.len();
+        self.runs
+            .retain(|run| !ExperimentRun::is_complete(&runs_dir.join(run.run_dir_name())));
+
+        if !self.runs.is_empty() {
+            for log in SERIES_LOG_FILES {
+                gunzip_file(&runs_dir.join(log))?;
+            }
+        }
+
+        for run in &mut self.runs {
+            let run_dir = runs_dir.join(run.run_dir_name());
+            if run_dir.exists() {
+                debug!("removing partial results of {}", run_dir.display());
+                std::fs::remove_dir_all(&run_dir)?;
+            }
+            run.persist(&run_dir)?;
+        }
+
+        self.stdout_log = read_to_string(runs_dir.join(SERIES_STDOUT_LOG)).unwrap_or_default();
+        self.stderr_log = read_to_string(runs_dir.join(SERIES_STDERR_LOG)).unwrap_or_default();
+        self.path = Some(exp_series_dir.to_path_buf());
+
+        Ok(total - self.runs.len())
+    }
+
+    /// Compresses all log files ([SERIES_LOG_FILES]) of this series with gzip,
+    /// see `gzip_file()`. Should be called after `persist_logs()`.
+    ///
+    /// ## Errors
+    /// - Returns an `Empty` Error, if self.path is empty
+    /// - Returns the errors of `gzip_file()`
+    pub fn compress_logs(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Err(Error::Empty(String::from("Series location not set")));
+        };
+
+        for log in SERIES_LOG_FILES {
+            let log = path.join(SERIES_RUNS_DIR).join(log);
+            if log.is_file() {
+                gzip_file(&log)?;
             }
         }
 
-        self.runs.extend(run_list);
         Ok(())
     }
 
@@ -172,14 +371,11 @@ impl ExperimentSeries {
     /// The name will be derived from the experiment name and the current date and time.
     ///
     /// ## Errors
-    /// - returns an `IoError` if the current directory is inaccessable
+    /// - returns an `IoError` or `ReaderError` if the current directory is inaccessable
     pub fn generate_series_filepath(exp_source: &Path) -> Result<PathBuf> {
         let format = format!("{}-%Y-%m-%d-%H-%M-%S", file_name_string(exp_source));
         let dirname = PathBuf::from(Local::now().format(&format).to_string());
-        Ok(std::env::current_dir()?
-            .canonicalize()?
-            .join(&dirname)
-            .to_path_buf())
+        Ok(canonicalize(&std::env::current_dir()?)?.join(&dirname))
     }
 
     // ========================= getter ========================================
@@ -203,6 +399,11 @@ impl ExperimentSeries {
         self.source.exomat_envs()
     }
 
+    /// Returns the Experiment Source of this Experiment Series
+    pub fn source(&self) -> &ExperimentSource {
+        &self.source
+    }
+
     /// Returns the run script of the Experiment Source of this Experiment Series
     pub fn run_script(&self) -> &str {
         self.source.run_script()
@@ -248,6 +449,107 @@ impl ExperimentSeries {
         keys
     }
 
+    /// Returns every key together with the number of runs that produced a value for it,
+    /// sorted by key.
+    ///
+    /// A run counts as producing a key, if its out_ file contains at least one value
+    /// that is neither empty nor "NA".
+    pub fn key_coverage(&self) -> Vec<(String, usize)> {
+        self.keys()
+            .into_iter()
+            .map(|key| {
+                let produced = self
+                    .runs
+                    .iter()
+                    .filter(|run| {
+                        run.out_var(key).is_some_and(|vals| {
+                            vals.iter().any(|val| !val.is_empty() && val != "NA")
+                        })
+                    })
+                    .count();
+                (key.to_string(), produced)
+            })
+            .collect()
+    }
+
+    /// Returns the rows that `to_csv_with()` writes, including the header row.
+    ///
+    /// Empty if there are no runs or all runs are empty.
+    pub fn to_rows(&self, with_env: bool) -> Vec<Vec<String>> {
+        match self.runs_are_empty() {
+            true => vec![],
+            false => self.to_csv_rows(with_env),
+        }
+    }
+
+    /// Returns all values of each key, in the same order as they would be serialized
+    /// by `to_csv()`.
+    pub fn to_columns(&self) -> HashMap<String, Vec<String>> {
+        let mut rows = self.to_csv_rows(false).into_iter();
+        let header = rows.next().unwrap_or_default();
+
+        let mut columns: HashMap<String, Vec<String>> =
+            header.iter().map(|key| (key.clone(), Vec::new())).collect();
+        for row in rows {
+            for (key, value) in header.iter().zip(row) {
+                columns.get_mut(key).unwrap().push(value);
+            }
+        }
+
+        columns
+    }
+
+    /// Parses an Experiment Series directory into an ExperimentSeries object.
+    ///
+    /// Outputs missing in some runs are filled with `options.na_value`. JSON objects
+    /// in `out_*.json` files are flattened into multiple outputs, if
+    /// `options.explode_json` is set.
+    ///
+    /// ### Error
+    /// - Returns a `ReaderError` if any RunReader failed to parse
+    pub fn parse_with(exp_series_dir: &Path, options: &TableOptions) -> Result<Self> {
+        debug!("looking for experiment runs");
+        let runs = <ExperimentSeries as FileReader>::find_all_files(
+            &exp_series_dir.join(SERIES_RUNS_DIR),
+        )?
+        .iter()
+        .map(|run| Self::parse_run(run, options))
+        .collect::<Result<Vec<_>>>()?;
+
+        debug!("reading log files");
+        let stdout_log =
+            read_maybe_gzipped(&exp_series_dir.join(SERIES_RUNS_DIR).join(SERIES_STDOUT_LOG))
+                .unwrap_or_default();
+        let stderr_log =
+            read_maybe_gzipped(&exp_series_dir.join(SERIES_RUNS_DIR).join(SERIES_STDERR_LOG))
+                .unwrap_or_default();
+
+        let mut reader = ExperimentSeries {
+            source: ExperimentSource::new(),
+            path: Some(exp_series_dir.to_path_buf()),
+            runs,
+            stdout_log,
+            stderr_log,
+            exomat_log: duplicate_log_to_pipe()?,
+            options: RunOptions::default(),
+        };
+
+        debug!("adding missing keys");
+        reader.fill_missing_keys(&options.na_value);
+        Ok(reader)
+    }
+
+    /// Parses the Experiment Run at `run_dir`.
+    ///
+    /// ## Errors
+    /// - Returns a `ReaderError` if the run could not be parsed
+    fn parse_run(run_dir: &Path, options: &TableOptions) -> Result<ExperimentRun> {
+        ExperimentRun::parse_with(run_dir, options).map_err(|e| Error::ReaderError {
+            dir: run_dir.display().to_string(),
+            reason: e.to_string(),
+        })
+    }
+
     // ========================= setter ========================================
 
     /// Updates the location of this Experiment Series.
@@ -270,25 +572,31 @@ impl ExperimentSeries {
         self.source = source.clone()
     }
 
+    /// Replaces the options passed to all Experiment Runs generated afterwards
+    pub fn set_run_options(&mut self, options: RunOptions) {
+        self.options = options
+    }
+
     // ========================= helper ========================================
 
     /// Compiles a list of all repetitions for each environment, then suffles said list.
     ///
     /// The shuffled list is then sorted by repetition, so that all n-repetitions run
-    /// before all n+1-repetitions.
-    fn shuffled_environments(&self) -> Vec<((&PathBuf, &Environment), u64)> {
+    /// before all n+1-repetitions. The same `seed` always results in the same order.
+    fn shuffled_environments(&self, seed: u64) -> Vec<((&PathBuf, &Environment), u64)> {
         let mut running_order = vec![];
         let max_rep = self.source.repetitions();
 
         trace!("Randomizing environments...");
         for rep in 0..*max_rep {
-            for env in self.source.envs() {
+            // envs are stored unordered, sort them so the seed determines the order
+            for env in self.source.envs().iter().sorted_by_key(|(path, _)| *path) {
                 // include the repetition in a tuple, so that it can be sorted correctly later
                 running_order.push((env, rep));
             }
         }
 
-        running_order.shuffle(&mut rand::rng());
+        running_order.shuffle(&mut StdRng::seed_from_u64(seed));
         running_order.sort_by_key(|a| a.1);
 
         running_order
@@ -297,15 +605,27 @@ impl ExperimentSeries {
     /// Adds missing out_ files to each Experiment Run.
     ///
     /// If a key is present in one Experiment Run but missing another, the key will be
-    /// added with "NA" as it's value.
-    fn fill_missing_keys(&mut self) {
+    /// added with `na_value` as it's value, see `fill_missing_keys_of()`.
+    fn fill_missing_keys(&mut self, na_value: &str) {
         let keys: Vec<String> = self.keys().into_iter().map(|k| k.to_string()).collect();
 
         for run in self.runs.iter_mut() {
-            for key in &keys {
-                if run.out_var(key).is_none() {
-                    run.insert_out_file(OutFile::from(key, vec!["NA".to_string()]));
-                }
+            Self::fill_missing_keys_of(run, &keys, na_value);
+        }
+    }
+
+    /// Adds every key of `keys` that `run` has no out_ file for, with `na_value`
+    /// as it's value.
+    ///
+    /// The out_ files of a run are balanced, so every added key gets as many
+    /// values as the other out_ files of `run` (at least one). Otherwise only the
+    /// first row of a run with multiple rows would be `na_value`.
+    fn fill_missing_keys_of<S: AsRef<str>>(run: &mut ExperimentRun, keys: &[S], na_value: &str) {
+        let value_count = run.out_files().max_length().max(1);
+        for key in keys {
+            let key = key.as_ref();
+            if run.out_var(key).is_none() {
+                run.insert_out_file(OutFile::from(key, vec![na_value.to_string(); value_count]));
             }
         }
     }
@@ -313,6 +633,11 @@ impl ExperimentSeries {
     /// Parses `self.runs` into rows, that can be serialized in a CSV format.
     /// Includes a header row, containing `self.keys()`.
     ///
+    /// If `with_env` is set, the header additionally contains all environment
+    /// variables of the runs (sorted together with the keys). Every row of a run
+    /// repeats its value of each variable, variables a run does not have are empty.
+    /// Outputs shadow environment variables of the same name.
+    ///
     /// Returns a Vector of all rows, with each entry being listed as a separate String.
     /// For example:
     /// ```csv
@@ -329,14 +654,26 @@ impl ExperimentSeries {
     ///     ["fortytwo", "42", "the best number"]
     /// ]
     /// ```
-    fn to_csv_rows(&self) -> Vec<Vec<String>> {
+    fn to_csv_rows(&self, with_env: bool) -> Vec<Vec<String>> {
         // sort runs by their repetition/env
         let mut sorted_runs = self.runs.clone();
         sorted_runs.sort_by_key(|run| run.run_dir_name().to_owned());
 
+        let keys = self.keys();
+        let mut columns: Vec<&str> = keys.clone();
+        if with_env {
+            columns.extend(
+                self.runs
+                    .iter()
+                    .flat_map(|run| run.environment().get_env_vars())
+                    .map(|var| var.as_str()),
+            );
+            columns.sort();
+            columns.dedup();
+        }
+
         // collect all header
-        let mut rows_vec: Vec<Vec<String>> =
-            vec![self.keys().iter().map(|k| k.to_string()).collect()];
+        let mut rows_vec: Vec<Vec<String>> = vec![columns.iter().map(|k| k.to_string()).collect()];
 
         let max_val_len = sorted_runs
             .iter()
@@ -348,25 +685,31 @@ impl ExperimentSeries {
         for i in 0..max_val_len {
             // for each run ...
             for run in &sorted_runs {
-                let mut row: Vec<String> = Vec::new();
-
-                // ... add ith element of each key to a list ...
-                for key in self.keys() {
-                    if let Some(vals) = &run.out_var(key) {
-                        row.push(vals.get(i).cloned().unwrap_or_else(String::new));
-                    } else {
-                        row.push(String::new())
-                    }
-                }
-
-                // ... and save the list for this run
-                rows_vec.push(row);
+                rows_vec.push(Self::csv_row(run, &columns, &keys, i));
             }
         }
 
         rows_vec
     }
 
+    /// Returns the `i`th element of each column in `run`, as one row of `to_csv_rows()`.
+    ///
+    /// `columns` that are not in `keys` are taken from the environment of `run`.
+    fn csv_row(run: &ExperimentRun, columns: &[&str], keys: &[&str], i: usize) -> Vec<String> {
+        columns
+            .iter()
+            .map(|column| match run.out_var(column) {
+                Some(vals) => vals.get(i).cloned().unwrap_or_default(),
+                None if keys.contains(column) => String::new(),
+                None => run
+                    .environment()
+                    .get_env_val(column)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
     /// Checks if there is anything recorded in self.runs
     ///
     /// Returns `true` if any of this true:
@@ -409,7 +752,7 @@ impl ExperimentSeries {
     fn from_out_lists(list_of_envlist: Vec<OutList>) -> Self {
         let runs: Vec<ExperimentRun> = list_of_envlist
             .iter()
-            .map(|envlist| ExperimentRun::from_out_list_unchecked(&envlist))
+            .map(ExperimentRun::from_out_list_unchecked)
             .collect();
 
         let (rdr, wtr) = std::io::pipe().unwrap();
@@ -418,10 +761,11 @@ impl ExperimentSeries {
         ExperimentSeries {
             source: ExperimentSource::new(),
             path: None,
-            runs: runs,
+            runs,
             stdout_log: String::new(),
             stderr_log: String::new(),
             exomat_log: rdr,
+            options: RunOptions::default(),
         }
     }
 }
@@ -430,34 +774,153 @@ impl ExperimentSeries {
 impl CsvWriter for ExperimentSeries {
     /// Serializes it's content into `file`.
     ///
+    /// Columns are sorted by name (see `keys()`) and every row lists its values
+    /// in the same order, so the same results always produce the same file.
+    ///
     /// If the no runs are found or all runs are empty, `file` will still be created.
     ///
-    /// Uses the default CSV delimiter `,`. Any values containing it will be escaped using
-    /// `""`.
+    /// Uses the default CSV delimiter `,` and includes the environment variables
+    /// of each run, see `to_csv_with()`.
     ///
     /// ## Errors
     /// - Returns a `CsvError` if something went wrong during the csv serialization
     fn to_csv(&self, csv_file: &Path) -> Result<()> {
-        let mut wtr = Writer::from_path(csv_file).map_err(|e| Error::CsvError {
-            reason: e.to_string(),
-        })?;
+        self.to_csv_with(csv_file, &TableOptions::default())
+    }
+}
 
-        if !self.runs_are_empty() {
-            // turn self.runs into csv rows (contains header)
-            let content = self.to_csv_rows();
-            debug!("series contains content: {:?}", content);
+impl ExperimentSeries {
+    /// Serializes it's content into `file` like `to_csv()`, but separates the
+    /// fields with `options.delimiter`.
+    ///
+    /// Any values containing the delimiter will be escaped using `""`. Outputs with
+    /// multiple lines still result in one row per line (see `to_csv_rows()`),
+    /// independent of the delimiter.
+    ///
+    /// If `options.with_env` is set, the environment variables of each run are
+    /// added as columns as well, unless an output has the same name.
+    ///
+    /// ## Errors
+    /// - Returns a `CsvError` if something went wrong during the csv serialization
+    pub fn to_csv_with(&self, csv_file: &Path, options: &TableOptions) -> Result<()> {
+        self.to_csv_writer(create_csv_file(csv_file)?, options)
+    }
 
-            for row in content {
-                wtr.write_record(row).map_err(|e| Error::CsvError {
-                    reason: e.to_string(),
-                })?;
-            }
+    /// Serializes it's content into `writer` like `to_csv_with()`, e.g. to write
+    /// it to stdout.
+    ///
+    /// ## Errors
+    /// - Returns a `CsvError` if something went wrong during the csv serialization
+    pub fn to_csv_writer(&self, writer: impl Write, options: &TableOptions) -> Result<()> {
+        let mut wtr = WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .from_writer(writer);
+
+        // turn self.runs into csv rows (contains header)
+        let content = self.to_rows(options.with_env);
+        debug!("series contains content: {:?}", content);
+
+        for row in content {
+            wtr.write_record(row).map_err(|e| Error::CsvError {
+                reason: e.to_string(),
+            })?;
         }
 
         wtr.flush().map_err(|e| Error::CsvError {
             reason: e.to_string(),
         })
     }
+
+    /// Collects the outputs of the Experiment Series at `exp_series_dir` into
+    /// `writer` like `parse_with()` followed by `to_csv_writer()`, but keeps only
+    /// one run in memory at a time.
+    ///
+    /// All runs are parsed twice: first to find all columns, then to write their
+    /// rows. Unlike `to_csv_with()`, all rows of a run are written next to each
+    /// other, instead of the first row of every run followed by the second row of
+    /// every run and so on. The content of the file is the same otherwise.
+    ///
+    /// Returns all output keys, sorted by name.
+    ///
+    /// ## Errors
+    /// - Returns a `ReaderError` if a run could not be parsed
+    /// - Returns a `CsvError` if something went wrong during the csv serialization
+    pub fn stream_csv(
+        exp_series_dir: &Path,
+        writer: impl Write,
+        options: &TableOptions,
+    ) -> Result<Vec<String>> {
+        let mut run_dirs = <ExperimentSeries as FileReader>::find_all_files(
+            &exp_series_dir.join(SERIES_RUNS_DIR),
+        )?;
+        run_dirs.sort_by_key(|run| file_name_string(run));
+
+        // first pass: columns and number of rows per run
+        let mut keys: Vec<String> = vec![];
+        let mut vars: Vec<String> = vec![];
+        let mut max_val_len = 0;
+        for run_dir in &run_dirs {
+            let run = Self::parse_run(run_dir, options)?;
+            keys.extend(run.out_files().iter().map(|f| f.var_name().clone()));
+            if options.with_env {
+                vars.extend(run.environment().get_env_vars().into_iter().cloned());
+            }
+            max_val_len = max_val_len.max(run.out_files().max_length());
+
+            // deduplicate right away, so memory does not grow with the number of runs
+            keys.sort();
+            keys.dedup();
+            vars.sort();
+            vars.dedup();
+        }
+
+        let to_csv_error = |e: csv::Error| Error::CsvError {
+            reason: e.to_string(),
+        };
+        let mut wtr = WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .from_writer(writer);
+
+        // no runs or all runs empty
+        if keys.is_empty() {
+            wtr.flush()?;
+            return Ok(keys);
+        }
+
+        let keys_ref: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let columns: Vec<&str> = keys_ref
+            .iter()
+            .copied()
+            .chain(vars.iter().map(String::as_str))
+            .sorted()
+            .dedup()
+            .collect();
+        wtr.write_record(&columns).map_err(to_csv_error)?;
+
+        // second pass: rows of one run after the other
+        for run_dir in &run_dirs {
+            let mut run = Self::parse_run(run_dir, options)?;
+            Self::fill_missing_keys_of(&mut run, &keys, &options.na_value);
+
+            for i in 0..max_val_len {
+                let row = Self::csv_row(&run, &columns, &keys_ref, i);
+                wtr.write_record(row).map_err(to_csv_error)?;
+            }
+        }
+
+        wtr.flush()?;
+        Ok(keys)
+    }
+}
+
+/// Creates (or truncates) `csv_file` for writing a table.
+///
+/// ## Errors
+/// - Returns a `CsvError` if the file could not be created
+pub fn create_csv_file(csv_file: &Path) -> Result<std::fs::File> {
+    std::fs::File::create(csv_file).map_err(|e| Error::CsvError {
+        reason: format!("cannot create {}: {e}", csv_file.display()),
+    })
 }
 
 impl FileWriter for ExperimentSeries {
@@ -467,6 +930,10 @@ impl FileWriter for ExperimentSeries {
     /// ```notest
     /// SERIES_DIR
     ///   |-> .exomat_series
+    ///   |-> .exomat_seed  [seed of the run order, if runs were shuffled]
+    ///   |-> [SERIES_HOST_INFO]  [hostname, kernel, CPU, memory and exomat version]
+    ///   |-> [SERIES_PROVENANCE]  [git commit of the source, if inside of a repository]
+    ///   |-> [SERIES_MANIFEST]  [see manifest()]
     ///   |-> [SERIES_SRC_DIR]
     ///   | |-> .exomat_source_cp  [replaces .exomat_source]
     ///   | \-> [copy of experiment source directory, read-only]
@@ -511,7 +978,7 @@ impl FileWriter for ExperimentSeries {
 
         // check if series dir is valid
         fn is_child_dir_of_of(maybe_child: &Path, parent: &Path) -> Result<bool> {
-            let parent = parent.canonicalize()?;
+            let parent = canonicalize(parent)?;
 
             Ok(maybe_child
                 .ancestors()
@@ -540,8 +1007,30 @@ impl FileWriter for ExperimentSeries {
         let _ = create_harness_file(&runs.join(SERIES_STDERR_LOG))?;
         let _ = create_harness_file(&runs.join(SERIES_EXOMAT_LOG))?;
 
+        // allows repeating the order of runs with `--seed`
+        if let Some(seed) = self.options.seed {
+            write(exp_series_dir.join(SERIES_SEED), seed.to_string())?;
+        }
+
+        // describes the machine the series is executed on
+        write(
+            exp_series_dir.join(SERIES_HOST_INFO),
+            HostInfo::collect().to_json(),
+        )?;
+
+        // git commit of the source, if it is inside of a repository
+        if let Some(provenance) = Provenance::of(self.source.location()) {
+            write(exp_series_dir.join(SERIES_PROVENANCE), provenance.to_env())?;
+        }
+
+        // describes the inputs of all runs, for tools that do not parse directory names
+        write(
+            exp_series_dir.join(SERIES_MANIFEST),
+            format!("{:#}", self.manifest()?),
+        )?;
+
         // copy exp_source/template to src and replace marker
-        copy_harness_dir(self.source.location(), &src)?;
+        copy_harness_dir(self.source.location(), &src, self.options.symlinks)?;
         std::fs::remove_file(src.join(MARKER_SRC))?;
         create_harness_file(&src.join(MARKER_SRC_CP))?;
 
@@ -600,84 +1089,43 @@ impl FileReader for ExperimentSeries {
 
     /// Parses an Experiment Series directory into an ExperimentSeries object.
     ///
+    /// Same as `parse_with()` using the default TableOptions.
+    ///
     /// ### Error
     /// - Returns a `ReaderError` if any RunReader failed to parse
     fn parse(exp_series_dir: &Path) -> Result<Self::Item> {
-        debug!("looking for experiment runs");
-        let runs =
-            <ExperimentSeries as FileReader>::find_all_files(&exp_series_dir.join(SERIES_RUNS_DIR))
-                .iter()
-                .map(|run| {
-                    ExperimentRun::parse(run).map_err(|e| Error::ReaderError {
-                        dir: run.display().to_string(),
-                        reason: e.to_string(),
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
-
-        debug!("reading log files");
-        let stdout_log =
-            read_to_string(exp_series_dir.join(SERIES_RUNS_DIR).join(SERIES_STDOUT_LOG))
-                .unwrap_or_default();
-        let stderr_log =
-            read_to_string(exp_series_dir.join(SERIES_RUNS_DIR).join(SERIES_STDERR_LOG))
-                .unwrap_or_default();
-
-        let mut reader = ExperimentSeries {
-            source: ExperimentSource::new(),
-            path: Some(exp_series_dir.to_path_buf()),
-            runs,
-            stdout_log,
-            stderr_log,
-            exomat_log: duplicate_log_to_pipe()?,
-        };
-
-        debug!("adding missing keys");
-        reader.fill_missing_keys();
-        Ok(reader)
+        Self::parse_with(exp_series_dir, &TableOptions::default())
     }
 
     /// Builds and returns a vector of all run repetitions in the given directory.
     ///
-    /// A directory is considered a run repetition, if it's name starts with "run_".
+    /// A directory is considered a run repetition, if it's name starts with
+    /// [SERIES_RUN_PREFIX]. Warmup runs ([SERIES_WARMUP_PREFIX]) are ignored, as
+    /// are directories with a name that is not valid UTF-8.
     ///
-    /// ## Panics
-    /// - Panics if directory traversal went wrong
-    fn find_all_files(runs_dir: &Path) -> Vec<PathBuf> {
-        let mut repetitions = Vec::<PathBuf>::new();
-
+    /// ## Errors
+    /// - Returns a `ReaderError` if `runs_dir` or any of its entries cannot be read
+    fn find_all_files(runs_dir: &Path) -> Result<Vec<PathBuf>> {
         // return the empty vector if runs_dir does not exist
         if !runs_dir.is_dir() {
             trace!("no experiment runs found");
-            return repetitions;
+            return Ok(vec![]);
         }
 
-        for entry in runs_dir.read_dir().expect("Could not read dir") {
-            if entry
-                .as_ref()
-                .expect("Entry not readable")
-                .metadata()
-                .expect("Metadata of entry not readable")
-                .is_dir()
-            {
-                // if directory name starts with "run_", it is considered a run repetition
-                if entry
-                    .as_ref()
-                    .expect("unreadyble entry")
-                    .path() // complete path
-                    .file_name() // last part of path; directory name
-                    .expect("entry has inaccessable file name")
-                    .to_str()
-                    .expect("cannot stringify file name")
-                    .starts_with("run_")
-                {
-                    trace!("found run: {}", entry.as_ref().unwrap().path().display());
-                    repetitions.push(entry.unwrap().path());
-                }
+        let mut repetitions = Vec::<PathBuf>::new();
+        for (path, metadata) in read_dir_entries(runs_dir)? {
+            // if directory name starts with "run_", it is considered a run repetition
+            let is_run = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(SERIES_RUN_PREFIX));
+            if metadata.is_dir() && is_run {
+                trace!("found run: {}", path.display());
+                repetitions.push(path);
             }
         }
 
-        repetitions
+        Ok(repetitions)
     }
 }
 
@@ -762,7 +1210,7 @@ mod tests {
         setup_series_empty_out, setup_series_no_out, skeleton_series_run,
         skeleton_series_run_empty, skeleton_src,
     };
-    use crate::helper::test_helper::{contains_either, create_out_file};
+    use crate::helper::test_helper::create_out_file;
     use rstest::rstest;
     use rusty_fork::rusty_fork_test;
     use tempfile::TempDir;
@@ -775,7 +1223,7 @@ mod tests {
             // create base tempdir, to act as parent
             let tmpdir = TempDir::new().unwrap();
             let tmpdir = tmpdir.path();
-            std::env::set_current_dir(&tmpdir).unwrap();
+            std::env::set_current_dir(tmpdir).unwrap();
 
             //create experiment source dir
             let exp_source = tmpdir.join("FooSource");
@@ -796,9 +1244,191 @@ mod tests {
             assert!(exp_series.join(SERIES_RUNS_DIR).join(SERIES_STDOUT_LOG).is_file());
             assert!(exp_series.join(SERIES_RUNS_DIR).join(SERIES_STDERR_LOG).is_file());
 
+            let host: serde_json::Value =
+                serde_json::from_str(&read_to_string(exp_series.join(SERIES_HOST_INFO)).unwrap()).unwrap();
+            assert_eq!(host["exomat_version"], env!("CARGO_PKG_VERSION"));
+
             // content of experiment source have been copied to exp_series/src
             // .exomat_source changed to .exomat_source_cp
         }
+
+        #[test]
+        fn seeded_run_order() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path();
+            std::env::set_current_dir(tmpdir).unwrap();
+
+            let mut source = ExperimentSource::new();
+            source.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("FooSource"), 3));
+            source
+                .set_envs(HashMap::from_iter((0..4).map(|i| {
+                    (
+                        PathBuf::from(format!("{i}.env")),
+                        Environment::from_env_list(vec![("FOO".to_string(), i.to_string())]),
+                    )
+                })))
+                .unwrap();
+            source.persist(&tmpdir.join("FooSource")).unwrap();
+
+            let run_order = |seed: Option<u64>, dir: &str| {
+                let mut series = ExperimentSeries::from_source(&source).unwrap();
+                series.set_run_options(RunOptions {
+                    seed,
+                    ..Default::default()
+                });
+                series.generate_runs().unwrap();
+                series.persist(&tmpdir.join(dir)).unwrap();
+
+                let seed = read_to_string(tmpdir.join(dir).join(SERIES_SEED)).unwrap();
+                let order: Vec<String> = series
+                    .runs()
+                    .iter()
+                    .map(|run| run.run_dir_name().to_string())
+                    .collect();
+                (seed, order)
+            };
+
+            let (seed, order) = run_order(Some(42), "first");
+            assert_eq!(seed, "42");
+            assert_eq!(run_order(Some(42), "second").1, order);
+
+            // a random seed is recorded and reproduces the same order
+            let (seed, order) = run_order(None, "random");
+            let (_, repeated) = run_order(Some(seed.parse().unwrap()), "repeated");
+            assert_eq!(repeated, order);
+
+            // RUN_INDEX follows the shuffled order
+            let mut series = ExperimentSeries::from_source(&source).unwrap();
+            series.set_run_options(RunOptions {
+                seed: Some(42),
+                ..Default::default()
+            });
+            series.generate_runs().unwrap();
+            series.persist(&tmpdir.join("indexed")).unwrap();
+            for (index, run) in series.runs().iter().enumerate() {
+                let env_file = tmpdir.join("indexed").join(SERIES_RUNS_DIR).join(run.run_dir_name()).join(RUN_ENV_FILE);
+                let env = Environment::from_file(&env_file).unwrap();
+                assert_eq!(env.get_env_val("RUN_INDEX"), Some(&index.to_string()));
+                assert_eq!(env.get_env_val("RUN_TOTAL"), Some(&"12".to_string()));
+            }
+
+            // the manifest lists the runs in the same order
+            let manifest: serde_json::Value =
+                serde_json::from_str(&read_to_string(tmpdir.join("indexed").join(SERIES_MANIFEST)).unwrap()).unwrap();
+            assert_eq!(manifest["experiment"], "FooSource");
+            assert_eq!(manifest["repetitions"], 3);
+            assert_eq!(manifest["environments"]["2.env"]["FOO"], "2");
+            let names: Vec<&str> = series.runs().iter().map(|run| run.run_dir_name()).collect();
+            assert_eq!(manifest["runs"], serde_json::json!(names));
+            assert!(manifest["created"].is_string());
+        }
+    }
+
+    #[test]
+    fn generate_runs_reserved_envs() {
+        let tmpdir = TempDir::new().unwrap();
+
+        let mut source = ExperimentSource::new();
+        source.set_exomat_envs(ExomatEnvironment::new(&tmpdir.path().join("FooSource"), 1));
+        source
+            .set_envs(HashMap::from_iter([0, 1].map(|i| {
+                (
+                    PathBuf::from(format!("{i}.env")),
+                    Environment::from_env_list(vec![
+                        ("REPETITION".to_string(), "3".to_string()),
+                        ("lower".to_string(), "ok".to_string()),
+                    ]),
+                )
+            })))
+            .unwrap();
+
+        let mut series = ExperimentSeries::from_source(&source).unwrap();
+        let err = series.generate_runs().unwrap_err().to_string();
+        assert!(err.contains("0.env: Invalid environment: 'REPETITION' is reserved"));
+        assert!(err.contains("1.env: Invalid environment: 'REPETITION' is reserved"));
+        assert!(!err.contains("lower"));
+        assert!(series.runs().is_empty());
+    }
+
+    #[test]
+    fn find_runs_with_invalid_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let tmpdir = TempDir::new().unwrap();
+        let runs_dir = tmpdir.path();
+        std::fs::create_dir(runs_dir.join("run_0_rep0")).unwrap();
+        std::fs::create_dir(runs_dir.join(OsStr::from_bytes(b"run_\xff"))).unwrap();
+        std::fs::write(runs_dir.join("run_1_rep0"), "").unwrap();
+
+        let runs = <ExperimentSeries as FileReader>::find_all_files(runs_dir).unwrap();
+        assert_eq!(runs, vec![runs_dir.join("run_0_rep0")]);
+    }
+
+    #[test]
+    fn generate_runs_with_base_env() {
+        let tmpdir = TempDir::new().unwrap();
+
+        let mut source = ExperimentSource::new();
+        source.set_exomat_envs(ExomatEnvironment::new(&tmpdir.path().join("FooSource"), 1));
+        source.set_base_env(Environment::from_env_list(vec![
+            ("SHARED".to_string(), "base".to_string()),
+            ("SIZE".to_string(), "1".to_string()),
+        ]));
+        source
+            .set_envs(HashMap::from([(
+                PathBuf::from("0.env"),
+                Environment::from_env_list(vec![("SIZE".to_string(), "2".to_string())]),
+            )]))
+            .unwrap();
+
+        let mut series = ExperimentSeries::from_source(&source).unwrap();
+        series.generate_runs().unwrap();
+        let env = series.runs()[0].environment();
+        assert_eq!(env.get_env_val("SHARED"), Some(&"base".to_string()));
+        assert_eq!(env.get_env_val("SIZE"), Some(&"2".to_string()));
+
+        // the base env is validated like the env files
+        source.set_base_env(Environment::from_env_list(vec![(
+            "REPETITION".to_string(),
+            "3".to_string(),
+        )]));
+        let mut series = ExperimentSeries::from_source(&source).unwrap();
+        let err = series.generate_runs().unwrap_err().to_string();
+        assert!(err.contains("template/base.env: Invalid environment: 'REPETITION' is reserved"));
+    }
+
+    #[test]
+    fn ordered_run_order() {
+        let tmpdir = TempDir::new().unwrap();
+
+        let mut source = ExperimentSource::new();
+        source.set_exomat_envs(ExomatEnvironment::new(&tmpdir.path().join("FooSource"), 2));
+        source
+            .set_envs(HashMap::from_iter(["b", "a", "c"].map(|name| {
+                (PathBuf::from(format!("{name}.env")), Environment::new())
+            })))
+            .unwrap();
+
+        let mut series = ExperimentSeries::from_source(&source).unwrap();
+        series.set_run_options(RunOptions {
+            ordered: true,
+            ..Default::default()
+        });
+        series.generate_runs().unwrap();
+
+        let order: Vec<&str> = series.runs().iter().map(|run| run.run_dir_name()).collect();
+        assert_eq!(
+            order,
+            vec![
+                "run_a_rep0",
+                "run_a_rep1",
+                "run_b_rep0",
+                "run_b_rep1",
+                "run_c_rep0",
+                "run_c_rep1"
+            ]
+        );
     }
 
     #[test]
@@ -813,8 +1443,8 @@ mod tests {
         // iterate over runs and observations
         for run in series_reader.iter() {
             for obs in run.iter() {
-                assert!(obs.get("number").is_some());
-                assert!(obs.get("word").is_some());
+                assert!(obs.contains_key("number"));
+                assert!(obs.contains_key("word"));
             }
         }
     }
@@ -874,12 +1504,114 @@ mod tests {
         let reader = ExperimentSeries::from_out_lists(vec![outlist_mixed_weird]);
         reader.to_csv(&out_file).unwrap();
 
-        // with multiple keys and values the order of items after serialization is
-        // random, so only check if the correct lines are there
-        let file_2 = std::fs::read_to_string(out_file).unwrap();
-        assert!(contains_either(&file_2, "VAR1,VAR2\n", "VAR2,VAR1\n"));
-        assert!(contains_either(&file_2, "VALUE,\n", ",VALUE\n"));
-        assert!(contains_either(&file_2, "\"a,b\",baz\n", "baz,\"a,b\"\n"));
+        // columns are sorted by name, every row uses the same order
+        let file_2 = std::fs::read(&out_file).unwrap();
+        assert_eq!(file_2, b"VAR1,VAR2\nVALUE,\nbaz,\"a,b\"\n");
+
+        // any writer gets the same content
+        let mut buffer: Vec<u8> = vec![];
+        reader
+            .to_csv_writer(&mut buffer, &TableOptions::default())
+            .unwrap();
+        assert_eq!(buffer, file_2);
+
+        // the order does not depend on the order of the out_ files
+        let reversed = OutList::from(vec![
+            OutFile::from("VAR2", vec![String::new(), "a,b".to_string()]),
+            OutFile::from("VAR1", vec!["VALUE".to_string(), "baz".to_string()]),
+        ])
+        .unwrap();
+        ExperimentSeries::from_out_lists(vec![reversed])
+            .to_csv(&out_file)
+            .unwrap();
+        assert_eq!(std::fs::read(&out_file).unwrap(), file_2);
+
+        // with another delimiter, the comma does not need to be quoted anymore
+        let options = TableOptions {
+            delimiter: b'\t',
+            ..Default::default()
+        };
+        reader.to_csv_with(&out_file, &options).unwrap();
+        assert_eq!(
+            std::fs::read(&out_file).unwrap(),
+            b"VAR1\tVAR2\nVALUE\t\nbaz\ta,b\n"
+        );
+    }
+
+    #[rstest]
+    fn seriesreader_serialize_env(filled_series_run_na: TempDir) {
+        let dir = filled_series_run_na.path().to_path_buf();
+        let runs = dir.join(SERIES_RUNS_DIR);
+        std::fs::write(
+            runs.join(TEST_RUN_REP_DIR0).join(RUN_ENV_FILE),
+            "THREADS=1\nREPETITION=0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            runs.join(TEST_RUN_REP_DIR1).join(RUN_ENV_FILE),
+            "THREADS=2\nREPETITION=1\nSIZE=big\nsome=shadowed\n",
+        )
+        .unwrap();
+        create_out_file(&dir, Some(TEST_RUN_REP_DIR0), "out_some", "foo\nbar");
+        create_out_file(&dir, Some(TEST_RUN_REP_DIR1), "out_some", "baz");
+
+        let reader = ExperimentSeries::parse(&dir).unwrap();
+        let out_file = dir.join("series.csv");
+
+        // variables are repeated in every row of a run, outputs replace variables
+        reader.to_csv(&out_file).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out_file).unwrap(),
+            "REPETITION,SIZE,THREADS,empty,some\n\
+             0,,1,,foo\n\
+             1,big,2,NA,baz\n\
+             0,,1,,bar\n\
+             1,big,2,,\n"
+        );
+
+        let options = TableOptions {
+            with_env: false,
+            ..Default::default()
+        };
+        reader.to_csv_with(&out_file, &options).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out_file).unwrap(),
+            "empty,some\n,foo\nNA,baz\n,bar\n,\n"
+        );
+
+        // same rows when streaming, but grouped by run
+        let keys = ExperimentSeries::stream_csv(
+            &dir,
+            create_csv_file(&out_file).unwrap(),
+            &TableOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(keys, vec!["empty", "some"]);
+        assert_eq!(
+            std::fs::read_to_string(&out_file).unwrap(),
+            "REPETITION,SIZE,THREADS,empty,some\n\
+             0,,1,,foo\n\
+             0,,1,,bar\n\
+             1,big,2,NA,baz\n\
+             1,big,2,,\n"
+        );
+    }
+
+    #[rstest]
+    fn seriesreader_stream_empty(
+        #[from(skeleton_series_run_empty)] dir: TempDir,
+        #[from(skeleton_src)] outdir: TempDir,
+    ) {
+        let out_file = outdir.path().join("empty.csv");
+        let keys = ExperimentSeries::stream_csv(
+            dir.path(),
+            create_csv_file(&out_file).unwrap(),
+            &TableOptions::default(),
+        )
+        .unwrap();
+
+        assert!(keys.is_empty());
+        assert_eq!(std::fs::read_to_string(out_file).unwrap(), "");
     }
 
     #[rstest]
@@ -956,6 +1688,108 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case("null")]
+    #[case("")]
+    fn seriesreader_parse_custom_na(filled_series_run_na: TempDir, #[case] na_value: &str) {
+        let options = TableOptions {
+            na_value: na_value.to_string(),
+            ..Default::default()
+        };
+        let reader = ExperimentSeries::parse_with(filled_series_run_na.path(), &options).unwrap();
+
+        // run without out_empty gets the custom value
+        let filled = reader
+            .runs()
+            .iter()
+            .find(|run| run.run_dir_name() == TEST_RUN_REP_DIR1)
+            .unwrap();
+        assert_eq!(
+            filled.out_var("empty").unwrap(),
+            &vec![na_value.to_string()]
+        );
+        assert!(!reader.to_columns()["empty"].contains(&"NA".to_string()));
+    }
+
+    #[test]
+    fn seriesreader_fill_missing_multiline() {
+        let tmpdir = TempDir::new().unwrap();
+        for (run, a, b) in [
+            ("run_0_rep0", "1", Some("x")),
+            ("run_1_rep0", "2\n3", None),
+            ("run_2_rep0", "4", Some("y")),
+        ] {
+            create_out_file(tmpdir.path(), Some(run), RUN_RUN_FILE, "");
+            create_out_file(tmpdir.path(), Some(run), "out_a", a);
+            if let Some(b) = b {
+                create_out_file(tmpdir.path(), Some(run), "out_b", b);
+            }
+        }
+
+        let reader = ExperimentSeries::parse(tmpdir.path()).unwrap();
+
+        // the run without out_b gets NA in each of its rows
+        for run in reader.runs() {
+            assert_eq!(
+                run.out_var("b").unwrap().len(),
+                run.out_var("a").unwrap().len()
+            );
+        }
+        let columns = reader.to_columns();
+        assert_eq!(columns["a"], vec!["1", "2", "4", "", "3", ""]);
+        assert_eq!(columns["b"], vec!["x", "NA", "y", "", "NA", ""]);
+
+        // same values when streaming
+        let out_file = tmpdir.path().join("series.csv");
+        ExperimentSeries::stream_csv(
+            tmpdir.path(),
+            create_csv_file(&out_file).unwrap(),
+            &TableOptions {
+                with_env: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out_file).unwrap(),
+            "a,b\n1,x\n,\n2,NA\n3,NA\n4,y\n,\n"
+        );
+    }
+
+    #[test]
+    fn seriesreader_parse_explode_json() {
+        let tmpdir = TempDir::new().unwrap();
+        for (run, json) in [
+            ("run_0_rep0", "{\"latency\": 5, \"extra\": {\"ok\": true}}"),
+            ("run_1_rep0", "{\"latency\": 7}"),
+        ] {
+            create_out_file(tmpdir.path(), Some(run), RUN_RUN_FILE, "");
+            create_out_file(tmpdir.path(), Some(run), "out_result.json", json);
+            create_out_file(tmpdir.path(), Some(run), "out_id", run);
+        }
+
+        let options = TableOptions {
+            explode_json: true,
+            ..Default::default()
+        };
+        let reader = ExperimentSeries::parse_with(tmpdir.path(), &options).unwrap();
+        assert_eq!(
+            reader.keys(),
+            vec!["id", "result.extra.ok", "result.latency"]
+        );
+
+        let columns = reader.to_columns();
+        let row = |id: &str| columns["id"].iter().position(|v| v == id).unwrap();
+        assert_eq!(columns["result.latency"][row("run_0_rep0")], "5");
+        assert_eq!(columns["result.latency"][row("run_1_rep0")], "7");
+        assert_eq!(columns["result.extra.ok"][row("run_0_rep0")], "true");
+        assert_eq!(columns["result.extra.ok"][row("run_1_rep0")], "NA");
+
+        // not flattened by default, only the extension is removed
+        let reader = ExperimentSeries::parse(tmpdir.path()).unwrap();
+        assert_eq!(reader.keys(), vec!["id", "result"]);
+    }
+
     #[rstest]
     fn seriesreader_parse_duplicates(filled_series_run_duplicate: TempDir) {
         let dir = filled_series_run_duplicate.path().to_path_buf();
@@ -964,8 +1798,10 @@ mod tests {
 
         let res = &reader.runs()[0];
 
+        // out_some and out_some.txt both result in some, the second one is numbered
         assert!(res.out_var("some").is_some());
-        assert!(res.out_var("some.txt").is_some());
+        assert!(res.out_var("some_2").is_some());
+        assert!(res.out_var("some.txt").is_none());
     }
 
     #[rstest]
@@ -1077,11 +1913,11 @@ mod tests {
         // check results
         let some0 = OutFile::from("some", vec![String::from("bar")]);
         let empty0 = OutFile::from("empty", vec![String::from("NA")]);
-        let emptytxt0 = OutFile::from("empty.txt", vec![String::from("NA")]);
+        let emptytxt0 = OutFile::from("empty_2", vec![String::from("NA")]);
 
         let some1 = OutFile::from("some", vec![String::from("foo")]);
         let empty1 = OutFile::from("empty", vec![String::from("")]);
-        let emptytxt1 = OutFile::from("empty.txt", vec![String::from("")]);
+        let emptytxt1 = OutFile::from("empty_2", vec![String::from("")]);
 
         assert_eq!(reader.run_count(), 2);
 
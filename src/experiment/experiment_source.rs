@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use log::{debug, info, warn};
 use std::{
     collections::HashMap,
@@ -7,18 +8,29 @@ use std::{
 
 use crate::experiment::{FileReader, FileWriter};
 use crate::harness::env::{
-    get_existing_environments_by_fname, Environment, EnvironmentContainer, EnvironmentLocationList,
-    ExomatEnvironment,
+    fetch_environment_files, get_existing_environments_by_fname, Environment, EnvironmentContainer,
+    EnvironmentLocationList, ExomatEnvironment,
 };
-use crate::helper::archivist::{create_harness_dir, create_harness_file};
+use crate::helper::archivist::{canonicalize, create_harness_dir, create_harness_file};
 use crate::helper::errors::{Error, Result};
 use crate::helper::fs_names::*;
+use crate::helper::glob::matches;
+
+/// Template of the README of a new Experiment Source, `{{EXPERIMENT}}`,
+/// `{{VARIABLES}}` and `{{RUN_FILE}}` are replaced when persisting
+const README_TEMPLATE: &str = include_str!("../harness/README.template");
+
+/// Template of the .gitignore of a new Experiment Source, ignoring series and
+/// trial directories, `{{EXPERIMENT}}` is replaced when persisting
+const GITIGNORE_TEMPLATE: &str = include_str!("../harness/gitignore.template");
 
 /// Container for an Experiment Source
 #[derive(Debug, Clone)]
 pub struct ExperimentSource {
     run_sh: String,
+    run_file: String,
     envs: EnvironmentLocationList,
+    base_env: Environment,
     exomat_envs: ExomatEnvironment,
 }
 
@@ -34,14 +46,18 @@ impl ExperimentSource {
     ///
     /// The following default values are set:
     /// - `run_sh`: content of `harness/run.sh.template`
+    /// - `run_file`: SRC_RUN_FILE
     /// - `envs`: empty HashMap
+    /// - `base_env`: empty Environment
     /// - `exomat_envs`:
     ///     - `exp_src_dir`: empty PathBuf
     ///     - `repetition`: 1
     pub fn new() -> Self {
         ExperimentSource {
             run_sh: include_str!("../harness/run.sh.template").to_string(),
+            run_file: SRC_RUN_FILE.to_string(),
             envs: HashMap::new(),
+            base_env: Environment::new(),
             exomat_envs: ExomatEnvironment::new(&PathBuf::new(), 1),
         }
     }
@@ -50,6 +66,7 @@ impl ExperimentSource {
     ///
     /// The following default values are set:
     /// - `run_sh`: copied
+    /// - `run_file`: copied
     /// - `envs`: one random Environment from self.envs (or an empty Environment, if self.envs is empty)
     /// - `exomat_envs`:
     ///     - `exp_src_dir`: copied
@@ -81,11 +98,10 @@ impl ExperimentSource {
 
         Self {
             run_sh: self.run_sh.clone(),
+            run_file: self.run_file.clone(),
             envs: trial_env,
-            exomat_envs: ExomatEnvironment {
-                exp_src_dir: self.location().to_path_buf(),
-                repetition: 1,
-            },
+            base_env: self.base_env.clone(),
+            exomat_envs: ExomatEnvironment::new(&self.location().to_path_buf(), 1),
         }
     }
 
@@ -98,6 +114,27 @@ impl ExperimentSource {
         &self.envs
     }
 
+    /// A reference to the variables shared by all Environments
+    ///
+    /// Stores the content of `[experiment]/SRC_TEMPLATE_DIR/SRC_BASE_ENV_FILE`,
+    /// empty if that file does not exist.
+    pub fn base_env(&self) -> &Environment {
+        &self.base_env
+    }
+
+    /// Returns `env` with all variables of `base_env()` it does not define.
+    ///
+    /// Values from `env` win over values from `base_env()`.
+    pub fn with_base_env(&self, env: &Environment) -> Environment {
+        let mut merged = self.base_env.clone();
+        merged.extend_envs(env);
+
+        let mut comments = env.comments().clone();
+        comments.extend(self.base_env.comments());
+        merged.set_comments(comments);
+        merged
+    }
+
     /// Returns the directory name of the Experiment Source.
     ///
     /// ## Errors
@@ -130,13 +167,39 @@ impl ExperimentSource {
         &self.exomat_envs.exp_src_dir
     }
 
+    /// Returns the README of a new Experiment Source at `exp_source_dir`, listing
+    /// the name of the experiment, its run script and all variables of its Environments.
+    fn readme(&self, exp_source_dir: &Path) -> String {
+        let variables: Vec<&String> = self
+            .envs
+            .values()
+            .flat_map(|env| env.get_env_vars())
+            .sorted()
+            .dedup()
+            .collect();
+        let variables = match variables.is_empty() {
+            true => "none yet, add some with `exomat env --add VAR VALUE...`".to_string(),
+            false => variables.iter().map(|v| format!("`{v}`")).join(", "),
+        };
+
+        README_TEMPLATE
+            .replace("{{EXPERIMENT}}", &file_name_string(exp_source_dir))
+            .replace("{{VARIABLES}}", &variables)
+            .replace("{{RUN_FILE}}", &self.run_file)
+    }
+
     /// A reference to the run script this Experiment will execute.
     ///
-    /// Stores the content of `self.location()/SRC_TEMPLATE_DIR/SRC_RUN_FILE``
+    /// Stores the content of `self.location()/SRC_TEMPLATE_DIR/self.run_file_name()`
     pub fn run_script(&self) -> &str {
         &self.run_sh
     }
 
+    /// File name of the run script inside of SRC_TEMPLATE_DIR, e.g. `run.sh` or `run.py`
+    pub fn run_file_name(&self) -> &str {
+        &self.run_file
+    }
+
     // ========================= setter ========================================
 
     /// Replace the run script
@@ -145,6 +208,11 @@ impl ExperimentSource {
         self.run_sh = script;
     }
 
+    /// Replace the file name of the run script, see `is_run_file()`
+    pub fn set_run_file_name(&mut self, name: &str) {
+        self.run_file = name.to_string();
+    }
+
     /// Replace envs
     ///
     /// ## Errors
@@ -164,6 +232,39 @@ impl ExperimentSource {
         Ok(())
     }
 
+    /// Removes all Environments whose env file name does not match `pattern`
+    /// (see `glob::matches()`).
+    ///
+    /// ## Errors
+    /// - Returns an `EnvError` if `pattern` matches none of the env files
+    pub fn filter_envs(&mut self, pattern: &str) -> Result<()> {
+        let total = self.envs.len();
+        self.envs.retain(|env_file, _| {
+            env_file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| matches(pattern, name))
+        });
+
+        match self.envs.is_empty() {
+            true => Err(Error::EnvError {
+                reason: format!("Filter '{pattern}' matches none of the {total} env files"),
+            }),
+            false => {
+                info!(
+                    "Filter '{pattern}' selected {} of {total} env files",
+                    self.envs.len()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the variables shared by all Environments
+    pub fn set_base_env(&mut self, base_env: Environment) {
+        self.base_env = base_env;
+    }
+
     /// Replace internal exomat variables
     pub fn set_exomat_envs(&mut self, exomat_envs: ExomatEnvironment) {
         self.exomat_envs = exomat_envs;
@@ -177,30 +278,36 @@ impl FileReader for ExperimentSource {
     /// Create an ExperimentSource based on a directory.
     ///
     /// The following values are set:
-    /// - `run_sh`: content of `dir/[SRC_TEMPLATE_DIR]/[SRC_RUN_FILE]`
+    /// - `run_sh`: content of the run script in `dir/[SRC_TEMPLATE_DIR]`, see `find_run_file()`
+    /// - `run_file`: file name of that run script
     /// - `envs`: content of `dir/[SRC_ENV_DIR]`
+    /// - `base_env`: content of `dir/[SRC_TEMPLATE_DIR]/[SRC_BASE_ENV_FILE]`, if it exists
     /// - `exomat_envs`:
     ///     - `exp_src_dir`: dir (absolute path)
     ///     - `repetition`: 1
     ///
-    /// ## Panics
+    /// ## Errors
+    /// - returns a `ReaderError` if the absolute path of `dir` cannot be build
     /// - returns an `IoError` if the run script could not be read
     /// - returns an `EnvError` if Environments could not be parsed
-    /// - panics if the absolute path of `dir` cannot be build
     fn parse(exp_source_dir: &Path) -> Result<Self::Item> {
-        let exomat_envs = ExomatEnvironment::new(
-            &exp_source_dir
-                .to_path_buf()
-                .canonicalize()
-                .expect("Could not resolve Source path"),
-            1,
-        );
-        let run_sh = read_to_string(exp_source_dir.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE))?;
+        let exomat_envs = ExomatEnvironment::new(&canonicalize(exp_source_dir)?, 1);
+        let template_dir = exp_source_dir.join(SRC_TEMPLATE_DIR);
+        let run_file_path =
+            find_run_file(&template_dir).unwrap_or_else(|| template_dir.join(SRC_RUN_FILE));
+        let run_sh = read_to_string(&run_file_path)?;
         let envs = get_existing_environments_by_fname(&exp_source_dir.join(SRC_ENV_DIR))?;
+        let base_env_path = template_dir.join(SRC_BASE_ENV_FILE);
+        let base_env = match base_env_path.is_file() {
+            true => Environment::from_file(&base_env_path)?,
+            false => Environment::new(),
+        };
 
         Ok(Self {
             run_sh,
+            run_file: file_name_string(&run_file_path),
             envs,
+            base_env,
             exomat_envs,
         })
     }
@@ -213,8 +320,11 @@ impl FileWriter for ExperimentSource {
     /// ```notest
     /// dir
     ///   |-> .exomat_source
+    ///   |-> SRC_README [content: README_TEMPLATE]
+    ///   |-> SRC_GITIGNORE [content: GITIGNORE_TEMPLATE]
     ///   |-> SRC_TEMPLATE_DIR/
-    ///   | \-> SRC_RUN_FILE [executable, content: self.run_sh]
+    ///   | |-> self.run_file [executable, content: self.run_sh]
+    ///   | \-> SRC_BASE_ENV_FILE [content: self.base_env, only if not empty]
     ///   \-> SRC_ENV_DIR/
     ///     | # if self.envs.is_empty
     ///     |-> SRC_ENV_FILE [EMPTY]
@@ -225,32 +335,82 @@ impl FileWriter for ExperimentSource {
     ///
     /// ## Errors
     /// - returns an `HarnessCreateError` if any entry of the list above could not be created.
-    /// - returns an `IoError` if the run script could not be written
+    /// - returns an `IoError` if the run script, README or .gitignore could not be written
     /// - returns an `EnvError` if Environment serialization failed
     fn persist(&mut self, exp_source_dir: &Path) -> Result<()> {
+        self.persist_with(exp_source_dir, false)
+    }
+}
+
+impl ExperimentSource {
+    /// Recreates the scaffolding of the experiment source folder at
+    /// `exp_source_dir`, like `persist()`, but in an existing folder.
+    ///
+    /// The run script is overwritten with `self.run_sh`, run scripts with another
+    /// name (e.g. `run.sh` when creating `run.py`) are removed. Everything else is only
+    /// created if it is missing: existing env files, the README and all other
+    /// files in SRC_TEMPLATE_DIR are kept. If `exp_source_dir` does not exist,
+    /// this is the same as `persist()`.
+    ///
+    /// ## Errors
+    /// - returns an `HarnessCreateError` if `exp_source_dir` exists, but is not an
+    ///   experiment source folder
+    /// - same errors as `persist()`
+    pub fn regenerate(&mut self, exp_source_dir: &Path) -> Result<()> {
+        if exp_source_dir.exists() && !exp_source_dir.join(MARKER_SRC).is_file() {
+            return Err(Error::HarnessCreateError {
+                entry: exp_source_dir.display().to_string(),
+                reason: format!("not an experiment source ({MARKER_SRC} is missing)"),
+            });
+        }
+
+        self.persist_with(exp_source_dir, true)
+    }
+
+    /// Creates the experiment source folder, see `persist()`. With `force`,
+    /// existing entries are kept, except for the run script which is replaced.
+    fn persist_with(&mut self, exp_source_dir: &Path, force: bool) -> Result<()> {
+        // with force, existing files are kept
+        let create_file = |file: PathBuf| -> Result<bool> {
+            match force && file.exists() {
+                true => Ok(false),
+                false => create_harness_file(&file).map(|_| true),
+            }
+        };
+
         create_harness_dir(&exp_source_dir.to_path_buf())?;
-        create_harness_file(&exp_source_dir.join(MARKER_SRC))?;
+        create_file(exp_source_dir.join(MARKER_SRC))?;
 
         // create envs if some are given, otherwise just create an empty env file
         debug!("persisting env dir");
-        create_harness_dir(&exp_source_dir.join(SRC_ENV_DIR))?;
-        if self.envs.is_empty() {
-            create_harness_file(&exp_source_dir.join(SRC_ENV_DIR).join(SRC_ENV_FILE))?;
+        let env_dir = exp_source_dir.join(SRC_ENV_DIR);
+        create_harness_dir(&env_dir)?;
+        if force && fetch_environment_files(&env_dir).is_some() {
+            debug!("keeping existing env files in {}", env_dir.display());
+        } else if self.envs.is_empty() {
+            create_file(env_dir.join(SRC_ENV_FILE))?;
         } else {
             let envs =
                 EnvironmentContainer::from_env_list(self.envs.clone().into_values().collect());
-            envs.serialize_environments(&exp_source_dir.join(SRC_ENV_DIR))?;
+            envs.serialize_environments(&env_dir)?;
         }
 
-        // create run.sh as executable
+        // create run script as executable
         debug!("persisting run script");
-        create_harness_dir(&exp_source_dir.join(SRC_TEMPLATE_DIR))?;
-        let mut run_file =
-            self.create_executable(&exp_source_dir.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE))?;
+        let template_dir = exp_source_dir.join(SRC_TEMPLATE_DIR);
+        create_harness_dir(&template_dir)?;
+        let run_file_path = template_dir.join(&self.run_file);
+        if force {
+            while let Some(existing) = find_run_file(&template_dir) {
+                debug!("replacing {}", existing.display());
+                std::fs::remove_file(&existing)?;
+            }
+        }
+        let mut run_file = self.create_executable(&run_file_path)?;
 
-        // write content to run.sh
+        // write content to run script
         let run_sh_bytes = if self.run_sh.is_empty() {
-            warn!("Tried to serialize empty run.sh; Used default template instead.");
+            warn!("Tried to serialize empty run script; Used default template instead.");
             include_bytes!("../harness/run.sh.template")
         } else {
             self.run_sh.as_bytes()
@@ -258,6 +418,27 @@ impl FileWriter for ExperimentSource {
 
         self.write_to_file(&mut run_file, run_sh_bytes)?;
 
+        let base_env_path = template_dir.join(SRC_BASE_ENV_FILE);
+        let keep_existing = force && base_env_path.exists();
+        if !self.base_env.to_env_map().is_empty() && !keep_existing {
+            debug!("persisting base env");
+            self.base_env.to_file(&base_env_path)?;
+        }
+
+        debug!("persisting README");
+        let readme = exp_source_dir.join(SRC_README);
+        if create_file(readme.clone())? {
+            std::fs::write(readme, self.readme(exp_source_dir))?;
+        }
+
+        debug!("persisting .gitignore");
+        let gitignore = exp_source_dir.join(SRC_GITIGNORE);
+        if create_file(gitignore.clone())? {
+            let content =
+                GITIGNORE_TEMPLATE.replace("{{EXPERIMENT}}", &file_name_string(exp_source_dir));
+            std::fs::write(gitignore, content)?;
+        }
+
         info!(
             "Experiment harness created under {}",
             exp_source_dir.display()
@@ -288,6 +469,139 @@ pub mod tests {
         assert!(src.persist(&tmpdir).is_err());
     }
 
+    #[test]
+    fn regenerate_keeps_user_files() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("Foo");
+
+        // same as persist for new folders
+        let mut src = ExperimentSource::new();
+        src.regenerate(&exp).unwrap();
+        assert!(exp.join(SRC_ENV_DIR).join(SRC_ENV_FILE).is_file());
+
+        std::fs::write(exp.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE), "old").unwrap();
+        std::fs::write(exp.join(SRC_TEMPLATE_DIR).join("input.dat"), "data").unwrap();
+        std::fs::write(exp.join(SRC_README), "my notes").unwrap();
+        std::fs::remove_file(exp.join(SRC_ENV_DIR).join(SRC_ENV_FILE)).unwrap();
+        std::fs::write(exp.join(SRC_ENV_DIR).join("1.env"), "A=1").unwrap();
+
+        src.set_run_script("new".to_string());
+        src.regenerate(&exp).unwrap();
+
+        let run_file = exp.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE);
+        assert_eq!(read_to_string(&run_file).unwrap(), "new");
+        assert!(run_file.executable());
+        assert_eq!(
+            read_to_string(exp.join(SRC_TEMPLATE_DIR).join("input.dat")).unwrap(),
+            "data"
+        );
+        assert_eq!(read_to_string(exp.join(SRC_README)).unwrap(), "my notes");
+        assert!(!exp.join(SRC_ENV_DIR).join(SRC_ENV_FILE).exists());
+        assert_eq!(
+            read_to_string(exp.join(SRC_ENV_DIR).join("1.env")).unwrap(),
+            "A=1"
+        );
+
+        // never touches folders that are no experiment source
+        let other = tmpdir.path().join("other");
+        std::fs::create_dir(&other).unwrap();
+        assert!(src.regenerate(&other).is_err());
+        assert!(!other.join(SRC_TEMPLATE_DIR).exists());
+    }
+
+    #[test]
+    fn parse_base_env() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("Foo");
+
+        let mut src = ExperimentSource::new();
+        src.persist(&exp).unwrap();
+        assert!(!exp.join(SRC_TEMPLATE_DIR).join(SRC_BASE_ENV_FILE).exists());
+        assert!(ExperimentSource::parse(&exp)
+            .unwrap()
+            .base_env()
+            .to_env_map()
+            .is_empty());
+
+        std::fs::write(
+            exp.join(SRC_TEMPLATE_DIR).join(SRC_BASE_ENV_FILE),
+            "SHARED=base\nSIZE=1",
+        )
+        .unwrap();
+        let src = ExperimentSource::parse(&exp).unwrap();
+        let env = src.with_base_env(&Environment::from_env_list(vec![(
+            "SIZE".to_string(),
+            "2".to_string(),
+        )]));
+        assert_eq!(env.get_env_val("SHARED"), Some(&"base".to_string()));
+        assert_eq!(env.get_env_val("SIZE"), Some(&"2".to_string()));
+    }
+
+    #[test]
+    fn persist_other_run_file() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("Foo");
+
+        let mut src = ExperimentSource::new();
+        src.persist(&exp).unwrap();
+
+        src.set_run_file_name("run.py");
+        src.set_run_script("#!/usr/bin/env python3\n".to_string());
+        src.regenerate(&exp).unwrap();
+
+        // run.sh is replaced by run.py
+        let template_dir = exp.join(SRC_TEMPLATE_DIR);
+        assert!(!template_dir.join(SRC_RUN_FILE).exists());
+        assert!(template_dir.join("run.py").executable());
+
+        let parsed = ExperimentSource::parse(&exp).unwrap();
+        assert_eq!(parsed.run_file_name(), "run.py");
+        assert_eq!(parsed.run_script(), "#!/usr/bin/env python3\n");
+    }
+
+    #[test]
+    fn filter_envs_by_glob() {
+        let mut src = ExperimentSource::new();
+        src.set_envs(HashMap::from_iter(
+            ["0.env", "1_high.env", "2_high.env", "3_low.env"]
+                .map(|name| (PathBuf::from(name), Environment::new())),
+        ))
+        .unwrap();
+
+        let mut high = src.clone();
+        high.filter_envs("*high*").unwrap();
+        let mut names: Vec<&PathBuf> = high.envs().keys().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![Path::new("1_high.env"), Path::new("2_high.env")]
+        );
+
+        let mut single = src.clone();
+        single.filter_envs("0.env").unwrap();
+        assert_eq!(single.envs().len(), 1);
+
+        let err = src.filter_envs("*medium*").unwrap_err();
+        assert!(err.to_string().contains("matches none of the 4 env files"));
+    }
+
+    #[test]
+    fn parse_missing_source() {
+        let tmpdir = TempDir::new().unwrap();
+        let missing = tmpdir.path().join("missing");
+
+        let err = ExperimentSource::parse(&missing).unwrap_err();
+        assert!(matches!(err, Error::ReaderError { .. }));
+        assert!(err.to_string().contains("cannot resolve absolute path"));
+
+        // source was removed after parsing
+        let exomat_env = ExomatEnvironment::new(&missing, 1);
+        assert!(matches!(
+            exomat_env.to_environment_full(),
+            Err(Error::ReaderError { .. })
+        ));
+    }
+
     rusty_fork_test! {
         #[test]
         fn persist_source_default() {
@@ -296,7 +610,7 @@ pub mod tests {
             // create base tempdir, to act as parent
             let tmpdir = TempDir::new().unwrap();
             let tmpdir = tmpdir.path();
-            std::env::set_current_dir(&tmpdir).unwrap();
+            std::env::set_current_dir(tmpdir).unwrap();
 
             // create experiment source dir (relative to current dir)
             let src_path = tmpdir.join("FooSource");
@@ -317,6 +631,18 @@ pub mod tests {
 
             assert_eq!(run, template);
             assert!(&run_file.executable());
+
+            // README describes the experiment
+            let readme = read_to_string(src_path.join(SRC_README)).unwrap();
+            assert!(readme.starts_with("# FooSource\n"));
+            assert!(readme.contains("exomat run FooSource"));
+            assert!(readme.contains("Current variables: none yet"));
+            assert!(!readme.contains("{{"));
+
+            // series of this experiment are ignored by git
+            let gitignore = read_to_string(src_path.join(SRC_GITIGNORE)).unwrap();
+            assert!(gitignore.contains("\nFooSource-[0-9][0-9][0-9][0-9]-"));
+            assert!(gitignore.contains("\nexomat_trial-*/"));
         }
 
         #[test]
@@ -324,7 +650,7 @@ pub mod tests {
             // create base tempdir, to act as parent
             let tmpdir = TempDir::new().unwrap();
             let tmpdir = tmpdir.path();
-            std::env::set_current_dir(&tmpdir).unwrap();
+            std::env::set_current_dir(tmpdir).unwrap();
 
             let custom_script = "!#/bin/bash\necho Something";
 
@@ -1,6 +1,6 @@
 use crate::helper::errors::{Error, Result};
 
-use std::fs::{File, OpenOptions};
+use std::fs::{File, Metadata, OpenOptions};
 use std::io::Write;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
@@ -14,7 +14,7 @@ pub trait FileWriter {
 
     fn persist_logs(&mut self) -> Result<()> {
         Err(Error::WriterError {
-            dir: format!("Log files"),
+            dir: "Log files".to_string(),
             reason: String::from("Tried to persist logs, but struct does keep log files"),
         })
     }
@@ -48,7 +48,7 @@ pub trait FileWriter {
                 reason: e.to_string(),
             })?;
 
-        std::fs::write(&file_path, content)?;
+        std::fs::write(file_path, content)?;
         Ok(())
     }
 }
@@ -60,27 +60,41 @@ pub trait FileReader {
 
     /// Builds and returns a vector of all readable files in the given directory.
     ///
-    /// ## Panics
-    /// - Panics if directory traversal went wrong
-    fn find_all_files(dir: &Path) -> Vec<PathBuf> {
-        let mut files = Vec::<PathBuf>::new();
-
-        for entry in dir.read_dir().expect("Could not read dir") {
-            if entry
-                .as_ref()
-                .expect("Entry not readable")
-                .metadata()
-                .expect("Metadata of entry not readable")
-                .is_file()
-            {
-                files.push(entry.unwrap().path());
-            }
-        }
-
-        files
+    /// ## Errors
+    /// - Returns a `ReaderError` if `dir` or any of its entries cannot be read
+    fn find_all_files(dir: &Path) -> Result<Vec<PathBuf>> {
+        Ok(read_dir_entries(dir)?
+            .into_iter()
+            .filter(|(_, metadata)| metadata.is_file())
+            .map(|(path, _)| path)
+            .collect())
     }
 }
 
+/// Returns the path and metadata of every entry in `dir`.
+///
+/// Symlinks are not followed, their metadata describes the link itself.
+///
+/// ## Errors
+/// - Returns a `ReaderError` naming the path that could not be read, if `dir`
+///   cannot be listed or the metadata of an entry is not accessible
+pub(crate) fn read_dir_entries(dir: &Path) -> Result<Vec<(PathBuf, Metadata)>> {
+    let reader_error = |path: &Path, e: std::io::Error| Error::ReaderError {
+        dir: path.display().to_string(),
+        reason: e.to_string(),
+    };
+
+    let mut entries = vec![];
+    for entry in std::fs::read_dir(dir).map_err(|e| reader_error(dir, e))? {
+        let entry = entry.map_err(|e| reader_error(dir, e))?;
+        let path = entry.path();
+        let metadata = entry.metadata().map_err(|e| reader_error(&path, e))?;
+        entries.push((path, metadata));
+    }
+
+    Ok(entries)
+}
+
 pub trait Runner {
     type Item;
 
@@ -0,0 +1,112 @@
+//! Outputs that runs print to stdout as marker lines, instead of writing out_ files
+
+use log::warn;
+
+use crate::helper::errors::{Error, Result};
+use crate::helper::shell_words::split;
+
+/// Placeholder for the `key=value` pairs in a marker pattern
+pub const MARKER_PLACEHOLDER: &str = "{}";
+
+/// Marker pattern used by `make-table --from-markers` if no other is given
+pub const DEFAULT_MARKER_PATTERN: &str = "::exomat-output {}::";
+
+/// Returns the `key=value` pairs of all lines in `stdout` that match `pattern`,
+/// in the order they were printed.
+///
+/// `pattern` describes a whole line, with [MARKER_PLACEHOLDER] standing for the
+/// pairs. With the [DEFAULT_MARKER_PATTERN], `::exomat-output latency=5 unit=ms::`
+/// results in `latency=5` and `unit=ms`. Pairs are split like shell words, so
+/// values containing whitespace can be quoted. Keys may be repeated.
+///
+/// Lines not matching `pattern` are ignored, words without `=` are skipped.
+///
+/// ## Example
+/// ```
+/// use exomat::experiment::markers::{parse_markers, DEFAULT_MARKER_PATTERN};
+///
+/// let stdout = "starting\n::exomat-output latency=5 msg='all good'::\n";
+/// let pairs = parse_markers(stdout, DEFAULT_MARKER_PATTERN).unwrap();
+/// assert_eq!(pairs[0], ("latency".to_string(), "5".to_string()));
+/// assert_eq!(pairs[1], ("msg".to_string(), "all good".to_string()));
+/// ```
+///
+/// ## Errors
+/// - Returns a `CsvError` if `pattern` does not contain [MARKER_PLACEHOLDER]
+pub fn parse_markers(stdout: &str, pattern: &str) -> Result<Vec<(String, String)>> {
+    let Some((prefix, suffix)) = pattern.split_once(MARKER_PLACEHOLDER) else {
+        return Err(Error::CsvError {
+            reason: format!("marker pattern \"{pattern}\" does not contain {MARKER_PLACEHOLDER}"),
+        });
+    };
+
+    let mut pairs = vec![];
+    for line in stdout.lines().map(str::trim) {
+        let Some(content) = line
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+        else {
+            continue;
+        };
+
+        let words = match split(content) {
+            Ok(words) => words,
+            Err(e) => {
+                warn!("Skipping invalid marker: {e}");
+                continue;
+            }
+        };
+
+        for word in words {
+            match word.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    pairs.push((key.to_string(), value.to_string()))
+                }
+                _ => warn!("Skipping \"{word}\" in marker \"{line}\", expected key=value"),
+            }
+        }
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_captured_stdout() {
+        let stdout = "\
+running benchmark
+::exomat-output latency=5::
+  ::exomat-output latency=7 size=3::
+::exomat-output note=\"two words\" broken =x::
+exomat-output latency=9
+";
+        assert_eq!(
+            parse_markers(stdout, DEFAULT_MARKER_PATTERN).unwrap(),
+            pairs(&[
+                ("latency", "5"),
+                ("latency", "7"),
+                ("size", "3"),
+                ("note", "two words")
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_custom_pattern() {
+        let stdout = "RESULT: latency=5\n::exomat-output size=3::\n";
+        assert_eq!(
+            parse_markers(stdout, "RESULT: {}").unwrap(),
+            pairs(&[("latency", "5")])
+        );
+        assert!(parse_markers(stdout, "RESULT:").is_err());
+    }
+}
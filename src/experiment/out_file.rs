@@ -1,6 +1,6 @@
+use log::warn;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
-use std::fs::read_to_string;
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 
@@ -145,17 +145,94 @@ impl OutFile {
     pub fn is_empty(&self) -> bool {
         self.content.is_empty()
     }
+
+    /// Flattens a JSON object in an `out_[var].json` file into one out_ file per key.
+    ///
+    /// The new out_ files are called `[var].[key]`, nested objects and arrays are
+    /// flattened further into `[var].[key].[nested key]` and `[var].[key].[index]`,
+    /// up to `depth` levels. Deeper values are kept as JSON, `null`s become `na_value`.
+    ///
+    /// Returns `None` if this is no `.json` out_ file or if it does not contain a
+    /// JSON object.
+    pub fn explode_json(&self, depth: usize, na_value: &str) -> Option<Vec<OutFile>> {
+        let prefix = self.name.strip_suffix(".json")?;
+        let value: serde_json::Value = serde_json::from_str(&self.content.join("\n")).ok()?;
+        if !value.is_object() {
+            return None;
+        }
+
+        let mut flat = vec![];
+        flatten_json(prefix, &value, depth, na_value, &mut flat);
+
+        Some(
+            flat.into_iter()
+                .map(|(name, value)| OutFile::from(&name, vec![value]))
+                .collect(),
+        )
+    }
+}
+
+/// Collects all values in `value` as (`[prefix].[path]`, value), see `OutFile::explode_json()`.
+fn flatten_json(
+    prefix: &str,
+    value: &serde_json::Value,
+    depth: usize,
+    na_value: &str,
+    flat: &mut Vec<(String, String)>,
+) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) if depth > 0 => {
+            for (key, nested) in map {
+                flatten_json(
+                    &format!("{prefix}.{key}"),
+                    nested,
+                    depth - 1,
+                    na_value,
+                    flat,
+                );
+            }
+        }
+        Value::Array(list) if depth > 0 => {
+            for (index, nested) in list.iter().enumerate() {
+                flatten_json(
+                    &format!("{prefix}.{index}"),
+                    nested,
+                    depth - 1,
+                    na_value,
+                    flat,
+                );
+            }
+        }
+        Value::Null => flat.push((prefix.to_string(), na_value.to_string())),
+        Value::String(s) => flat.push((prefix.to_string(), s.clone())),
+        other => flat.push((prefix.to_string(), other.to_string())),
+    }
 }
 
 // ========================== Reader ==========================
+/// Prefix of out_ files, whose content is recorded as is
+pub const OUT_PREFIX: &str = "out_";
+/// Prefix of out_ files, whose content are paths to artifacts
+pub const OUTPATH_PREFIX: &str = "outpath_";
+
 impl FileReader for OutFile {
     type Item = OutFile;
 
     /// Parses the content of outfile into an OutFile object.
     ///
+    /// For `out_[var]` files, every line is one value. For `outpath_[var]` files,
+    /// every line is the path to an artifact (relative to the `outpath_` file),
+    /// and the value is the absolute path of this artifact. The content of the
+    /// artifact itself is never read.
+    ///
+    /// Content that is not valid UTF-8 is read lossily (with a warning), so a
+    /// stray byte does not lose the other values.
+    ///
     /// ## Errors
     /// - Returns a `ReaderError` if outfile is not a file
-    /// - Returns a `ReaderError` if outfile does not start with "out_"
+    /// - Returns a `ReaderError` if outfile does not start with "out_" or "outpath_"
     /// - Returns an `Empty` Error if outfile has an invalid name
     fn parse(outfile: &Path) -> Result<Self::Item> {
         if !outfile.is_file() {
@@ -165,35 +242,78 @@ impl FileReader for OutFile {
             });
         }
 
-        let prefix = "out_";
         let file_name = file_name_string(outfile);
+        let (prefix, name) = match [OUT_PREFIX, OUTPATH_PREFIX]
+            .iter()
+            .find_map(|prefix| Some((*prefix, file_name.strip_prefix(prefix)?)))
+        {
+            Some((prefix, name)) => (prefix, name.to_string()),
+            None => {
+                return Err(Error::ReaderError {
+                    dir: outfile.display().to_string(),
+                    reason: "not an out file".to_string(),
+                })
+            }
+        };
 
-        if file_name.starts_with(prefix) {
-            // parse variable name from out file
-            let name = file_name.strip_prefix(prefix).unwrap().to_string();
-            if name.is_empty() {
-                return Err(Error::Empty(
-                    "variable name (prefix out_ alone is not permitted)".to_string(),
-                ));
+        // parse variable name from out file
+        if name.is_empty() {
+            return Err(Error::Empty(format!(
+                "variable name (prefix {prefix} alone is not permitted)"
+            )));
+        }
+
+        // read content, invalid UTF-8 only breaks the affected values
+        let bytes = std::fs::read(outfile)?;
+        let text = match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!(
+                    "{} is not valid UTF-8, invalid bytes are replaced with \u{FFFD}",
+                    outfile.display()
+                );
+                String::from_utf8_lossy(e.as_bytes()).to_string()
             }
+        };
+        let lines = text.trim().split("\n").map(|v| v.to_string()).collect();
 
-            // read content
-            let content = read_to_string(outfile)?
-                .trim()
-                .split("\n")
-                .map(|v| v.to_string())
-                .collect();
+        let content = match prefix {
+            OUTPATH_PREFIX => resolve_artifacts(outfile, lines),
+            _ => lines,
+        };
 
-            Ok(Self { name, content })
-        } else {
-            Err(Error::ReaderError {
-                dir: outfile.display().to_string(),
-                reason: "not an out file".to_string(),
-            })
-        }
+        Ok(Self { name, content })
     }
 }
 
+/// Turns every path in `lines` (relative to `outfile`) into an absolute path.
+///
+/// Empty lines are kept. Warns about artifacts that do not exist.
+fn resolve_artifacts(outfile: &Path, lines: Vec<String>) -> Vec<String> {
+    let base = outfile.parent().unwrap_or(Path::new("."));
+
+    lines
+        .into_iter()
+        .map(|line| match line.is_empty() {
+            true => line,
+            false => {
+                let artifact = base.join(line.trim());
+                match artifact.canonicalize() {
+                    Ok(path) => path.display().to_string(),
+                    Err(_) => {
+                        warn!(
+                            "artifact {} listed in {} does not exist",
+                            artifact.display(),
+                            outfile.display()
+                        );
+                        artifact.display().to_string()
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
 // ========================== Writer ==========================
 impl Display for OutFile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -282,6 +402,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_outfile_invalid_utf8() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let tmpdir = tmpdir.path().to_path_buf();
+        let outfile = create_out_file(&tmpdir, None, "out_test", "");
+        std::fs::write(&outfile, b"1\n2\xff\n3").unwrap();
+
+        let parsed = OutFile::parse(&outfile).unwrap();
+        assert_eq!(
+            parsed.values(),
+            &vec!["1".to_string(), "2\u{FFFD}".to_string(), "3".to_string()]
+        );
+    }
+
+    #[test]
+    fn parse_outfile_paths() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let tmpdir = tmpdir.path().to_path_buf();
+        let outfile = create_out_file(&tmpdir, None, "outpath_model", "model.bin\nmissing.bin");
+        let rundir = outfile.parent().unwrap();
+        std::fs::write(rundir.join("model.bin"), "huge binary content").unwrap();
+
+        let parsed = OutFile::parse(&outfile).unwrap();
+        assert_eq!(parsed.var_name(), "model");
+        assert_eq!(
+            parsed.values(),
+            &vec![
+                rundir
+                    .join("model.bin")
+                    .canonicalize()
+                    .unwrap()
+                    .display()
+                    .to_string(),
+                rundir.join("missing.bin").display().to_string(),
+            ]
+        );
+
+        let outfile = create_out_file(&tmpdir, None, "outpath_", "model.bin");
+        assert!(matches!(OutFile::parse(&outfile), Err(Error::Empty(_))));
+    }
+
+    #[test]
+    fn explode_json_objects() {
+        let outfile = |name: &str, content: &str| {
+            OutFile::from(name, content.lines().map(|l| l.to_string()).collect())
+        };
+        let names_values = |files: Vec<OutFile>| -> Vec<(String, String)> {
+            files
+                .into_iter()
+                .map(|f| (f.var_name().clone(), f.values()[0].clone()))
+                .collect()
+        };
+
+        let flat = outfile("result.json", "{\"latency\": 5, \"throughput\": 100.5}");
+        assert_eq!(
+            names_values(flat.explode_json(4, "NA").unwrap()),
+            vec![
+                ("result.latency".to_string(), "5".to_string()),
+                ("result.throughput".to_string(), "100.5".to_string()),
+            ]
+        );
+
+        let nested = outfile(
+            "result.json",
+            "{\n  \"cpu\": {\"name\": \"x86\", \"cores\": [1, 2]},\n  \"gpu\": null\n}",
+        );
+        assert_eq!(
+            names_values(nested.explode_json(4, "NA").unwrap()),
+            vec![
+                ("result.cpu.cores.0".to_string(), "1".to_string()),
+                ("result.cpu.cores.1".to_string(), "2".to_string()),
+                ("result.cpu.name".to_string(), "x86".to_string()),
+                ("result.gpu".to_string(), "NA".to_string()),
+            ]
+        );
+
+        // deeper levels are kept as JSON
+        assert_eq!(
+            names_values(nested.explode_json(1, "NA").unwrap())[0],
+            (
+                "result.cpu".to_string(),
+                "{\"cores\":[1,2],\"name\":\"x86\"}".to_string()
+            )
+        );
+
+        // only objects in .json files are exploded
+        assert!(outfile("result.json", "[1, 2]")
+            .explode_json(4, "NA")
+            .is_none());
+        assert!(outfile("result.json", "no json")
+            .explode_json(4, "NA")
+            .is_none());
+        assert!(outfile("result", "{\"a\": 1}")
+            .explode_json(4, "NA")
+            .is_none());
+    }
+
     #[test]
     fn parse_outfile_not_out() {
         let tmpdir = tempfile::TempDir::new().unwrap();
@@ -0,0 +1,108 @@
+//! Settings that change how Experiment Runs are executed
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::helper::affinity::CpuSet;
+use crate::helper::archivist::SymlinkMode;
+use crate::helper::errors::{Error, Result};
+
+/// Working directory of the run scripts
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WorkDir {
+    /// directory of the run, where its out_ files are collected
+    #[default]
+    Run,
+    /// Experiment Source directory, so relative paths of the source keep working
+    Source,
+}
+
+impl FromStr for WorkDir {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "run" => Ok(WorkDir::Run),
+            "source" => Ok(WorkDir::Source),
+            _ => Err(Error::HarnessRunError {
+                experiment: s.to_string(),
+                err: "unknown working directory, use run or source".to_string(),
+            }),
+        }
+    }
+}
+
+/// Options for executing the Experiment Runs of an Experiment Series
+///
+/// Set by `exomat run`. The default matches a plain `exomat run` without any
+/// additional flags.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    /// Run POSIX shell scripts with `-x`, so that every executed command is
+    /// printed to stderr
+    pub shell_trace: bool,
+    /// Print which output keys were produced by how many runs, after all runs
+    /// have finished
+    pub list_outputs: bool,
+    /// Write the progress of the Experiment Series to disk every n finished runs,
+    /// 0 disables checkpoints
+    pub checkpoint_interval: u64,
+    /// Log routine messages of successful runs at debug instead of info level
+    pub quiet_runs: bool,
+    /// Ring the terminal bell (and show a desktop notification, if available)
+    /// once all runs have finished
+    pub notify: bool,
+    /// How symlinks in the Experiment Source are copied into the Experiment Series
+    pub symlinks: SymlinkMode,
+    /// Give every run a private temp directory via `$TMPDIR`, `$TMP` and `$TEMP`
+    pub isolate_tmp: bool,
+    /// Keep the private temp directories of successful runs, see `isolate_tmp`
+    pub keep_tmp: bool,
+    /// Directory with previously executed runs. Runs with the same environment
+    /// as one of them are not executed, their outputs are copied instead
+    pub skip_cached: Option<PathBuf>,
+    /// Kill runs (including all processes they started) that take longer than
+    /// this, the series continues with the next run
+    pub timeout: Option<Duration>,
+    /// Continue with the remaining runs after a run failed, instead of aborting
+    /// the Experiment Series
+    pub keep_going: bool,
+    /// Abort the Experiment Series once this many runs have failed, even with
+    /// `keep_going`
+    pub max_failures: Option<usize>,
+    /// Seed for shuffling the order of the Experiment Runs, a random seed is
+    /// chosen if not set
+    pub seed: Option<u64>,
+    /// Do not shuffle the Experiment Runs, execute them sorted by env file and
+    /// repetition instead
+    pub ordered: bool,
+    /// Execute a failed run up to this many more times before counting it as
+    /// failed
+    pub retries: usize,
+    /// Only retry runs that failed with one of these exit codes, empty retries
+    /// on any non-zero exit code
+    pub retry_on_codes: Vec<i32>,
+    /// Number of warmup runs per environment, executed before all other runs
+    /// and ignored by `make-table`
+    pub warmup: u64,
+    /// Write the program, arguments, working directory and environment every
+    /// run is started with to its directory
+    pub capture_command: bool,
+    /// Variables whose values are not written by `capture_command`
+    pub redact: Vec<String>,
+    /// Compress the log files of the series with gzip once all runs are done
+    pub compress_logs: bool,
+    /// Webhook that receives a JSON summary via POST once the series has
+    /// finished or was aborted
+    pub notify_url: Option<String>,
+    /// Shell command executed once the series has finished or was aborted
+    pub notify_command: Option<String>,
+    /// Cores every run is pinned to (Linux only)
+    pub cpu_affinity: Option<CpuSet>,
+    /// Nice value every run is started with, see `priority::NICE_RANGE`
+    pub nice: Option<i32>,
+    /// Working directory of the run scripts, out_ files are always collected
+    /// from the run directory
+    pub workdir: WorkDir,
+}
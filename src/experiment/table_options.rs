@@ -0,0 +1,188 @@
+//! Settings that change how the results of an Experiment Series are collected
+
+use crate::helper::errors::{Error, Result};
+
+/// Placeholder for outputs that a run did not produce
+pub const DEFAULT_NA_VALUE: &str = "NA";
+
+/// Nesting levels of JSON outputs that are flattened by default
+pub const DEFAULT_JSON_DEPTH: usize = 4;
+
+/// Field delimiter of the table written by `make-table` by default
+pub const DEFAULT_DELIMITER: u8 = b',';
+
+/// Options for collecting the out_ files of an Experiment Series into a table
+///
+/// Set by `exomat make-table`. The default matches a plain `exomat make-table`
+/// without any additional flags.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableOptions {
+    /// Inserted for every output a run did not produce, may be empty
+    pub na_value: String,
+    /// Flatten JSON objects in `out_*.json` files into one column per key
+    pub explode_json: bool,
+    /// Nesting levels that are flattened with `explode_json`, deeper values are
+    /// kept as JSON
+    pub json_depth: usize,
+    /// Also collect outputs printed to stdout as markers matching this pattern,
+    /// see `markers::parse_markers()`
+    pub markers: Option<String>,
+    /// Remove the extension of out_ file names from their column, e.g.
+    /// `out_latency.txt` becomes `latency` (default)
+    pub strip_extension: bool,
+    /// Case of all column names derived from outputs
+    pub column_case: ColumnCase,
+    /// Field delimiter of the written table
+    pub delimiter: u8,
+    /// Add the environment variables of each run as columns, otherwise the
+    /// table only contains outputs
+    pub with_env: bool,
+    /// Write the table run by run instead of collecting all runs first
+    pub streaming: bool,
+    /// Write the table to stdout instead of a file
+    pub to_stdout: bool,
+    /// Also collect out_ files in subdirectories of a run, see
+    /// `ExperimentRun::parse_with()`
+    pub recursive: bool,
+}
+
+/// Parses the argument of `make-table --delimiter`.
+///
+/// `\t` and `tab` stand for a tab, so a TSV file can be written without typing
+/// a literal tab in the shell.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `arg` is not exactly one byte (e.g. empty, multiple
+///   characters or a non-ASCII character)
+pub fn parse_delimiter(arg: &str) -> Result<u8> {
+    match arg {
+        "\\t" | "tab" => Ok(b'\t'),
+        _ => match arg.as_bytes() {
+            [delimiter] => Ok(*delimiter),
+            _ => Err(Error::CsvError {
+                reason: format!("delimiter must be a single byte, got \"{arg}\""),
+            }),
+        },
+    }
+}
+
+/// Normalization of the case of column names
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ColumnCase {
+    /// use names as they are
+    #[default]
+    Keep,
+    /// convert names to lower case
+    Lower,
+    /// convert names to upper case
+    Upper,
+}
+
+impl std::str::FromStr for ColumnCase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keep" => Ok(ColumnCase::Keep),
+            "lower" => Ok(ColumnCase::Lower),
+            "upper" => Ok(ColumnCase::Upper),
+            _ => Err(Error::CsvError {
+                reason: format!("unknown column case \"{s}\", use keep, lower or upper"),
+            }),
+        }
+    }
+}
+
+/// Returns the output file `name` (without `out_` prefix) without its extension.
+///
+/// Only the last extension is removed (`result.tar.gz` becomes `result.tar`).
+/// Names without a stem or extension (`.hidden`, `latency.`) are kept as they are.
+pub fn strip_extension(name: &str) -> &str {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => stem,
+        _ => name,
+    }
+}
+
+impl TableOptions {
+    /// Returns the column for the output `name` (without `out_` prefix).
+    ///
+    /// If `strip_extension` is set (default) and `name` is the name of a file (not
+    /// e.g. a key of an exploded JSON file), its extension is removed (see
+    /// [strip_extension]), so `out_x`, `out_x.txt` and `out_x.csv` all result in
+    /// `x`. `ExperimentRun::parse_with()` numbers such collisions (`x`, `x_2`,
+    /// `x_3`) and warns about them. Otherwise, the column is the whole name, `x`,
+    /// `x.txt` and `x.csv`.
+    pub fn column_name(&self, name: &str, is_file_name: bool) -> String {
+        let name = match self.strip_extension && is_file_name {
+            true => strip_extension(name),
+            false => name,
+        };
+
+        match self.column_case {
+            ColumnCase::Keep => name.to_string(),
+            ColumnCase::Lower => name.to_lowercase(),
+            ColumnCase::Upper => name.to_uppercase(),
+        }
+    }
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            na_value: DEFAULT_NA_VALUE.to_string(),
+            explode_json: false,
+            json_depth: DEFAULT_JSON_DEPTH,
+            markers: None,
+            strip_extension: true,
+            column_case: ColumnCase::Keep,
+            delimiter: DEFAULT_DELIMITER,
+            with_env: true,
+            streaming: false,
+            to_stdout: false,
+            recursive: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(false, ColumnCase::Keep, "latency.txt", true, "latency.txt")]
+    #[case(true, ColumnCase::Keep, "latency.txt", true, "latency")]
+    #[case(true, ColumnCase::Keep, "result.tar.gz", true, "result.tar")]
+    #[case(true, ColumnCase::Keep, "result.latency", false, "result.latency")]
+    #[case(true, ColumnCase::Keep, ".hidden", true, ".hidden")]
+    #[case(true, ColumnCase::Lower, "Runtime.TXT", true, "runtime")]
+    #[case(false, ColumnCase::Upper, "runtime", true, "RUNTIME")]
+    fn derive_column_names(
+        #[case] strip_extension: bool,
+        #[case] column_case: ColumnCase,
+        #[case] name: &str,
+        #[case] is_file_name: bool,
+        #[case] expected: &str,
+    ) {
+        let options = TableOptions {
+            strip_extension,
+            column_case,
+            ..Default::default()
+        };
+        assert_eq!(options.column_name(name, is_file_name), expected);
+    }
+
+    #[rstest]
+    #[case(",", Some(b','))]
+    #[case(";", Some(b';'))]
+    #[case("\t", Some(b'\t'))]
+    #[case("\\t", Some(b'\t'))]
+    #[case("tab", Some(b'\t'))]
+    #[case("", None)]
+    #[case(";;", None)]
+    #[case("ä", None)]
+    fn parse_delimiters(#[case] arg: &str, #[case] expected: Option<u8>) {
+        assert_eq!(parse_delimiter(arg).ok(), expected);
+    }
+}
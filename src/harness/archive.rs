@@ -0,0 +1,77 @@
+//! harness archive subcommand
+
+use log::info;
+use std::path::Path;
+
+use crate::helper::archivist::{archive_dir, find_marker, find_marker_pwd, Compression};
+use crate::helper::errors::Result;
+use crate::helper::fs_names::*;
+
+/// entrypoint for archive binary
+///
+/// Packs the Experiment Series at (or containing) `series_dir`, or the one
+/// containing the current directory, into `[series].tar.gz` (or `.tar.zst`)
+/// next to it. All files are kept as they are, including the marker files, so
+/// the unpacked archive is a series again.
+///
+/// With `remove`, the series directory is deleted after it was archived.
+///
+/// ## Errors
+/// - Returns a `FindMarkerError` if no Experiment Series is found
+/// - Returns the errors of `archive_dir()`
+/// - Returns an `IoError` if the series could not be removed
+pub fn main(series_dir: Option<&Path>, compression: Compression, remove: bool) -> Result<()> {
+    let series_dir = match series_dir {
+        Some(dir) => find_marker(dir, MARKER_SERIES)?,
+        None => find_marker_pwd(MARKER_SERIES)?,
+    };
+
+    let archive = series_dir.with_file_name(format!(
+        "{}.{}",
+        file_name_string(&series_dir),
+        compression.extension()
+    ));
+    archive_dir(&series_dir, &archive, compression)?;
+    info!("Archived series to {}", archive.display());
+
+    if remove {
+        std::fs::remove_dir_all(&series_dir)?;
+        info!("Removed {}", series_dir.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn archive_and_remove_series() {
+        let tmpdir = TempDir::new().unwrap();
+        let series = tmpdir.path().join("Foo-2025-01-01-00-00-00");
+        std::fs::create_dir_all(series.join(SERIES_RUNS_DIR)).unwrap();
+        std::fs::write(series.join(MARKER_SERIES), "").unwrap();
+
+        // works from inside of the series as well
+        main(
+            Some(&series.join(SERIES_RUNS_DIR)),
+            Compression::Gzip,
+            false,
+        )
+        .unwrap();
+        let archive = tmpdir.path().join("Foo-2025-01-01-00-00-00.tar.gz");
+        assert!(archive.is_file());
+        assert!(series.is_dir());
+
+        // never overwrites an archive, the series is kept
+        assert!(main(Some(&series), Compression::Gzip, true).is_err());
+        assert!(series.is_dir());
+
+        std::fs::remove_file(&archive).unwrap();
+        main(Some(&series), Compression::Gzip, true).unwrap();
+        assert!(archive.is_file());
+        assert!(!series.exists());
+    }
+}
@@ -0,0 +1,155 @@
+//! harness clean subcommand
+
+use log::info;
+use std::path::{Path, PathBuf};
+
+use crate::helper::archivist::find_marker_pwd;
+use crate::helper::errors::{Error, Result};
+use crate::helper::fs_names::*;
+
+/// entrypoint for clean binary
+///
+/// Always operates on the experiment source containing pwd.
+///
+/// Lists all series directories of this experiment next to its source folder
+/// (see `find_series_dirs()`) and deletes them if `yes` is set. With `dry_run`,
+/// they are only listed.
+///
+/// ## Errors
+/// - Returns a `FindMarkerError` if pwd is not inside of an experiment source
+/// - Returns a `WriterError` if neither `dry_run` nor `yes` is set
+/// - Returns an `IoError` if a directory could not be read or deleted
+pub fn main(dry_run: bool, yes: bool) -> Result<()> {
+    let exp_source = find_marker_pwd(MARKER_SRC)?;
+    let series_dirs = find_series_dirs(&exp_source)?;
+
+    if series_dirs.is_empty() {
+        info!("No series of {} found", file_name_string(&exp_source));
+        return Ok(());
+    }
+
+    for series_dir in &series_dirs {
+        println!("{}", series_dir.display());
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    if !yes {
+        return Err(Error::WriterError {
+            dir: exp_source.display().to_string(),
+            reason: format!(
+                "refusing to delete {} series without --yes",
+                series_dirs.len()
+            ),
+        });
+    }
+
+    let mut removed = 0;
+    for series_dir in &series_dirs {
+        // never delete anything that is not a series
+        if series_dir.join(MARKER_SERIES).is_file() {
+            std::fs::remove_dir_all(series_dir)?;
+            removed += 1;
+        }
+    }
+
+    info!("Removed {removed} series");
+    Ok(())
+}
+
+/// Returns all series directories of the experiment source at `exp_source`,
+/// sorted by name.
+///
+/// These are all directories next to `exp_source` that contain [MARKER_SERIES]
+/// and whose name starts with `[experiment]-` (like the ones created by
+/// `ExperimentSeries::generate_series_filepath()`). Symlinks are never followed.
+///
+/// ## Errors
+/// - Returns an `IoError` if the parent of `exp_source` could not be read
+fn find_series_dirs(exp_source: &Path) -> Result<Vec<PathBuf>> {
+    let Some(parent) = exp_source.parent() else {
+        return Ok(vec![]);
+    };
+    let prefix = format!("{}-", file_name_string(exp_source));
+
+    let mut series_dirs = vec![];
+    for entry in std::fs::read_dir(parent)? {
+        let entry = entry?;
+        let is_series = entry.file_type()?.is_dir()
+            && entry.file_name().to_string_lossy().starts_with(&prefix)
+            && entry.path().join(MARKER_SERIES).is_file();
+        if is_series {
+            series_dirs.push(entry.path());
+        }
+    }
+
+    series_dirs.sort();
+    Ok(series_dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusty_fork::rusty_fork_test;
+    use tempfile::TempDir;
+
+    /// Creates `names` in `dir`, with [MARKER_SERIES] if `marked`.
+    fn create_dirs(dir: &Path, names: &[&str], marked: bool) {
+        for name in names {
+            std::fs::create_dir(dir.join(name)).unwrap();
+            if marked {
+                std::fs::write(dir.join(name).join(MARKER_SERIES), "").unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn find_only_marked_series() {
+        let tmpdir = TempDir::new().unwrap();
+        create_dirs(tmpdir.path(), &["Foo"], false);
+        create_dirs(
+            tmpdir.path(),
+            &[
+                "Foo-2025-01-02-00-00-00",
+                "Foo-2025-01-01-00-00-00",
+                "Bar-2025-01-01-00-00-00",
+            ],
+            true,
+        );
+        create_dirs(tmpdir.path(), &["Foo-notes"], false);
+
+        assert_eq!(
+            find_series_dirs(&tmpdir.path().join("Foo")).unwrap(),
+            vec![
+                tmpdir.path().join("Foo-2025-01-01-00-00-00"),
+                tmpdir.path().join("Foo-2025-01-02-00-00-00"),
+            ]
+        );
+    }
+
+    rusty_fork_test! {
+        #[test]
+        fn clean_series() {
+            let tmpdir = TempDir::new().unwrap();
+            let exp = tmpdir.path().join("Foo");
+            create_dirs(tmpdir.path(), &["Foo", "Foo-notes"], false);
+            std::fs::write(exp.join(MARKER_SRC), "").unwrap();
+            create_dirs(tmpdir.path(), &["Foo-2025-01-01-00-00-00"], true);
+            let series = tmpdir.path().join("Foo-2025-01-01-00-00-00");
+            std::env::set_current_dir(&exp).unwrap();
+
+            // dry run and missing confirmation keep everything
+            main(true, false).unwrap();
+            assert!(series.is_dir());
+            assert!(main(false, false).is_err());
+            assert!(series.is_dir());
+
+            main(false, true).unwrap();
+            assert!(!series.exists());
+            assert!(exp.is_dir());
+            assert!(tmpdir.path().join("Foo-notes").is_dir());
+        }
+    }
+}
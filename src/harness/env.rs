@@ -1,20 +1,27 @@
 //! harness env subcommand
 
 use itertools::Itertools;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use regex::Regex;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
+pub mod constraint;
 pub mod environment;
 pub mod environment_container;
 pub mod exomat_environment;
+pub mod grid;
+pub mod validation;
 
-use crate::helper::archivist::find_marker_pwd;
+use crate::helper::archivist::{create_harness_file, find_marker_pwd};
 use crate::helper::errors::{Error, Result};
+use crate::helper::fs_names::{case_collisions, file_name_string};
+pub use constraint::Constraint;
 pub use environment::Environment;
 pub use environment_container::EnvironmentContainer;
 pub use exomat_environment::ExomatEnvironment;
+pub use validation::{ValidationRules, Violation};
 
 /// map of all variables with all possible values
 ///
@@ -75,6 +82,11 @@ pub fn fetch_environment_files(from: &PathBuf) -> Option<Vec<PathBuf>> {
         .map(|env_file| env_file.path()) // turn to path
         .collect::<Vec<PathBuf>>();
 
+    let stems: Vec<String> = files.iter().map(|file| file_name_string(file)).collect();
+    for (a, b) in case_collisions(stems.iter().map(|s| s.as_str())) {
+        warn!("{a} and {b} only differ in case and collide on case-insensitive filesystems");
+    }
+
     match files.is_empty() {
         true => None,
         false => Some(files),
@@ -175,6 +187,9 @@ fn try_assemble_all(given: &Environment, to_add: &EnvList) -> Result<Vec<Environ
 /// Takes a list of `Vec<Vec<String>>` and turns it into a `HashMap<String, Vec<String>>`.
 /// The first element of each `Vec<String>` will be used as a key.
 ///
+/// Values are expanded with [expand_values], e.g. `@values.txt` is replaced by
+/// the lines of `values.txt`.
+///
 /// ## Example
 /// ```ignore
 /// use exomat::harness::env::to_env_list;
@@ -198,6 +213,7 @@ fn try_assemble_all(given: &Environment, to_add: &EnvList) -> Result<Vec<Environ
 ///
 /// ## Errors
 /// - Returns an `EnvError` if `old_list` is empty
+/// - Returns an `EnvError` if values could not be expanded
 fn to_env_list(old_list: &Vec<Vec<String>>) -> Result<EnvList> {
     if old_list.is_empty() {
         return Err(Error::EnvError {
@@ -210,6 +226,7 @@ fn to_env_list(old_list: &Vec<Vec<String>>) -> Result<EnvList> {
     for occurence in old_list {
         let mut val = occurence.clone();
         let key = val.remove(0);
+        let val = expand_values(&key, &val)?;
 
         transformed.insert(key, val);
     }
@@ -217,6 +234,105 @@ fn to_env_list(old_list: &Vec<Vec<String>>) -> Result<EnvList> {
     Ok(transformed)
 }
 
+/// Prefix of a value, that reads the actual values from a file
+const VALUES_FILE_PREFIX: char = '@';
+
+/// Expands the values given for `var` on the command line.
+///
+/// `@FILE` is replaced by the lines of `FILE`, each trimmed, skipping empty lines.
+/// A value starting with `@@` stands for itself with one `@` less, e.g. `@@home`
+/// is the literal value `@home`.
+///
+/// `START..END` is replaced by all integers from START to END (inclusive),
+/// `START..END:STEP` by every STEP-th of them, see [expand_range].
+///
+/// All other values are kept as they are.
+///
+/// ## Errors
+/// - Returns an `EnvError` if a file could not be read or a range is invalid
+fn expand_values(var: &str, values: &[String]) -> Result<Vec<String>> {
+    let mut expanded = vec![];
+    for value in values {
+        match value.strip_prefix(VALUES_FILE_PREFIX) {
+            Some(literal) if literal.starts_with(VALUES_FILE_PREFIX) => {
+                expanded.push(literal.to_string())
+            }
+            Some(file) => {
+                let content = std::fs::read_to_string(file).map_err(|e| Error::EnvError {
+                    reason: format!("Cannot read values of {var} from {file}: {e}"),
+                })?;
+                expanded.extend(
+                    content
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(String::from),
+                );
+            }
+            None => match expand_range(var, value)? {
+                Some(range) => expanded.extend(range),
+                None => expanded.push(value.clone()),
+            },
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Values of the form `START..END[:STEP]`, with an integer START
+static RANGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(-?[0-9]+)\.\.(.*)$").expect("Could not create Regex"));
+
+/// Expands `value` into all integers from START to END (inclusive) if it has the
+/// form `START..END` or `START..END:STEP`, e.g. `1..7:2` into `1`, `3`, `5`, `7`.
+///
+/// Returns `None` for all other values (including everything where START is not
+/// an integer), so these are kept as literal values.
+///
+/// ## Errors
+/// - Returns an `EnvError` if END or STEP is not an integer, START > END or STEP
+///   is not positive
+fn expand_range(var: &str, value: &str) -> Result<Option<Vec<String>>> {
+    let Some(captures) = RANGE.captures(value) else {
+        return Ok(None);
+    };
+
+    let range_error = |reason: &str| Error::EnvError {
+        reason: format!("Invalid range {value} for {var}: {reason}"),
+    };
+    let (end, step) = match captures[2].split_once(':') {
+        Some((end, step)) => (end, step),
+        None => (&captures[2], "1"),
+    };
+
+    let start: i64 = captures[1]
+        .parse()
+        .map_err(|_| range_error("start is not an integer"))?;
+    let end: i64 = end
+        .parse()
+        .map_err(|_| range_error("end is not an integer"))?;
+    let step: usize = step
+        .parse()
+        .ok()
+        .filter(|step| *step > 0)
+        .ok_or_else(|| range_error("step is not a positive integer"))?;
+    if start > end {
+        return Err(range_error("start is greater than end"));
+    }
+
+    Ok(Some(
+        (start..=end).step_by(step).map(|i| i.to_string()).collect(),
+    ))
+}
+
+/// Transforms `list` with [to_env_list], an empty `list` results in an empty EnvList.
+fn to_env_list_or_empty(list: &Vec<Vec<String>>) -> Result<EnvList> {
+    match list.is_empty() {
+        true => Ok(EnvList::new()),
+        false => to_env_list(list),
+    }
+}
+
 /// Fetch and load existing environment variables from .env file preserving file names
 ///
 /// ## Errors and Panics
@@ -244,90 +360,503 @@ pub fn get_existing_environments_by_fname(from: &PathBuf) -> Result<EnvironmentL
     Ok(envs)
 }
 
-/// Checks every first String of every Vector for a valid name.
+/// Checks the names of all variables in `env_list`, see [ValidationRules].
+/// Lower case letters are only accepted if `allow_lowercase` is set.
 ///
-/// "Environment variable names [...] consist solely of uppercase letters, digits,
-/// and the underscore [...] and do not begin with a digit."
+/// ## Errors
+/// - Returns a `ValidationError` listing all invalid names
+fn check_env_vars(env_list: &EnvList, allow_lowercase: bool) -> Result<()> {
+    let rules = ValidationRules {
+        check_reserved: false,
+        allow_lowercase,
+        ..Default::default()
+    };
+    check_with(env_list, &rules)
+}
+
+/// Checks that no variable in `env_list` is reserved by the exomat.
 ///
-/// ## Errors and Panics
-/// - Returns an EnvError on invalid names
-/// - Panics if any Vec<String> is empty (or the first item cannot be extracted)
-fn check_env_vars(env_list: &EnvList) -> Result<()> {
-    let re_env_name = Regex::new(r"^[A-Z_][0-9A-Z_]*$").expect("Could not create Regex");
+/// ## Errors
+/// - Returns a `ValidationError` if `env_list` contains a reserved variable
+///   (see `ExomatEnvironment::RESERVED_ENV_VARS`)
+fn check_reserved(env_list: &EnvList) -> Result<()> {
+    let rules = ValidationRules {
+        check_names: false,
+        ..Default::default()
+    };
+    check_with(env_list, &rules)
+}
 
-    let invalid: Vec<&String> = env_list
+/// Checks the names of all variables in `env_list` (ignoring their values)
+/// against `rules`, reporting every violating variable once.
+fn check_with(env_list: &EnvList, rules: &ValidationRules) -> Result<()> {
+    let no_value = String::new();
+    let violations = rules.violations(env_list.keys().sorted().map(|var| (var, &no_value)));
+    match violations.is_empty() {
+        true => Ok(()),
+        false => Err(Error::ValidationError { violations }),
+    }
+}
+
+/// Edits of the variables of an Experiment Source, as given to `exomat env`
+///
+/// Every inner vector is one variable followed by its values. The default
+/// does not edit anything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnvEdits {
+    /// New variables, every combination of their values is used
+    pub add: Vec<Vec<String>>,
+    /// Values to append to existing variables
+    pub append: Vec<Vec<String>>,
+    /// Values to remove from existing variables
+    pub remove: Vec<Vec<String>>,
+    /// New variables that vary together, their n-th values form one combination
+    pub zip: Vec<Vec<String>>,
+    /// Environments matching any of these constraints are removed after all
+    /// other edits
+    pub exclude: Vec<Constraint>,
+    /// Names of new variables may contain lower case letters
+    pub allow_lowercase: bool,
+}
+
+impl EnvEdits {
+    /// Returns all edits as operations, in the order they are applied.
+    ///
+    /// Zipped variables are added last, as appending and removing values
+    /// combines all values of all variables again.
+    ///
+    /// ## Errors
+    /// - Same Errors as [to_env_list]
+    fn operations(&self) -> Result<Vec<(EnvOperation, EnvList)>> {
+        Ok(vec![
+            (EnvOperation::Add, to_env_list_or_empty(&self.add)?),
+            (EnvOperation::Append, to_env_list_or_empty(&self.append)?),
+            (EnvOperation::Remove, to_env_list_or_empty(&self.remove)?),
+            (EnvOperation::Zip, to_env_list_or_empty(&self.zip)?),
+        ])
+    }
+}
+
+/// Operations to perform on the variables of an Experiment Source
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EnvOperation {
+    Add,
+    Append,
+    Remove,
+    Zip,
+}
+
+impl EnvOperation {
+    /// Applies this operation with all variables in `env_list` to `env`.
+    ///
+    /// ## Errors
+    /// - Returns an `EnvError` if `env_list` contains a reserved variable
+    /// - Returns an `EnvError` if the operation failed, `env` may be partially edited then
+    fn apply(&self, env: &mut EnvironmentContainer, env_list: EnvList) -> Result<()> {
+        check_reserved(&env_list)?;
+
+        if env_list.is_empty() {
+            return Ok(());
+        }
+
+        match self {
+            EnvOperation::Add => env.add_environments(env_list),
+            EnvOperation::Zip => env.add_zipped_environments(env_list),
+            EnvOperation::Append => env.append_to_environments(env_list),
+            EnvOperation::Remove => env.remove_from_environments(env_list),
+        }
+    }
+}
+
+impl std::fmt::Display for EnvOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvOperation::Add => write!(f, "--add"),
+            EnvOperation::Zip => write!(f, "--zip"),
+            EnvOperation::Append => write!(f, "--append"),
+            EnvOperation::Remove => write!(f, "--remove"),
+        }
+    }
+}
+
+/// Applies every variable of every operation on its own, except for zipped
+/// variables which are applied as one group. Operations that fail leave `env`
+/// untouched, all other operations are applied.
+///
+/// Returns a description of every failed operation.
+fn apply_valid_operations(
+    env: &mut EnvironmentContainer,
+    operations: Vec<(EnvOperation, EnvList)>,
+) -> Vec<String> {
+    let mut failed = vec![];
+
+    for (operation, env_list) in operations {
+        let groups: Vec<(String, EnvList)> = match operation {
+            EnvOperation::Zip if !env_list.is_empty() => {
+                vec![(env_list.keys().sorted().join(" "), env_list)]
+            }
+            _ => env_list
+                .into_iter()
+                .sorted_by(|a, b| a.0.cmp(&b.0))
+                .map(|(var, vals)| (var.clone(), HashMap::from([(var, vals)])))
+                .collect(),
+        };
+
+        for (vars, env_list) in groups {
+            // work on a copy, so a failed operation does not leave partial changes
+            let mut edited = env.clone();
+            match operation.apply(&mut edited, env_list) {
+                Ok(()) => *env = edited,
+                Err(e) => {
+                    warn!("Skipping {operation} {vars}: {e}");
+                    failed.push(format!("{operation} {vars}: {e}"));
+                }
+            }
+        }
+    }
+
+    failed
+}
+
+/// Returns a warning for every environment in `environments` that is identical
+/// to an earlier one, ignoring file names and comments.
+///
+/// Identical environments result in identical runs, which wastes time.
+fn duplicate_warnings(environments: &[(PathBuf, Environment)]) -> Vec<String> {
+    environments
         .iter()
-        .map(|env_vec| env_vec.0) // get env name
-        .filter(|env_name| re_env_name.captures(env_name).is_none()) // collect names that do not match regex
-        .collect();
+        .enumerate()
+        .filter_map(|(i, (file, env))| {
+            let (first, _) = environments[..i].iter().find(|(_, other)| other == env)?;
+            Some(format!(
+                "{} is identical to {}, remove duplicates with `exomat env --dedup`",
+                file_name_string(file),
+                file_name_string(first)
+            ))
+        })
+        .collect()
+}
 
-    match invalid.is_empty() {
-        false => Err(Error::EnvError {
-            reason: format!("Invalid environment variable name(s), only upper case alphanumeric and _ allowed: {invalid:?}").replace("\"", "'"),
-        }),
-        true => Ok(()),
+/// Returns a warning for every variable of `env`, that has the same value in
+/// all environments.
+///
+/// Such variables do not vary the experiment, which often means that a value
+/// was forgotten.
+fn single_value_warnings(env: &EnvironmentContainer) -> Vec<String> {
+    env.possible_envs()
+        .into_iter()
+        .filter(|(_, values)| values.len() == 1)
+        .sorted()
+        .map(|(var, values)| {
+            format!(
+                "{var} has the single value {:?} in all environments and does not vary the experiment, \
+                consider setting it as a constant instead",
+                values[0]
+            )
+        })
+        .collect()
+}
+
+/// Number of environments may grow by this factor in one edit, before a warning is shown
+const MATRIX_GROWTH_WARN_FACTOR: u64 = 4;
+
+/// Checks how the number of environments changed from `before` to `after` an edit.
+///
+/// Returns a warning, if the number grew by [MATRIX_GROWTH_WARN_FACTOR] or more.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `after` exceeds `max_matrix`
+fn check_matrix_growth(before: u64, after: u64, max_matrix: Option<u64>) -> Result<Option<String>> {
+    if let Some(max) = max_matrix.filter(|max| after > *max) {
+        return Err(Error::EnvError {
+            reason: format!(
+                "Edit would result in {after} environments (currently {before}), but at most {max} are allowed"
+            ),
+        });
+    }
+
+    match after >= before.max(1) * MATRIX_GROWTH_WARN_FACTOR {
+        true => Ok(Some(format!(
+            "Number of environments grew from {before} to {after}"
+        ))),
+        false => Ok(None),
     }
 }
 
-/// Reads existing variables from all env files in `env_path`, edits them, then
-/// serializes the new variables into `env_path`.
+/// Reads existing variables from all env files in `env_path`, applies
+/// `operations` in order, removes all environments matching any of `exclude`,
+/// then serializes the new variables into `env_path`. New variables may contain
+/// lower case letters if `allow_lowercase` is set.
 ///
-/// ## Errors and Panics
-/// - Returns an `EnvError` if any Vector contains a reserved variable (see [RESERVED_ENVS])
-/// - Panics if reading/writing of env files failed
+/// If `continue_on_error` is set, every variable is edited on its own. Edits that
+/// fail are skipped and reported after all valid edits have been written.
+///
+/// Warns if the number of environments grew a lot. Nothing is written, if it would
+/// exceed `max_matrix`.
+///
+/// ## Errors
+/// - Returns an `EnvError` if any Vector contains a reserved variable
+///   (see `ExomatEnvironment::RESERVED_ENV_VARS`)
+/// - Returns an `EnvError` listing all failed edits if `continue_on_error` is set
+/// - Returns an `EnvError` if the new number of environments exceeds `max_matrix`
+/// - Returns an `EnvError` if `exclude` removes all environments
+/// - Returns an `EnvError` if an existing env file could not be parsed or the
+///   new env files could not be written
+/// - Returns an `IoError` if an existing env file could not be removed
 fn generate_environments(
     env_path: PathBuf,
-    to_add: EnvList,
-    to_append: EnvList,
-    to_remove: EnvList,
+    operations: Vec<(EnvOperation, EnvList)>,
+    exclude: &[Constraint],
+    allow_lowercase: bool,
+    continue_on_error: bool,
+    max_matrix: Option<u64>,
 ) -> Result<()> {
     let mut env = EnvironmentContainer::from_files(&env_path)?;
+    env.set_allow_lowercase(allow_lowercase);
+    let order = env.variable_order();
+    let comments = env.comments();
+    let count_before = env.environment_count();
+
+    let failed = edit_environments(&mut env, operations, exclude, continue_on_error)?;
 
-    fn contains_reserved(env_list: &EnvList) -> bool {
-        env_list
-            .keys()
-            .any(|k| ExomatEnvironment::RESERVED_ENV_VARS.contains(&k.as_str()))
+    if let Some(warning) = check_matrix_growth(count_before, env.environment_count(), max_matrix)? {
+        warn!("{warning}");
     }
 
-    // Check if user tries to edit reserved variable
-    if contains_reserved(&to_add) || contains_reserved(&to_append) || contains_reserved(&to_remove)
-    {
-        return Err(Error::EnvError {
+    // keep the previous order and comments, new variables are appended
+    env.reorder_variables(&order);
+    env.set_comments(&comments);
+    replace_environments(&env_path, &env)?;
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(Error::EnvError {
             reason: format!(
-                "Cannot set reserved env: {:?}",
-                ExomatEnvironment::RESERVED_ENV_VARS
+                "{} operation(s) failed, all other operations were applied:\n{}",
+                failed.len(),
+                failed.join("\n")
             ),
-        });
+        }),
     }
+}
 
-    // edit existing envs
-    if !to_add.is_empty() {
-        env.add_environments(to_add)?;
-    }
+/// Applies `operations` to `env` in order, then removes all environments
+/// matching any of `exclude`. Does not touch any files.
+///
+/// If `continue_on_error` is set, failed operations are skipped (see
+/// [apply_valid_operations]) and returned.
+///
+/// ## Errors
+/// - Returns an `EnvError` if any operation contains a reserved variable
+/// - Returns an `EnvError` if an operation failed and `continue_on_error` is not set
+/// - Returns an `EnvError` if `exclude` removes all environments
+fn edit_environments(
+    env: &mut EnvironmentContainer,
+    operations: Vec<(EnvOperation, EnvList)>,
+    exclude: &[Constraint],
+    continue_on_error: bool,
+) -> Result<Vec<String>> {
+    let failed = match continue_on_error {
+        true => apply_valid_operations(env, operations),
+        false => {
+            // Check if user tries to edit reserved variable before editing anything
+            for (_, env_list) in &operations {
+                check_reserved(env_list)?;
+            }
 
-    if !to_append.is_empty() {
-        env.append_to_environments(to_append)?;
+            for (operation, env_list) in operations {
+                operation.apply(env, env_list)?;
+            }
+            vec![]
+        }
+    };
+
+    if !exclude.is_empty() {
+        let assembled = env.environment_count();
+        let pruned = env.exclude(exclude);
+        info!("Excluded {pruned} of {assembled} environment(s)");
+        if env.environment_count() == 0 {
+            return Err(Error::EnvError {
+                reason: format!("All {assembled} environment(s) are excluded"),
+            });
+        }
     }
 
-    if !to_remove.is_empty() {
-        env.remove_from_environments(to_remove)?;
+    Ok(failed)
+}
+
+/// Returns the number of environments in `env_path` and, if there are any
+/// `operations` or `exclude` constraints, the number of environments after
+/// applying them (see [generate_environments]).
+///
+/// The edits are only applied in memory, no files are written or removed.
+///
+/// ## Errors
+/// - Returns an `EnvError` if the env files could not be read
+/// - Same Errors as [edit_environments]
+fn count_environments(
+    env_path: &Path,
+    operations: Vec<(EnvOperation, EnvList)>,
+    exclude: &[Constraint],
+    allow_lowercase: bool,
+) -> Result<(u64, Option<u64>)> {
+    let mut env = EnvironmentContainer::from_files(&env_path.to_path_buf())?;
+    env.set_allow_lowercase(allow_lowercase);
+    let count = env.environment_count();
+
+    if operations.iter().all(|(_, env_list)| env_list.is_empty()) && exclude.is_empty() {
+        return Ok((count, None));
     }
 
+    edit_environments(&mut env, operations, exclude, false)?;
+    Ok((count, Some(env.environment_count())))
+}
+
+/// Removes all env files in `env_path` and serializes `env` into it instead.
+///
+/// ## Errors
+/// - Returns an `IoError` if an existing file could not be removed
+/// - Returns an `EnvError` if writing failed
+fn replace_environments(env_path: &Path, env: &EnvironmentContainer) -> Result<()> {
     // remove existing env files
-    for entry in std::fs::read_dir(&env_path)? {
+    for entry in std::fs::read_dir(env_path)? {
         let entry = entry?;
         std::fs::remove_file(entry.path())?;
     }
 
     // serialize new env files
-    env.serialize_environments(&env_path)
+    env.serialize_environments(env_path)
+}
+
+/// Returns one Environment per distinct combination of `columns` in `table`.
+///
+/// Environments are ordered by the first row they appear in.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `table` could not be read
+/// - Returns an `EnvError` if `columns` is empty or any column is missing in `table`
+fn environments_from_table(table: &Path, columns: &[String]) -> Result<Vec<Environment>> {
+    let table_columns = crate::harness::table::gate::read_columns(table)?;
+
+    if columns.is_empty() {
+        return Err(Error::EnvError {
+            reason: "no columns given to import".to_string(),
+        });
+    }
+
+    let missing: Vec<&String> = columns
+        .iter()
+        .filter(|c| !table_columns.contains_key(*c))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::EnvError {
+            reason: format!("column(s) {missing:?} not found in {}", table.display()),
+        });
+    }
+
+    let row_count = table_columns[&columns[0]].len();
+    let environments = (0..row_count)
+        .map(|row| {
+            columns
+                .iter()
+                .map(|c| (c.clone(), table_columns[c][row].clone()))
+                .collect::<Vec<_>>()
+        })
+        .unique()
+        .map(Environment::from_env_list)
+        .collect();
+
+    Ok(environments)
+}
+
+/// Removes all .env files in `env_path` and creates a single empty `0.env`.
+///
+/// This restores the state of a freshly created experiment skeleton. Other
+/// files in `env_path` are kept.
+///
+/// ## Errors
+/// - Returns an `IoError` if an env file could not be removed
+/// - Returns a `HarnessCreateError` if `0.env` could not be created
+fn clear_environments(env_path: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(env_path)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "env") {
+            debug!("removing {}", path.display());
+            std::fs::remove_file(path)?;
+        }
+    }
+
+    create_harness_file(&env_path.join(crate::SRC_ENV_FILE))?;
+    Ok(())
+}
+
+/// Sets the order of variables in all env files in `env_path` to `order`.
+///
+/// This order is used for serialization and when printing the environments.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `order` contains duplicates or unknown variables
+/// - Returns an `EnvError` if any existing variable is missing from `order`
+fn reorder_environments(env_path: &Path, order: &[String]) -> Result<()> {
+    let mut env = EnvironmentContainer::from_files(&env_path.to_path_buf())?;
+    let existing = env.variable_order();
+
+    let duplicates: Vec<&String> = order.iter().duplicates().collect();
+    let unknown: Vec<&String> = order.iter().filter(|v| !existing.contains(v)).collect();
+    let missing: Vec<&String> = existing.iter().filter(|v| !order.contains(v)).collect();
+
+    if !duplicates.is_empty() {
+        return Err(Error::EnvError {
+            reason: format!("Variable(s) listed more than once: {duplicates:?}"),
+        });
+    }
+    if !unknown.is_empty() {
+        return Err(Error::EnvError {
+            reason: format!("Cannot reorder unknown variable(s): {unknown:?}"),
+        });
+    }
+    if !missing.is_empty() {
+        return Err(Error::EnvError {
+            reason: format!("All variables must be listed, missing: {missing:?}"),
+        });
+    }
+
+    env.reorder_variables(order);
+    replace_environments(env_path, &env)
 }
 
 /// print a pretty table of all configured environments in env_path
 ///
 /// Fails if a file contains an extra key
-fn print_all_environments(env_path: PathBuf) -> Result<()> {
-    let all_envs_by_fname = get_existing_environments_by_fname(&env_path)?;
+fn print_all_environments(env_path: PathBuf, preserve_order: bool) -> Result<()> {
+    let (table, environments) = environment_table(&env_path, preserve_order)?;
+    // note: println to enforce newline after end
+    println!("{table}");
+
+    for warning in duplicate_warnings(&environments) {
+        warn!("{warning}");
+    }
+
+    let environments = environments.into_iter().map(|(_, env)| env).collect();
+    for warning in single_value_warnings(&EnvironmentContainer::from_env_list(environments)) {
+        warn!("{warning}");
+    }
+
+    Ok(())
+}
+
+/// Builds the table printed by `print_all_environments()`, with one row per env file.
+///
+/// Columns are in alphabetical order, or in the order the variables are written
+/// in the env files if `preserve_order` is set. Also returns all Environments
+/// with their files, sorted by file name.
+///
+/// Fails if a file contains an extra key
+fn environment_table(
+    env_path: &PathBuf,
+    preserve_order: bool,
+) -> Result<(String, Vec<(PathBuf, Environment)>)> {
+    let all_envs_by_fname = get_existing_environments_by_fname(env_path)?;
     let all_envs_with_fname: Vec<(PathBuf, Environment)> = all_envs_by_fname
         .into_iter()
         .sorted_by_cached_key(|(k, _)| k.clone())
@@ -337,9 +866,15 @@ fn print_all_environments(env_path: PathBuf) -> Result<()> {
     let mut table_builder = tabled::builder::Builder::default();
     info!("{} env files found", all_envs_with_fname.len());
 
+    let environments = all_envs_with_fname.clone();
+
     for (fname, env) in all_envs_with_fname {
         // variables from env file
-        let this_env_keys: Vec<String> = env.get_env_vars().iter().map(|s| s.to_string()).collect();
+        let mut this_env_keys: Vec<String> =
+            env.get_env_vars().iter().map(|s| s.to_string()).collect();
+        if !preserve_order {
+            this_env_keys.sort();
+        }
 
         match keys {
             // on first iteration add "file", then variables from env file (=header)
@@ -377,32 +912,237 @@ fn print_all_environments(env_path: PathBuf) -> Result<()> {
 
     let mut table = table_builder.build();
     table.with(tabled::settings::Style::sharp());
-    // note: println to enforce newline after end
-    println!("{table}");
+
+    Ok((table.to_string(), environments))
+}
+
+/// entry point for `exomat env --dedup`
+///
+/// Always operates in pwd
+///
+/// Removes every environment that is identical to another one (ignoring file
+/// names), then writes the remaining environments with compact numbering.
+/// Of identical environments, the one in the first file (by name) is kept.
+///
+/// ## Errors
+/// - Returns an `EnvError` if the env files could not be read or written
+pub fn dedup() -> Result<()> {
+    let exp_source = find_marker_pwd(crate::MARKER_SRC)?;
+    let env_path = exp_source.join(crate::SRC_ENV_DIR);
+
+    let removed = dedup_environments(&env_path)?;
+    info!("Removed {removed} duplicate environment(s)");
+    Ok(())
+}
+
+/// Removes all duplicate environments in `env_path` and rewrites the env files,
+/// see [dedup()]. Returns the number of removed environments.
+///
+/// Nothing is written if there are no duplicates.
+///
+/// ## Errors
+/// - Returns an `EnvError` if the env files could not be read or written
+fn dedup_environments(env_path: &PathBuf) -> Result<u64> {
+    let environments: Vec<Environment> = get_existing_environments_by_fname(env_path)?
+        .into_iter()
+        .sorted_by_cached_key(|(file, _)| file.clone())
+        .map(|(_, env)| env)
+        .collect();
+
+    let mut env = EnvironmentContainer::from_env_list(environments);
+    let order = env.variable_order();
+    let comments = env.comments();
+
+    let removed = env.dedup();
+    if removed > 0 {
+        env.reorder_variables(&order);
+        env.set_comments(&comments);
+        replace_environments(env_path, &env)?;
+    }
+
+    Ok(removed)
+}
+
+/// entry point for `exomat env --clear`
+///
+/// Always operates in pwd
+///
+/// Removes all environments, leaving only an empty `0.env`. Does nothing
+/// unless `yes` confirms that all env files should be deleted.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `yes` is not set
+/// - Same errors as `clear_environments()`
+pub fn clear(yes: bool) -> Result<()> {
+    let exp_source = find_marker_pwd(crate::MARKER_SRC)?;
+    let env_path = exp_source.join(crate::SRC_ENV_DIR);
+
+    if !yes {
+        return Err(Error::EnvError {
+            reason: format!(
+                "refusing to delete all env files in {} without --yes",
+                env_path.display()
+            ),
+        });
+    }
+
+    clear_environments(&env_path)?;
+    info!("Removed all environments, {} is empty", crate::SRC_ENV_FILE);
     Ok(())
 }
 
+/// entry point for `exomat env --from-table`
+///
+/// Always operates in pwd
+///
+/// Replaces all environments by the distinct combinations of `columns` in `table`,
+/// e.g. a table written by `exomat make-table`.
+///
+/// ## Errors
+/// - Returns an `EnvError` if a column is missing or a reserved variable is used
+/// - Same errors as `environments_from_table()`
+pub fn import_table(table: &Path, columns: &[String]) -> Result<()> {
+    let exp_source = find_marker_pwd(crate::MARKER_SRC)?;
+    let env_path = exp_source.join(crate::SRC_ENV_DIR);
+
+    let reserved: EnvList = columns.iter().map(|c| (c.clone(), vec![])).collect();
+    check_reserved(&reserved)?;
+
+    let environments = environments_from_table(table, columns)?;
+    info!(
+        "Importing {} environments from {}",
+        environments.len(),
+        table.display()
+    );
+    replace_environments(
+        &env_path,
+        &EnvironmentContainer::from_env_list(environments),
+    )
+}
+
+/// entry point for `exomat env --grid-file`
+///
+/// Always operates in pwd
+///
+/// Replaces all environments by the ones described in the grid file `grid`,
+/// see [grid] for its format.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `grid` could not be read
+/// - Same errors as `grid::environments_from_grid()`
+pub fn import_grid(grid: &Path) -> Result<()> {
+    let exp_source = find_marker_pwd(crate::MARKER_SRC)?;
+    let env_path = exp_source.join(crate::SRC_ENV_DIR);
+
+    let spec = std::fs::read_to_string(grid).map_err(|e| Error::EnvError {
+        reason: format!("Could not read {}: {e}", grid.display()),
+    })?;
+    let environments = grid::environments_from_grid(&spec)?;
+    info!(
+        "Importing {} environments from {}",
+        environments.environment_count(),
+        grid.display()
+    );
+    replace_environments(&env_path, &environments)
+}
+
+/// Returns the Environments of the Experiment Source at `exp_source`, ordered by file name.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `exp_source` is not an Experiment Source
+/// - Same errors as `get_existing_environments_by_fname()`
+fn source_environments(exp_source: &Path) -> Result<EnvironmentContainer> {
+    if !exp_source.join(crate::MARKER_SRC).is_file() {
+        return Err(Error::EnvError {
+            reason: format!("{} is not an experiment source", exp_source.display()),
+        });
+    }
+
+    let environments = get_existing_environments_by_fname(&exp_source.join(crate::SRC_ENV_DIR))?
+        .into_iter()
+        .sorted_by_cached_key(|(fname, _)| fname.clone())
+        .map(|(_, env)| env)
+        .collect();
+
+    Ok(EnvironmentContainer::from_env_list(environments))
+}
+
+/// entry point for `exomat merge-envs`
+///
+/// Combines the environments of the Experiment Sources `src1` and `src2` and
+/// replaces the environments of the Experiment Source `into` with the result.
+/// Environments of `src2` that already exist in `src1` are only written once.
+///
+/// Variables that are set in only one source are set to `fill` in all
+/// environments of the other. Without `fill`, they are an error.
+///
+/// ## Errors
+/// - Returns an `EnvError` if any directory is not an Experiment Source
+/// - Returns an `EnvError` if a variable is missing in one source and `fill` is `None`
+pub fn merge(src1: &Path, src2: &Path, into: &Path, fill: Option<String>) -> Result<()> {
+    let mut env = source_environments(src1)?;
+    let other = source_environments(src2)?;
+
+    // fail before touching anything
+    source_environments(into)?;
+
+    env.merge_environments(&other, fill.as_deref())?;
+    info!(
+        "Writing {} merged environments to {}",
+        env.environment_count(),
+        into.display()
+    );
+
+    replace_environments(&into.join(crate::SRC_ENV_DIR), &env)
+}
+
 /// main entry point for env binary
 ///
 /// Always operates in pwd
 ///
-/// Performs the given operations by default.
-/// If no operations are given, print a pretty table of all configured environments.
+/// Performs the given `edits` by default. With `continue_on_error`, failed
+/// operations are skipped and reported at the end instead of aborting.
+/// If `reorder` is given, only sets the order of all variables.
+/// If `count` is set, only prints the number of environments before and after
+/// the `edits`, without changing any files.
+/// If no operations are given, print a pretty table of all configured environments,
+/// with variables in their authored order if `preserve_order` is set.
 pub fn main(
-    to_add: Vec<Vec<String>>,
-    to_append: Vec<Vec<String>>,
-    to_remove: Vec<Vec<String>>,
+    edits: EnvEdits,
+    reorder: Vec<String>,
+    continue_on_error: bool,
+    max_matrix: Option<u64>,
+    preserve_order: bool,
+    count: bool,
 ) -> Result<()> {
     let exp_source = find_marker_pwd(crate::MARKER_SRC)?;
     let env_path = exp_source.join(crate::SRC_ENV_DIR);
 
-    let to_add = to_env_list(&to_add).unwrap_or_default();
-    let to_append = to_env_list(&to_append).unwrap_or_default();
-    let to_remove = to_env_list(&to_remove).unwrap_or_default();
+    if !reorder.is_empty() {
+        return reorder_environments(&env_path, &reorder);
+    }
+
+    let operations = edits.operations()?;
+    if count {
+        let (current, projected) =
+            count_environments(&env_path, operations, &edits.exclude, edits.allow_lowercase)?;
+        println!("{current} environment(s)");
+        if let Some(projected) = projected {
+            println!("{projected} environment(s) after the given edits");
+        }
+        return Ok(());
+    }
 
-    match to_add.is_empty() && to_append.is_empty() && to_remove.is_empty() {
-        true => print_all_environments(env_path),
-        false => generate_environments(env_path, to_add, to_append, to_remove),
+    match operations.iter().all(|(_, env_list)| env_list.is_empty()) && edits.exclude.is_empty() {
+        true => print_all_environments(env_path, preserve_order),
+        false => generate_environments(
+            env_path,
+            operations,
+            &edits.exclude,
+            edits.allow_lowercase,
+            continue_on_error,
+            max_matrix,
+        ),
     }
 }
 
@@ -420,6 +1160,19 @@ mod tests {
         skeleton_src_envs, vec_321, vec_ab,
     };
 
+    /// Returns the operations of `exomat env --add --append --remove`
+    fn operations(
+        to_add: EnvList,
+        to_append: EnvList,
+        to_remove: EnvList,
+    ) -> Vec<(EnvOperation, EnvList)> {
+        vec![
+            (EnvOperation::Add, to_add),
+            (EnvOperation::Append, to_append),
+            (EnvOperation::Remove, to_remove),
+        ]
+    }
+
     #[rstest]
     fn fetch_envs_valid(filled_src_envs: TempDir) {
         // create experiment source dir
@@ -481,51 +1234,77 @@ mod tests {
         // try using a reserved var in any position
         assert!(generate_environments(
             mock_env.clone(),
-            reserved.clone(),
-            HashMap::new(),
-            HashMap::new()
+            operations(reserved.clone(), HashMap::new(), HashMap::new()),
+            &[],
+            false,
+            false,
+            None
         )
         .is_err());
         assert!(generate_environments(
             mock_env.clone(),
-            HashMap::new(),
-            reserved.clone(),
-            HashMap::new()
+            operations(HashMap::new(), reserved.clone(), HashMap::new()),
+            &[],
+            false,
+            false,
+            None
+        )
+        .is_err());
+        assert!(generate_environments(
+            mock_env.clone(),
+            operations(HashMap::new(), HashMap::new(), reserved.clone()),
+            &[],
+            false,
+            false,
+            None
+        )
+        .is_err());
+
+        // also when continuing on errors
+        assert!(generate_environments(
+            mock_env,
+            operations(reserved, HashMap::new(), HashMap::new()),
+            &[],
+            false,
+            true,
+            None
         )
         .is_err());
-        assert!(generate_environments(mock_env, HashMap::new(), HashMap::new(), reserved).is_err());
     }
 
-    #[test]
-    fn env_validate_names() {
+    #[rstest]
+    fn env_validate_names(#[values(false, true)] allow_lowercase: bool) {
         // correct names
         let valid_list = HashMap::from([
             (String::from("VALID"), vec![String::from("val")]),
             (String::from("ALSO_VALID123_4"), vec![String::from("val")]),
             (String::from("_FOO_"), vec![String::from("val")]),
         ]);
-        assert!(check_env_vars(&valid_list).is_ok());
+        assert!(check_env_vars(&valid_list, allow_lowercase).is_ok());
 
         // starts with number
         let invalid_number = HashMap::from([(String::from("1"), vec![String::from("val")])]);
-        assert!(check_env_vars(&invalid_number).is_err());
+        assert!(check_env_vars(&invalid_number, allow_lowercase).is_err());
 
-        // includes lowercase
-        let invalid_lowercase = HashMap::from([(String::from("NoPE"), vec![String::from("val")])]);
-        assert!(check_env_vars(&invalid_lowercase).is_err());
+        // includes lowercase, only valid if allowed
+        let lowercase = HashMap::from([(String::from("NoPE"), vec![String::from("val")])]);
+        assert_eq!(
+            check_env_vars(&lowercase, allow_lowercase).is_ok(),
+            allow_lowercase
+        );
 
         // includes forbidden characters
         let invalid_characters =
             HashMap::from([(String::from("FOO,.-!§$&()?#~'<"), vec![String::from("val")])]);
-        assert!(check_env_vars(&invalid_characters).is_err());
+        assert!(check_env_vars(&invalid_characters, allow_lowercase).is_err());
 
         // more invalid characters (only whitespace)
         let invalid_whitespace = HashMap::from([(String::from(" "), vec![String::from("val")])]);
-        assert!(check_env_vars(&invalid_whitespace).is_err());
+        assert!(check_env_vars(&invalid_whitespace, allow_lowercase).is_err());
 
         // empty string
         let invalid_empty = HashMap::from([(String::new(), vec![String::from("val")])]);
-        assert!(check_env_vars(&invalid_empty).is_err());
+        assert!(check_env_vars(&invalid_empty, allow_lowercase).is_err());
     }
 
     #[rstest]
@@ -567,19 +1346,516 @@ mod tests {
         assert_eq!(*new_map.get("VAR2").unwrap(), vec_321);
     }
 
+    #[test]
+    fn env_values_from_file() {
+        let tmpdir = TempDir::new().unwrap();
+        let file = tmpdir.path().join("values.txt");
+        std::fs::write(&file, "1\n  2 \n\n4\n").unwrap();
+
+        let values: Vec<String> = ["0", &format!("@{}", file.display()), "@@home"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            expand_values("SIZE", &values).unwrap(),
+            vec!["0", "1", "2", "4", "@home"]
+        );
+
+        let missing = vec![format!("@{}", tmpdir.path().join("missing").display())];
+        let err = expand_values("SIZE", &missing).unwrap_err().to_string();
+        assert!(err.contains("Cannot read values of SIZE"));
+    }
+
+    #[rstest]
+    #[case("1..4", Some(vec!["1", "2", "3", "4"]))]
+    #[case("1..16:5", Some(vec!["1", "6", "11", "16"]))]
+    #[case("-2..0", Some(vec!["-2", "-1", "0"]))]
+    #[case("3..3", Some(vec!["3"]))]
+    #[case("16", None)]
+    #[case("a..b", None)]
+    #[case("1.5..2", None)]
+    fn env_expand_range(#[case] value: &str, #[case] expected: Option<Vec<&str>>) {
+        let expected = expected.map(|vals| vals.iter().map(|v| v.to_string()).collect());
+        assert_eq!(expand_range("THREADS", value).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("4..1", "start is greater than end")]
+    #[case("1..x", "end is not an integer")]
+    #[case("1..8:0", "step is not a positive integer")]
+    #[case("1..8:-1", "step is not a positive integer")]
+    fn env_expand_range_invalid(#[case] value: &str, #[case] reason: &str) {
+        let err = expand_range("THREADS", value).unwrap_err().to_string();
+        assert!(err.contains(reason), "{err} does not contain {reason}");
+    }
+
+    #[rstest]
+    fn env_continue_on_error(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "FOO=\"1\"").unwrap();
+
+        let list = |var: &str, vals: &[&str]| {
+            (
+                var.to_string(),
+                vals.iter().map(|v| v.to_string()).collect::<Vec<String>>(),
+            )
+        };
+
+        // FOO exists already, BAR and BAZ do not
+        let to_add = HashMap::from([list("FOO", &["2"]), list("BAR", &["a", "b"])]);
+        let to_append = HashMap::from([list("FOO", &["3"]), list("BAZ", &["x"])]);
+
+        // aborts without changing anything
+        assert!(generate_environments(
+            mock_env.clone(),
+            operations(to_add.clone(), to_append.clone(), HashMap::new()),
+            &[],
+            false,
+            false,
+            None
+        )
+        .is_err());
+        let envs = EnvironmentContainer::from_files(&mock_env).unwrap();
+        assert_eq!(envs.environment_count(), 1);
+
+        // applies valid operations, reports invalid ones
+        let err = generate_environments(
+            mock_env.clone(),
+            operations(to_add, to_append, HashMap::new()),
+            &[],
+            false,
+            true,
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("2 operation(s) failed"));
+        assert!(err.contains("--add FOO"));
+        assert!(err.contains("--append BAZ"));
+
+        let possible = EnvironmentContainer::from_files(&mock_env)
+            .unwrap()
+            .possible_envs();
+        assert_eq!(possible.len(), 2);
+        assert_eq!(possible["FOO"], vec!["1", "3"]);
+        assert_eq!(possible["BAR"], vec!["a", "b"]);
+
+        // 2 values of FOO x 2 values of BAR
+        assert_eq!(fetch_environment_files(&mock_env).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn env_merge_sources() {
+        let tmpdir = TempDir::new().unwrap();
+        let source = |name: &str, envs: &[&str]| {
+            let dir = tmpdir.path().join(name);
+            std::fs::create_dir_all(dir.join(crate::SRC_ENV_DIR)).unwrap();
+            std::fs::write(dir.join(crate::MARKER_SRC), "").unwrap();
+            for (i, content) in envs.iter().enumerate() {
+                std::fs::write(
+                    dir.join(crate::SRC_ENV_DIR).join(format!("{i}.env")),
+                    content,
+                )
+                .unwrap();
+            }
+            dir
+        };
+
+        let src1 = source("src1", &["A=\"1\"", "A=\"2\""]);
+        let src2 = source("src2", &["A=\"2\"", "A=\"3\"\nB=\"x\""]);
+        let dest = source("dest", &["C=\"old\""]);
+
+        assert!(merge(&src1, &src2, &dest, None).is_err());
+        assert!(merge(&src1, &src2, tmpdir.path(), None).is_err());
+
+        merge(&src1, &src2, &dest, Some("NA".to_string())).unwrap();
+        let merged = source_environments(&dest).unwrap();
+        let values: Vec<(String, String)> = merged
+            .to_environments()
+            .iter()
+            .map(|env| {
+                (
+                    env.get_env_val("A").unwrap().clone(),
+                    env.get_env_val("B").unwrap().clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("1".to_string(), "NA".to_string()),
+                ("2".to_string(), "NA".to_string()),
+                ("3".to_string(), "x".to_string()),
+            ]
+        );
+        assert!(merged
+            .to_environments()
+            .iter()
+            .all(|env| !env.contains_env_var("C")));
+    }
+
+    #[rstest]
+    fn env_from_table(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        let table = skeleton_src_envs.path().join("results.csv");
+        std::fs::write(
+            &table,
+            "THREADS,SIZE,time\n1,S,0.5\n2,S,0.3\n1,S,0.6\n2,L,1.1\n",
+        )
+        .unwrap();
+
+        let columns = vec!["THREADS".to_string(), "SIZE".to_string()];
+        let environments = environments_from_table(&table, &columns).unwrap();
+        let combination = |env: &Environment| {
+            (
+                env.get_env_val("THREADS").unwrap().clone(),
+                env.get_env_val("SIZE").unwrap().clone(),
+            )
+        };
+        assert_eq!(
+            environments.iter().map(combination).collect::<Vec<_>>(),
+            vec![
+                ("1".to_string(), "S".to_string()),
+                ("2".to_string(), "S".to_string()),
+                ("2".to_string(), "L".to_string()),
+            ]
+        );
+        assert!(environments
+            .iter()
+            .all(|env| env.get_env_val("time").is_none()));
+
+        replace_environments(
+            &mock_env,
+            &EnvironmentContainer::from_env_list(environments),
+        )
+        .unwrap();
+        assert_eq!(fetch_environment_files(&mock_env).unwrap().len(), 3);
+
+        let unknown = vec!["THREADS".to_string(), "NOPE".to_string()];
+        let err = environments_from_table(&table, &unknown).unwrap_err();
+        assert!(err.to_string().contains("NOPE"));
+    }
+
+    #[rstest]
+    fn env_table_order(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "ZETA=\"1\"\nALPHA=\"2\"\nMID=\"3\"").unwrap();
+
+        let header = |preserve_order: bool| {
+            let (table, _) = environment_table(&mock_env, preserve_order).unwrap();
+            let line = table.lines().nth(1).unwrap().to_string();
+            line.split('│')
+                .map(|cell| cell.trim().to_string())
+                .filter(|cell| !cell.is_empty())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(header(false), vec!["file", "ALPHA", "MID", "ZETA"]);
+        // order as written in the env files
+        assert_eq!(header(true), vec!["file", "ZETA", "ALPHA", "MID"]);
+    }
+
+    #[rstest]
+    fn env_clear(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "A=\"1\"").unwrap();
+        std::fs::write(mock_env.join("1.env"), "A=\"2\"").unwrap();
+        std::fs::write(mock_env.join("notes.txt"), "keep me").unwrap();
+
+        clear_environments(&mock_env).unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(&mock_env)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_str().unwrap().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["0.env", "notes.txt"]);
+        assert!(std::fs::read_to_string(mock_env.join("0.env"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rstest]
+    fn env_reorder(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "A=\"1\"\nB=\"2\"\nC=\"3\"").unwrap();
+
+        let order = |vars: &[&str]| vars.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        // all variables must be listed exactly once
+        assert!(reorder_environments(&mock_env, &order(&["C", "A"])).is_err());
+        assert!(reorder_environments(&mock_env, &order(&["C", "A", "B", "D"])).is_err());
+        assert!(reorder_environments(&mock_env, &order(&["C", "A", "B", "A"])).is_err());
+
+        reorder_environments(&mock_env, &order(&["C", "A", "B"])).unwrap();
+        let serialized = std::fs::read_to_string(mock_env.join("0.env")).unwrap();
+        let vars: Vec<&str> = serialized
+            .lines()
+            .map(|l| l.split_once('=').unwrap().0)
+            .collect();
+        assert_eq!(vars, vec!["C", "A", "B"]);
+
+        // order is kept when editing
+        generate_environments(
+            mock_env.clone(),
+            operations(
+                HashMap::from([("D".to_string(), vec!["4".to_string()])]),
+                HashMap::from([("A".to_string(), vec!["5".to_string()])]),
+                HashMap::new(),
+            ),
+            &[],
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+        let envs = EnvironmentContainer::from_files(&mock_env).unwrap();
+        assert_eq!(envs.environment_count(), 2);
+        assert_eq!(envs.variable_order(), order(&["C", "A", "B", "D"]));
+    }
+
+    #[rstest]
+    fn env_keep_comments(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        let content = "# problem size\nSIZE=\"1\"\n\n# the quoted # is no comment\nNOTE=\"two\n# lines\"\n# end";
+        std::fs::write(mock_env.join("0.env"), content).unwrap();
+
+        // recombining all values keeps the comments of every variable
+        generate_environments(
+            mock_env.clone(),
+            operations(
+                HashMap::from([("NEW".to_string(), vec!["x".to_string()])]),
+                HashMap::from([("SIZE".to_string(), vec!["2".to_string()])]),
+                HashMap::new(),
+            ),
+            &[],
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let expected = |size: &str| {
+            format!("# problem size\nSIZE=\"{size}\"\n\n# the quoted # is no comment\nNOTE=\"two\n# lines\"\nNEW=\"x\"\n# end")
+        };
+        let mut written: Vec<String> = fetch_environment_files(&mock_env)
+            .unwrap()
+            .iter()
+            .map(|file| std::fs::read_to_string(file).unwrap())
+            .collect();
+        written.sort();
+        assert_eq!(written, vec![expected("1"), expected("2")]);
+
+        // comments do not change the content
+        let without = Environment::from_env_list(vec![
+            ("SIZE".to_string(), "1".to_string()),
+            ("NOTE".to_string(), "two\n# lines".to_string()),
+            ("NEW".to_string(), "x".to_string()),
+        ]);
+        let envs = EnvironmentContainer::from_files(&mock_env).unwrap();
+        assert!(envs.to_environments().contains(&without));
+    }
+
+    #[rstest]
+    fn env_dedup(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "A=\"1\"\nB=\"x\"").unwrap();
+        std::fs::write(mock_env.join("1.env"), "A=\"2\"\nB=\"x\"").unwrap();
+        // same content, different order
+        std::fs::write(mock_env.join("2.env"), "B=\"x\"\nA=\"1\"").unwrap();
+        std::fs::write(mock_env.join("3.env"), "A=\"2\"\nB=\"x\"").unwrap();
+
+        let (_, environments) = environment_table(&mock_env, false).unwrap();
+        let warnings = duplicate_warnings(&environments);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("2.env is identical to 0.env"));
+        assert!(warnings[1].starts_with("3.env is identical to 1.env"));
+
+        assert_eq!(dedup_environments(&mock_env).unwrap(), 2);
+        let mut files: Vec<String> = fetch_environment_files(&mock_env)
+            .unwrap()
+            .iter()
+            .map(|file| file_name_string(file))
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["0.env", "1.env"]);
+        assert_eq!(
+            std::fs::read_to_string(mock_env.join("1.env")).unwrap(),
+            "A=\"2\"\nB=\"x\""
+        );
+
+        // nothing left to remove
+        assert_eq!(dedup_environments(&mock_env).unwrap(), 0);
+        let (_, environments) = environment_table(&mock_env, false).unwrap();
+        assert!(duplicate_warnings(&environments).is_empty());
+    }
+
+    #[rstest]
+    fn env_count_projection(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "A=\"1\"").unwrap();
+        std::fs::write(mock_env.join("1.env"), "A=\"2\"").unwrap();
+
+        let values = |vals: &[&str]| vals.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            count_environments(&mock_env, vec![], &[], false).unwrap(),
+            (2, None)
+        );
+
+        let to_add = HashMap::from([("B".to_string(), values(&["x", "y", "z"]))]);
+        let to_append = HashMap::from([("A".to_string(), values(&["3"]))]);
+        assert_eq!(
+            count_environments(
+                &mock_env,
+                operations(to_add, to_append, HashMap::new()),
+                &[],
+                false
+            )
+            .unwrap(),
+            (2, Some(9))
+        );
+
+        // nothing was written
+        assert_eq!(fetch_environment_files(&mock_env).unwrap().len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(mock_env.join("0.env")).unwrap(),
+            "A=\"1\""
+        );
+    }
+
+    #[rstest]
+    #[case(4, 8, None, false)]
+    #[case(4, 16, None, true)]
+    #[case(0, 4, None, true)]
+    #[case(0, 3, None, false)]
+    #[case(10, 2, None, false)]
+    #[case(4, 16, Some(16), true)]
+    fn env_matrix_growth(
+        #[case] before: u64,
+        #[case] after: u64,
+        #[case] max_matrix: Option<u64>,
+        #[case] warns: bool,
+    ) {
+        let warning = check_matrix_growth(before, after, max_matrix).unwrap();
+        assert_eq!(warning.is_some(), warns);
+    }
+
+    #[test]
+    fn env_single_value_warning() {
+        let env = |a: &str| {
+            Environment::from_env_list(vec![
+                ("A".to_string(), a.to_string()),
+                ("B".to_string(), "const".to_string()),
+            ])
+        };
+
+        let warnings = single_value_warnings(&EnvironmentContainer::from_env_list(vec![
+            env("1"),
+            env("2"),
+        ]));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("B has the single value \"const\""));
+
+        assert!(single_value_warnings(&EnvironmentContainer::new()).is_empty());
+    }
+
+    #[rstest]
+    fn env_max_matrix(skeleton_src_envs: TempDir) {
+        let mock_env = skeleton_src_envs.path().join(crate::SRC_ENV_DIR);
+        std::fs::write(mock_env.join("0.env"), "A=\"1\"\nB=\"2\"").unwrap();
+        std::fs::write(mock_env.join("1.env"), "A=\"3\"\nB=\"2\"").unwrap();
+
+        let values = |vals: &[&str]| vals.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let to_append = HashMap::from([("B".to_string(), values(&["4", "5", "6"]))]);
+
+        // 2 x 4 environments exceed the limit, nothing is changed
+        let err = generate_environments(
+            mock_env.clone(),
+            operations(HashMap::new(), to_append.clone(), HashMap::new()),
+            &[],
+            false,
+            false,
+            Some(7),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("8 environments"));
+        assert_eq!(fetch_environment_files(&mock_env).unwrap().len(), 2);
+
+        // within the limit
+        generate_environments(
+            mock_env.clone(),
+            operations(HashMap::new(), to_append, HashMap::new()),
+            &[],
+            false,
+            false,
+            Some(8),
+        )
+        .unwrap();
+        assert_eq!(fetch_environment_files(&mock_env).unwrap().len(), 8);
+    }
+
     rusty_fork_test! {
+        #[test]
+        fn env_case_collision_warning() {
+            crate::activate_logging(log::LevelFilter::Info);
+
+            let src_envs = skeleton_src_envs();
+            let mock_env = src_envs.path().join(crate::SRC_ENV_DIR);
+            std::fs::write(mock_env.join("Foo.env"), "VAR=1").unwrap();
+            std::fs::write(mock_env.join("bar.env"), "VAR=2").unwrap();
+            crate::helper::warnings::take();
+
+            fetch_environment_files(&mock_env).unwrap();
+            assert!(crate::helper::warnings::take().is_empty());
+
+            std::fs::write(mock_env.join("foo.env"), "VAR=3").unwrap();
+            fetch_environment_files(&mock_env).unwrap();
+            let warnings = crate::helper::warnings::take();
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].contains("Foo.env") && warnings[0].contains("foo.env"));
+            assert!(warnings[0].contains("case-insensitive"));
+        }
+
         #[test]
         fn env_e2e() {
             // ouput dir with empty envs/ dir
             let out_dir = skeleton_out();
             std::env::set_current_dir(&out_dir).unwrap();
 
-            let to_add = vec![vec!["VAR".to_string(), "VAL".to_string()]];
-            let to_append = vec![vec!["VAR".to_string(), "FOO".to_string()]];
-            let to_remove = vec![vec!["VAR".to_string(), "FOO".to_string()]];
+            let edits = EnvEdits {
+                add: vec![vec!["VAR".to_string(), "VAL".to_string()]],
+                append: vec![vec!["VAR".to_string(), "FOO".to_string()]],
+                remove: vec![vec!["VAR".to_string(), "FOO".to_string()]],
+                zip: vec![
+                    vec!["WIDTH".to_string(), "640".to_string(), "1280".to_string()],
+                    vec!["HEIGHT".to_string(), "480".to_string(), "720".to_string()],
+                ],
+                exclude: vec!["WIDTH==640 && HEIGHT==480".parse().unwrap()],
+                allow_lowercase: false,
+            };
 
             // check that no error occurs
-            main( to_add, to_append, to_remove).unwrap()
+            main(edits, vec![], false, None, false, false).unwrap();
+            let env = EnvironmentContainer::from_files(&out_dir.path().join(crate::SRC_ENV_DIR)).unwrap();
+            assert_eq!(env.environment_count(), 1);
+        }
+
+        #[test]
+        fn env_allow_lowercase() {
+            let out_dir = skeleton_out();
+            std::env::set_current_dir(&out_dir).unwrap();
+
+            let mut edits = EnvEdits {
+                add: vec![vec!["batch_size".to_string(), "8".to_string()]],
+                ..Default::default()
+            };
+            assert!(main(edits.clone(), vec![], false, None, false, false).is_err());
+
+            // names keep their case when written
+            edits.allow_lowercase = true;
+            main(edits, vec![], false, None, false, false).unwrap();
+            let env = EnvironmentContainer::from_files(&out_dir.path().join(crate::SRC_ENV_DIR)).unwrap();
+            assert_eq!(env.to_environments()[0].get_env_val("batch_size").unwrap(), "8");
         }
 
         #[test]
@@ -0,0 +1,198 @@
+//! Constraints that exclude environments, e.g. `MODE==cpu && GPU_COUNT!=0`
+//!
+//! A constraint consists of comparisons `VAR==VALUE` or `VAR!=VALUE`, combined
+//! with `&&` and `||`. `&&` binds stronger than `||`, there are no parentheses.
+//! Values containing whitespace or operator characters can be quoted with `'`
+//! or `"`.
+//!
+//! A variable that is not set in an environment is not equal to any value.
+
+use std::str::FromStr;
+
+use super::environment::Environment;
+use crate::helper::errors::{Error, Result};
+
+/// A single token of a constraint
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+/// `var==value` or (if not `equal`) `var!=value`
+#[derive(Debug, Clone, PartialEq)]
+struct Comparison {
+    var: String,
+    equal: bool,
+    value: String,
+}
+
+impl Comparison {
+    fn matches(&self, env: &Environment) -> bool {
+        let is_equal = env.get_env_val(&self.var) == Some(&self.value);
+        is_equal == self.equal
+    }
+}
+
+/// Constraint matching environments that should be excluded, see the module
+/// documentation for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    /// constraint as it was given
+    text: String,
+    /// matches if all comparisons of any of the groups match
+    any_of: Vec<Vec<Comparison>>,
+}
+
+impl Constraint {
+    /// Returns `true` if `env` matches this constraint.
+    pub fn matches(&self, env: &Environment) -> bool {
+        self.any_of
+            .iter()
+            .any(|all_of| all_of.iter().all(|cmp| cmp.matches(env)))
+    }
+}
+
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl FromStr for Constraint {
+    type Err = Error;
+
+    /// ## Errors
+    /// - Returns an `EnvError` if `s` is not a valid constraint
+    fn from_str(s: &str) -> Result<Self> {
+        let constraint_error = |reason: &str| Error::EnvError {
+            reason: format!("Invalid constraint '{s}': {reason}"),
+        };
+
+        let tokens = tokenize(s).map_err(|reason| constraint_error(&reason))?;
+        if tokens.is_empty() {
+            return Err(constraint_error("no comparison given"));
+        }
+
+        let mut any_of = vec![];
+        for group in tokens.split(|t| *t == Token::Or) {
+            let mut all_of = vec![];
+            for comparison in group.split(|t| *t == Token::And) {
+                all_of.push(match comparison {
+                    [Token::Word(var), op @ (Token::Equal | Token::NotEqual), Token::Word(value)] => {
+                        Comparison {
+                            var: var.clone(),
+                            equal: *op == Token::Equal,
+                            value: value.clone(),
+                        }
+                    }
+                    _ => return Err(constraint_error("expected VAR==VALUE or VAR!=VALUE")),
+                });
+            }
+            any_of.push(all_of);
+        }
+
+        Ok(Constraint {
+            text: s.to_string(),
+            any_of,
+        })
+    }
+}
+
+/// Splits `s` into tokens, returns the reason if that is not possible.
+fn tokenize(s: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let operator = match (c, chars.peek()) {
+            ('=', Some('=')) => Some(Token::Equal),
+            ('!', Some('=')) => Some(Token::NotEqual),
+            ('&', Some('&')) => Some(Token::And),
+            ('|', Some('|')) => Some(Token::Or),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            chars.next();
+            tokens.push(operator);
+            continue;
+        }
+
+        match c {
+            c if c.is_whitespace() => (),
+            '\'' | '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => word.push(next),
+                        None => return Err(format!("missing closing {c}")),
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            '=' | '!' | '&' | '|' => return Err(format!("unexpected '{c}'")),
+            c => {
+                let mut word = c.to_string();
+                while let Some(next) = chars
+                    .next_if(|next| !next.is_whitespace() && !matches!(next, '=' | '!' | '&' | '|'))
+                {
+                    word.push(next);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn env(list: &[(&str, &str)]) -> Environment {
+        Environment::from_env_list(
+            list.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[rstest]
+    #[case("MODE==cpu && GPU_COUNT!=0", &[("MODE", "cpu"), ("GPU_COUNT", "4")], true)]
+    #[case("MODE==cpu && GPU_COUNT!=0", &[("MODE", "cpu"), ("GPU_COUNT", "0")], false)]
+    #[case("MODE==cpu && GPU_COUNT!=0", &[("MODE", "gpu"), ("GPU_COUNT", "4")], false)]
+    #[case("A==1 || B==2 && C==3", &[("A", "1")], true)]
+    #[case("A==1 || B==2 && C==3", &[("B", "2")], false)]
+    #[case("A==1 || B==2 && C==3", &[("B", "2"), ("C", "3")], true)]
+    #[case("NAME=='two words'", &[("NAME", "two words")], true)]
+    #[case("NAME==\"a==b\"", &[("NAME", "a==b")], true)]
+    #[case("UNSET==x", &[], false)]
+    #[case("UNSET!=x", &[], true)]
+    fn constraint_matches(
+        #[case] constraint: &str,
+        #[case] vars: &[(&str, &str)],
+        #[case] expected: bool,
+    ) {
+        let constraint: Constraint = constraint.parse().unwrap();
+        assert_eq!(constraint.matches(&env(vars)), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("MODE")]
+    #[case("MODE=cpu")]
+    #[case("MODE==cpu &&")]
+    #[case("|| MODE==cpu")]
+    #[case("MODE==cpu GPU==0")]
+    #[case("NAME=='open")]
+    fn constraint_invalid(#[case] constraint: &str) {
+        let err = constraint.parse::<Constraint>().unwrap_err().to_string();
+        assert!(err.contains("Invalid constraint"), "{err}");
+    }
+}
@@ -1,14 +1,100 @@
 //! Implementation of the Environment struct
 
+use indexmap::IndexMap;
+use itertools::Itertools;
 use std::collections::HashMap;
 use std::path::Path;
 
+use super::{EnvList, EnvironmentContainer, ValidationRules};
 use crate::helper::errors::{Error, Result};
 
 /// Represents one environment file
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Variables keep the order in which they were inserted (or read from a file).
+/// This order is used when serializing.
+///
+/// Comments and blank lines of an env file are kept as well and written back
+/// by `to_file()`. They are not part of the content, two Environments with the
+/// same variables and values are equal regardless of their comments.
+#[derive(Debug, Clone)]
 pub struct Environment {
-    envs: HashMap<String, String>,
+    envs: IndexMap<String, String>,
+    comments: Comments,
+}
+
+impl PartialEq for Environment {
+    fn eq(&self, other: &Self) -> bool {
+        self.envs == other.envs
+    }
+}
+
+/// Comment and blank lines of an env file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Comments {
+    /// lines directly before the definition of each variable
+    before: HashMap<String, Vec<String>>,
+    /// lines after the last variable
+    trailing: Vec<String>,
+}
+
+impl Comments {
+    /// Returns the comment and blank lines of the env file `content`, each
+    /// associated with the variable defined directly after them.
+    ///
+    /// Lines inside of quoted multi-line values are not treated as comments.
+    pub fn parse(content: &str) -> Self {
+        let mut comments = Comments::default();
+        let mut pending: Vec<String> = vec![];
+        let mut open_quote: Option<char> = None;
+
+        for line in content.lines() {
+            if let Some(quote) = open_quote {
+                if line.contains(quote) {
+                    open_quote = None;
+                }
+                continue;
+            }
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                pending.push(line.trim_end().to_string());
+                continue;
+            }
+
+            let assignment = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+            if let Some((var, value)) = assignment.split_once('=') {
+                let value = value.trim_start();
+                open_quote = value
+                    .chars()
+                    .next()
+                    .filter(|q| (*q == '"' || *q == '\'') && !value[1..].contains(*q));
+                comments
+                    .before
+                    .insert(var.trim().to_string(), std::mem::take(&mut pending));
+            }
+        }
+
+        comments.trailing = pending;
+        comments.before.retain(|_, lines| !lines.is_empty());
+        comments
+    }
+
+    /// Returns `true` if there are no comment or blank lines.
+    pub fn is_empty(&self) -> bool {
+        self.before.is_empty() && self.trailing.is_empty()
+    }
+
+    /// Adds all lines of `other` that are not set here yet.
+    pub fn extend(&mut self, other: &Comments) {
+        for (var, lines) in &other.before {
+            self.before
+                .entry(var.clone())
+                .or_insert_with(|| lines.clone());
+        }
+        if self.trailing.is_empty() {
+            self.trailing = other.trailing.clone();
+        }
+    }
 }
 
 impl Default for Environment {
@@ -21,12 +107,14 @@ impl Environment {
     /// Constructs an empty Environment
     pub fn new() -> Self {
         Environment {
-            envs: HashMap::new(),
+            envs: IndexMap::new(),
+            comments: Comments::default(),
         }
     }
 
     /// Constructs a new Environment with all variables and values from a file.
-    /// Does not include process environment variables.
+    /// Does not include process environment variables. Comment and blank lines
+    /// are kept, see [Comments].
     ///
     /// ## Parameters
     /// `file` needs to be a valid env file, see Errors and Panics
@@ -54,14 +142,28 @@ impl Environment {
 
             env.envs.insert(var, val);
         }
+        env.comments = Comments::parse(&std::fs::read_to_string(file)?);
 
         Ok(env)
     }
 
+    /// Collects all variables defined in any .env file in `dir`.
+    ///
+    /// Since different files may assign different values to the same variable,
+    /// this does not return an Environment, but an `EnvList` mapping each variable
+    /// to all of its distinct values (sorted). Variables only defined in some of
+    /// the files are included as well.
+    ///
+    /// Delegates to `EnvironmentContainer::from_files()`, has same errors & panics.
+    pub fn from_dir(dir: &Path) -> Result<EnvList> {
+        Ok(EnvironmentContainer::from_files(&dir.to_path_buf())?.possible_envs())
+    }
+
     /// Returns a new Environment with `list` as it's variables.
     pub fn from_env_list(list: Vec<(String, String)>) -> Self {
         Environment {
             envs: list.into_iter().collect(),
+            comments: Comments::default(),
         }
     }
 
@@ -101,7 +203,8 @@ impl Environment {
         Ok(Environment::from_env_list(dotenvy::vars().collect()))
     }
 
-    /// Serialize current envs to `file_path`.
+    /// Serialize current envs to `file_path`, every variable preceded by its
+    /// comments.
     ///
     /// Will create a new file if `file_path` does not exist and will overwrite it if it does.
     /// This will fail if any parent directories of `file_path` do not exist.
@@ -109,13 +212,38 @@ impl Environment {
     /// ## Errors
     /// - Returns an `EnvError` if writing failed
     pub fn to_file(&self, file_path: &Path) -> Result<()> {
-        serde_envfile::to_file(file_path, &self.envs).map_err(|e| Error::EnvError {
+        let to_env_error = |e: serde_envfile::Error| Error::EnvError {
+            reason: e.to_string(),
+        };
+
+        let mut lines: Vec<String> = vec![];
+        for (var, val) in &self.envs {
+            if let Some(comments) = self.comments.before.get(var) {
+                lines.extend(comments.iter().cloned());
+            }
+            // serde_envfile would write all names in upper case, only quote the value
+            let val = serde_envfile::to_string(val).map_err(to_env_error)?;
+            lines.push(format!("{var}={val}"));
+        }
+        lines.extend(self.comments.trailing.iter().cloned());
+
+        std::fs::write(file_path, lines.join("\n")).map_err(|e| Error::EnvError {
             reason: e.to_string(),
         })
     }
 
+    /// Returns the comment and blank lines of this Environment.
+    pub fn comments(&self) -> &Comments {
+        &self.comments
+    }
+
+    /// Replaces the comment and blank lines of this Environment.
+    pub fn set_comments(&mut self, comments: Comments) {
+        self.comments = comments;
+    }
+
     /// Returns a map of all envs saved in this Environment.
-    pub fn to_env_map(&self) -> &HashMap<String, String> {
+    pub fn to_env_map(&self) -> &IndexMap<String, String> {
         &self.envs
     }
 
@@ -142,7 +270,14 @@ impl Environment {
         self.envs.insert(var, val);
     }
 
+    /// Removes `var` from this Environment, the other variables keep their order.
+    pub fn remove_env(&mut self, var: &str) {
+        self.envs.shift_remove(var);
+    }
+
     /// Append all variables from `other_env` onto this Environment.
+    ///
+    /// Variables that are already defined keep their position.
     pub fn extend_envs(&mut self, other_env: &Environment) {
         self.envs.extend(other_env.to_env_map().to_owned());
     }
@@ -158,4 +293,47 @@ impl Environment {
     pub fn get_env_vars(&self) -> Vec<&String> {
         self.envs.keys().collect()
     }
+
+    /// Returns a hash of all variables and their values, as 16 hex digits.
+    ///
+    /// Independent of the order of the variables, so two Environments that are
+    /// equal have the same hash. Uses 64 bit FNV-1a, which is stable across
+    /// platforms and exomat versions.
+    pub fn content_hash(&self) -> String {
+        const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let mut hash = FNV_OFFSET;
+        for (var, val) in self.envs.iter().sorted() {
+            // separators keep `A=BC` and `AB=C` apart
+            for byte in [var.as_bytes(), b"=", val.as_bytes(), b"\n"].concat() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        format!("{hash:016x}")
+    }
+
+    /// Checks the names and values of all variables against `rules`.
+    ///
+    /// ## Errors
+    /// - Returns a `ValidationError` listing every violation of `rules`
+    pub fn validate(&self, rules: &ValidationRules) -> Result<()> {
+        let violations = rules.violations(&self.envs);
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(Error::ValidationError { violations }),
+        }
+    }
+
+    /// Moves all variables listed in `order` to the front, in the given order.
+    ///
+    /// Variables not listed in `order` keep their relative order after them.
+    /// Entries of `order` that are not defined in this Environment are ignored.
+    pub fn reorder(&mut self, order: &[String]) {
+        self.envs.sort_by_cached_key(|var, _| {
+            order.iter().position(|o| o == var).unwrap_or(order.len())
+        });
+    }
 }
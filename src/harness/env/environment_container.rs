@@ -4,7 +4,8 @@ use log::{debug, warn};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use super::environment::Environment;
+use super::constraint::Constraint;
+use super::environment::{Comments, Environment};
 use super::{
     assert_exists, check_env_vars, get_existing_environments_by_fname, try_assemble_all, EnvList,
 };
@@ -14,6 +15,8 @@ use crate::helper::errors::{Error, Result};
 #[derive(Debug, Clone)]
 pub struct EnvironmentContainer {
     environment_list: Vec<Environment>,
+    /// new variables may contain lower case letters
+    allow_lowercase: bool,
 }
 
 impl Default for EnvironmentContainer {
@@ -27,6 +30,7 @@ impl EnvironmentContainer {
     pub fn new() -> Self {
         EnvironmentContainer {
             environment_list: vec![],
+            allow_lowercase: false,
         }
     }
 
@@ -38,17 +42,25 @@ impl EnvironmentContainer {
         let environments_by_fname = get_existing_environments_by_fname(from)?;
 
         // create an Environment from each file
-        Ok(EnvironmentContainer {
-            environment_list: environments_by_fname
+        Ok(EnvironmentContainer::from_env_list(
+            environments_by_fname
                 .into_values()
                 .collect::<Vec<Environment>>(),
-        })
+        ))
+    }
+
+    /// Allow lower case letters in the names of variables added from now on.
+    ///
+    /// By default, names have to consist of upper case letters, digits and `_`.
+    pub fn set_allow_lowercase(&mut self, allow_lowercase: bool) {
+        self.allow_lowercase = allow_lowercase;
     }
 
     /// Returns a new EnvironmentContainer from the content of `list`.
     pub fn from_env_list(list: Vec<Environment>) -> Self {
         EnvironmentContainer {
             environment_list: list,
+            allow_lowercase: false,
         }
     }
 
@@ -93,7 +105,7 @@ impl EnvironmentContainer {
             .iter()
             .for_each(|v| assert!(!v.1.is_empty(), "Found variable without value. Aborting."));
 
-        check_env_vars(&to_add)?;
+        check_env_vars(&to_add, self.allow_lowercase)?;
 
         // combine them, produces list of all env files with content
         if self.environment_list.is_empty() {
@@ -119,6 +131,65 @@ impl EnvironmentContainer {
         Ok(())
     }
 
+    /// Takes existing envs and combines them with the variables from `to_zip`,
+    /// which vary together: the n-th values of all variables form one new env.
+    ///
+    /// `{A: [1, 2], B: [x, y]}` results in `A=1 B=x` and `A=2 B=y`, which are then
+    /// combined with every existing env.
+    ///
+    /// # Errors and Panics
+    /// - Panics if `to_zip` is empty
+    /// - Same Errors and Panics as `check_env_names()`
+    /// - Returns an `EnvError` if the variables do not have the same number of values
+    /// - Returns an `EnvError` if a variable from `to_zip` is already set
+    pub fn add_zipped_environments(&mut self, to_zip: EnvList) -> Result<()> {
+        assert!(!to_zip.is_empty(), "No envs to zip. Aborting.");
+        check_env_vars(&to_zip, self.allow_lowercase)?;
+
+        let mut vars: Vec<(&String, &Vec<String>)> = to_zip.iter().collect();
+        vars.sort();
+
+        let length = vars[0].1.len();
+        if vars.iter().any(|(_, values)| values.len() != length) {
+            let lengths: Vec<String> = vars
+                .iter()
+                .map(|(var, values)| format!("{var}: {}", values.len()))
+                .collect();
+            return Err(Error::EnvError {
+                reason: format!(
+                    "Zipped variables need the same number of values ({})",
+                    lengths.join(", ")
+                ),
+            });
+        }
+
+        let empty = [Environment::new()];
+        let existing: &[Environment] = match self.environment_list.is_empty() {
+            true => &empty,
+            false => &self.environment_list,
+        };
+
+        let mut new_list = vec![];
+        for file in existing {
+            if let Some((var, _)) = vars.iter().find(|(var, _)| file.contains_env_var(var)) {
+                return Err(Error::EnvError {
+                    reason: format!("Env var '{var}' is already set"),
+                });
+            }
+
+            for i in 0..length {
+                let mut env = file.clone();
+                for (var, values) in &vars {
+                    env.add_env(var.to_string(), values[i].clone());
+                }
+                new_list.push(env);
+            }
+        }
+
+        self.environment_list = new_list;
+        Ok(())
+    }
+
     /// Appends all values from `to_append` to the existing variables.
     ///
     /// There are two cases where nothing will be changed:
@@ -200,7 +271,9 @@ impl EnvironmentContainer {
         self.try_remove_env_vals(&to_remove)
     }
 
-    fn possible_envs(&self) -> EnvList {
+    /// Returns every variable defined in any Environment of this container, together
+    /// with all of its distinct values (sorted).
+    pub fn possible_envs(&self) -> EnvList {
         let mut possible_envs: EnvList = HashMap::new();
 
         // create a list of all possible values from all given files
@@ -308,6 +381,123 @@ impl EnvironmentContainer {
             .for_each(|combo| combo.extend_envs(new_environment));
     }
 
+    /// Returns all variables of this container, in the order they appear in its
+    /// Environments.
+    pub fn variable_order(&self) -> Vec<String> {
+        let mut order: Vec<String> = vec![];
+        for environment in &self.environment_list {
+            for var in environment.get_env_vars() {
+                if !order.contains(var) {
+                    order.push(var.to_string());
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Reorders the variables of every Environment according to `order`.
+    ///
+    /// See `Environment::reorder()`.
+    pub fn reorder_variables(&mut self, order: &[String]) {
+        self.environment_list
+            .iter_mut()
+            .for_each(|environment| environment.reorder(order));
+    }
+
+    /// Adds all Environments of `other` that are not part of this container yet.
+    ///
+    /// Variables that are only set in some Environments are set to `fill` in all
+    /// others. Environments that are equal after filling are only kept once.
+    ///
+    /// ## Errors
+    /// - Returns an `EnvError` if a variable is not set in all Environments and
+    ///   `fill` is `None`, this container is not changed then
+    pub fn merge_environments(
+        &mut self,
+        other: &EnvironmentContainer,
+        fill: Option<&str>,
+    ) -> Result<()> {
+        let mut merged = self.environment_list.clone();
+        merged.extend(other.environment_list.iter().cloned());
+
+        let order = EnvironmentContainer::from_env_list(merged.clone()).variable_order();
+        for environment in merged.iter_mut() {
+            for var in &order {
+                if environment.contains_env_var(var) {
+                    continue;
+                }
+
+                match fill {
+                    Some(value) => environment.add_env(var.to_string(), value.to_string()),
+                    None => {
+                        return Err(Error::EnvError {
+                            reason: format!(
+                                "Variable {var} is not set in all environments, \
+                                 specify a value to fill it with"
+                            ),
+                        })
+                    }
+                }
+            }
+            environment.reorder(&order);
+        }
+
+        self.environment_list = vec![];
+        for environment in merged {
+            if !self.environment_list.contains(&environment) {
+                self.environment_list.push(environment);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes every Environment that is equal to an earlier one, keeping the
+    /// order of all others.
+    ///
+    /// Returns the number of removed Environments.
+    pub fn dedup(&mut self) -> u64 {
+        let before = self.environment_count();
+        let mut unique: Vec<Environment> = vec![];
+        for env in self.environment_list.drain(..) {
+            if !unique.contains(&env) {
+                unique.push(env);
+            }
+        }
+        self.environment_list = unique;
+        before - self.environment_count()
+    }
+
+    /// Removes every Environment that matches any of `constraints`.
+    ///
+    /// Returns the number of removed Environments.
+    pub fn exclude(&mut self, constraints: &[Constraint]) -> u64 {
+        let before = self.environment_count();
+        self.environment_list
+            .retain(|env| !constraints.iter().any(|constraint| constraint.matches(env)));
+        before - self.environment_count()
+    }
+
+    /// Returns the comment and blank lines of all Environments, for every
+    /// variable the ones of the first Environment that has any.
+    pub fn comments(&self) -> Comments {
+        let mut comments = Comments::default();
+        for env in &self.environment_list {
+            comments.extend(env.comments());
+        }
+        comments
+    }
+
+    /// Sets `comments` as the comment and blank lines of every Environment.
+    ///
+    /// Used to keep comments when editing recombines the Environments.
+    pub fn set_comments(&mut self, comments: &Comments) {
+        for env in self.environment_list.iter_mut() {
+            env.set_comments(comments.clone());
+        }
+    }
+
     /// Number of Environments defined in this EnvironmentContainer.
     pub fn environment_count(&self) -> u64 {
         self.environment_list.len() as u64
@@ -354,6 +544,76 @@ mod tests {
         assert!(env.add_environments(envlist_one_var_two_val).is_err());
     }
 
+    #[rstest]
+    fn env_add_zipped(envlist_one_var_two_val: EnvList) {
+        let to_zip = EnvList::from([
+            (
+                "WIDTH".to_string(),
+                vec!["640".to_string(), "1280".to_string()],
+            ),
+            (
+                "HEIGHT".to_string(),
+                vec!["480".to_string(), "720".to_string()],
+            ),
+        ]);
+
+        let mut env = EnvironmentContainer::new();
+        env.add_zipped_environments(to_zip.clone()).unwrap();
+        assert_eq!(env.environment_count(), 2);
+        for (environment, (width, height)) in env
+            .environment_list
+            .iter()
+            .zip([("640", "480"), ("1280", "720")])
+        {
+            assert_eq!(environment.get_env_val("WIDTH").unwrap(), width);
+            assert_eq!(environment.get_env_val("HEIGHT").unwrap(), height);
+        }
+
+        // combined with every existing env
+        let mut env = EnvironmentContainer::new();
+        env.add_environments(envlist_one_var_two_val).unwrap();
+        env.add_zipped_environments(to_zip.clone()).unwrap();
+        assert_eq!(env.environment_count(), 4);
+
+        // zipping the same variables again fails
+        assert!(env.add_zipped_environments(to_zip).is_err());
+        assert_eq!(env.environment_count(), 4);
+    }
+
+    #[rstest]
+    fn env_exclude(envlist_two_var_two_val: EnvList) {
+        let mut env = EnvironmentContainer::new();
+        env.add_environments(envlist_two_var_two_val).unwrap();
+
+        let constraints = ["VAR1==VAL1 && VAR2!=VAL2".parse().unwrap()];
+        assert_eq!(env.exclude(&constraints), 1);
+        assert_eq!(env.environment_count(), 3);
+        assert!(!env.environment_list.iter().any(|environment| {
+            environment.get_env_val("VAR1").unwrap() == "VAL1"
+                && environment.get_env_val("VAR2").unwrap() == "VAL22"
+        }));
+
+        assert_eq!(env.exclude(&constraints), 0);
+    }
+
+    #[test]
+    fn env_add_zipped_mismatch() {
+        let to_zip = EnvList::from([
+            (
+                "WIDTH".to_string(),
+                vec!["640".to_string(), "1280".to_string()],
+            ),
+            ("HEIGHT".to_string(), vec!["480".to_string()]),
+        ]);
+
+        let err = EnvironmentContainer::new()
+            .add_zipped_environments(to_zip)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("same number of values"));
+        assert!(err.contains("HEIGHT: 1, WIDTH: 2"));
+    }
+
     #[rstest]
     fn env_add_multiple(envlist_one_var_two_val: EnvList, envlist_two_var_two_val: EnvList) {
         // add to empty EnvironmentContainer
@@ -465,6 +725,26 @@ mod tests {
         assert!(env1.get_env_val("VAR2").is_none());
     }
 
+    #[test]
+    fn env_from_dir_union() {
+        let tmpdir = TempDir::new().unwrap();
+        let tmpdir = tmpdir.path().to_path_buf();
+        std::fs::write(tmpdir.join("0.env"), "SHARED=1\nONLY_A=a").unwrap();
+        std::fs::write(tmpdir.join("1.env"), "SHARED=2\nONLY_B=b").unwrap();
+        std::fs::write(tmpdir.join("2.env"), "SHARED=1\nONLY_B=c").unwrap();
+
+        let union = Environment::from_dir(&tmpdir).unwrap();
+
+        assert_eq!(
+            union,
+            HashMap::from([
+                ("SHARED".to_string(), vec!["1".to_string(), "2".to_string()]),
+                ("ONLY_A".to_string(), vec!["a".to_string()]),
+                ("ONLY_B".to_string(), vec!["b".to_string(), "c".to_string()]),
+            ])
+        );
+    }
+
     #[rstest]
     fn env_serialize(container_single: EnvironmentContainer) {
         // helper
@@ -489,4 +769,61 @@ mod tests {
         assert!(read_env(&tmpdir.join("00.env")).is_empty());
         assert!(read_env(&tmpdir.join("10.env")).is_empty());
     }
+
+    #[test]
+    fn env_merge_overlapping() {
+        let env = |a: &str, b: &str| {
+            Environment::from_env_list(vec![
+                ("A".to_string(), a.to_string()),
+                ("B".to_string(), b.to_string()),
+            ])
+        };
+
+        let mut left = EnvironmentContainer::from_env_list(vec![env("1", "x"), env("2", "x")]);
+        let right = EnvironmentContainer::from_env_list(vec![env("2", "x"), env("3", "y")]);
+
+        left.merge_environments(&right, None).unwrap();
+        assert_eq!(
+            left.to_environments(),
+            &vec![env("1", "x"), env("2", "x"), env("3", "y")]
+        );
+    }
+
+    #[test]
+    fn env_merge_disjoint() {
+        let mut left =
+            EnvironmentContainer::from_env_list(vec![Environment::from_env_list(vec![(
+                "A".to_string(),
+                "1".to_string(),
+            )])]);
+        let right = EnvironmentContainer::from_env_list(vec![
+            Environment::from_env_list(vec![("B".to_string(), "2".to_string())]),
+            Environment::from_env_list(vec![("B".to_string(), "3".to_string())]),
+        ]);
+
+        // missing variables are an error without fill value
+        let untouched = left.clone();
+        assert!(left.merge_environments(&right, None).is_err());
+        assert_eq!(left.to_environments(), untouched.to_environments());
+
+        left.merge_environments(&right, Some("NA")).unwrap();
+        let merged: Vec<Vec<(&str, &str)>> = left
+            .to_environments()
+            .iter()
+            .map(|env| {
+                env.to_env_map()
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                vec![("A", "1"), ("B", "NA")],
+                vec![("A", "NA"), ("B", "2")],
+                vec![("A", "NA"), ("B", "3")],
+            ]
+        );
+    }
 }
@@ -3,12 +3,17 @@
 use std::path::{Path, PathBuf};
 
 use crate::harness::env::environment::Environment;
+use crate::helper::archivist::canonicalize;
 use crate::helper::errors::Result;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExomatEnvironment {
     pub exp_src_dir: PathBuf,
     pub repetition: u64,
+    /// 0-based position of the run in the execution order of its series
+    pub run_index: usize,
+    /// number of runs in the series
+    pub run_total: usize,
 }
 
 impl ExomatEnvironment {
@@ -16,6 +21,8 @@ impl ExomatEnvironment {
         ExomatEnvironment {
             exp_src_dir: exp_src_dir.to_owned(),
             repetition,
+            run_index: 0,
+            run_total: 1,
         }
     }
 
@@ -23,36 +30,67 @@ impl ExomatEnvironment {
     ///
     /// - "EXP_SRC_DIR" (absolute path)
     /// - "REPETITION"
-    pub fn to_environment_full(&self) -> Environment {
+    /// - "RUN_INDEX"
+    /// - "RUN_TOTAL"
+    ///
+    /// ## Errors
+    /// - Returns a `ReaderError` if the absolute path of `exp_src_dir` cannot be build
+    pub fn to_environment_full(&self) -> Result<Environment> {
         let mut env = self.to_environment_serializable();
 
         env.extend_envs(&Environment::from_env_list(Vec::from([(
             String::from("EXP_SRC_DIR"),
-            self.exp_src_dir
-                .canonicalize()
-                .unwrap_or_else(|_| {
-                    panic!("EXP_SRC_DIR is no dir ({})", self.exp_src_dir.display())
-                })
-                .display()
-                .to_string(),
+            canonicalize(&self.exp_src_dir)?.display().to_string(),
         )])));
 
-        env
+        Ok(env)
     }
 
     /// Returns an Environment with all environment variables that are allowed to
     /// be serialized. This means it contains:
     ///
     /// - "REPETITION"
+    /// - "RUN_INDEX"
+    /// - "RUN_TOTAL"
     pub fn to_environment_serializable(&self) -> Environment {
-        Environment::from_env_list(Vec::from([(
-            String::from("REPETITION"),
-            self.repetition.to_string(),
-        )]))
+        Environment::from_env_list(Vec::from([
+            (String::from("REPETITION"), self.repetition.to_string()),
+            (String::from("RUN_INDEX"), self.run_index.to_string()),
+            (String::from("RUN_TOTAL"), self.run_total.to_string()),
+        ]))
     }
 
+    /// Environment variable whose value is passed to the run script as positional
+    /// arguments, split like a POSIX shell would (see [split](crate::helper::shell_words::split)).
+    ///
+    /// Unlike [Self::RESERVED_ENV_VARS] it is set by the user.
+    pub const ARGS_ENV_VAR: &str = "EXOMAT_ARGS";
+
+    /// Environment variable pointing to the shell helper functions of each run
+    /// (see [RUN_LIB_FILE](crate::helper::fs_names::RUN_LIB_FILE)).
+    ///
+    /// Set by the Experiment Run itself, because it depends on the run directory.
+    pub const LIB_ENV_VAR: &str = "EXOMAT_LIB";
+
+    /// Environment variable containing the absolute path of the run directory,
+    /// which is also the working directory of the run script.
+    ///
+    /// Set by the Experiment Run itself, like [Self::LIB_ENV_VAR].
+    pub const RUN_DIR_ENV_VAR: &str = "EXP_RUN_DIR";
+
+    /// Environment variables that only depend on the (possibly shuffled) order of
+    /// the runs. Runs that only differ in them are executed with the same variables.
+    pub const ORDER_ENV_VARS: [&str; 2] = ["RUN_INDEX", "RUN_TOTAL"];
+
     /// List of all environment variable names that exomat reserves for internal use
-    pub const RESERVED_ENV_VARS: [&str; 2] = ["EXP_SRC_DIR", "REPETITION"];
+    pub const RESERVED_ENV_VARS: [&str; 6] = [
+        "EXP_SRC_DIR",
+        Self::RUN_DIR_ENV_VAR,
+        "REPETITION",
+        Self::ORDER_ENV_VARS[0],
+        Self::ORDER_ENV_VARS[1],
+        Self::LIB_ENV_VAR,
+    ];
 }
 
 /// Adds serializable exomat envs to an env file
@@ -0,0 +1,282 @@
+//! Environments from a grid file, mixing cartesian and coupled variables
+//!
+//! A grid file is a TOML file with two optional tables:
+//! ```toml
+//! # every combination of these values is used
+//! [product]
+//! THREADS = [1, 2, 4]
+//! SIZE = ["small", "large"]
+//!
+//! # variables of one group vary together, the n-th values form one tuple
+//! [coupled.gpu]
+//! BACKEND = ["cuda", "rocm"]
+//! DRIVER = ["nvidia", "amdgpu"]
+//! ```
+//! Every coupled group acts like one more product dimension, whose values are
+//! the tuples of the group. The example results in 3 * 2 * 2 = 12 environments,
+//! `BACKEND=cuda` is always combined with `DRIVER=nvidia`.
+//!
+//! Values may be strings, integers, floats or booleans. In `[product]`, a single
+//! value instead of a list sets the variable to that value in every environment.
+//! Coupled groups do not repeat single values: a single value counts as a list
+//! with one value, so it is rejected unless all variables of the group have
+//! exactly one value.
+
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use super::environment::Environment;
+use super::environment_container::EnvironmentContainer;
+use super::{check_with, EnvList, ValidationRules};
+use crate::helper::errors::{Error, Result};
+
+/// Table of the grid file with the cartesian variables
+const PRODUCT_TABLE: &str = "product";
+
+/// Table of the grid file with the groups of coupled variables
+const COUPLED_TABLE: &str = "coupled";
+
+/// Variables that vary together, with their values in the same order
+type CoupledGroup = Vec<(String, Vec<String>)>;
+
+/// Returns all Environments described by the grid file content `spec`, see the
+/// module documentation for the format.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `spec` is not valid TOML or does not follow the format
+/// - Returns an `EnvError` if a coupled group is empty or its lists differ in length
+/// - Returns an `EnvError` if a variable is set more than once
+/// - Returns a `ValidationError` if a variable name is invalid or reserved
+pub fn environments_from_grid(spec: &str) -> Result<EnvironmentContainer> {
+    let document: DocumentMut = spec.parse().map_err(|e| grid_error(format!("{e}")))?;
+
+    let mut product = EnvList::new();
+    let mut coupled: Vec<(String, CoupledGroup)> = vec![];
+    for (key, item) in document.iter() {
+        match key {
+            PRODUCT_TABLE => {
+                for (var, values) in variables(key, item)? {
+                    product.insert(var, values);
+                }
+            }
+            COUPLED_TABLE => {
+                let groups = item
+                    .as_table_like()
+                    .ok_or_else(|| grid_error(format!("[{COUPLED_TABLE}] must be a table")))?;
+                for (name, group) in groups.iter() {
+                    let name = format!("{COUPLED_TABLE}.{name}");
+                    let group = variables(&name, group)?;
+                    check_group_lengths(&name, &group)?;
+                    coupled.push((name, group));
+                }
+            }
+            _ => {
+                return Err(grid_error(format!(
+                    "unknown table [{key}], expected [{PRODUCT_TABLE}] or [{COUPLED_TABLE}.NAME]"
+                )))
+            }
+        }
+    }
+
+    // every variable is set exactly once and has a valid name
+    let mut all_vars = EnvList::new();
+    let coupled_vars = coupled
+        .iter()
+        .flat_map(|(_, group)| group.iter().map(|(var, values)| (var, values)));
+    for (var, values) in product.iter().chain(coupled_vars) {
+        if all_vars.insert(var.clone(), values.clone()).is_some() {
+            return Err(grid_error(format!("variable {var} is set more than once")));
+        }
+    }
+    if all_vars.is_empty() {
+        return Err(grid_error("no variables defined".to_string()));
+    }
+    check_with(&all_vars, &ValidationRules::default())?;
+
+    let mut environments = match product.is_empty() {
+        true => vec![Environment::new()],
+        false => {
+            let mut container = EnvironmentContainer::new();
+            container.add_environments(product)?;
+            container.to_environments().clone()
+        }
+    };
+
+    for (_, group) in coupled {
+        let tuples = group[0].1.len();
+        environments = environments
+            .iter()
+            .flat_map(|env| {
+                (0..tuples).map(|i| {
+                    let mut env = env.clone();
+                    for (var, values) in &group {
+                        env.add_env(var.clone(), values[i].clone());
+                    }
+                    env
+                })
+            })
+            .collect();
+    }
+
+    Ok(EnvironmentContainer::from_env_list(environments))
+}
+
+/// Returns the variables of table `name` with their values.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `item` is not a table, a variable has no values or
+///   a value is not a string, integer, float or boolean
+fn variables(name: &str, item: &Item) -> Result<Vec<(String, Vec<String>)>> {
+    let table: &dyn TableLike = item
+        .as_table_like()
+        .ok_or_else(|| grid_error(format!("[{name}] must be a table")))?;
+
+    table
+        .iter()
+        .map(|(var, item)| {
+            let values = match item.as_value() {
+                Some(Value::Array(array)) => array.iter().map(|v| scalar(var, v)).collect(),
+                Some(value) => scalar(var, value).map(|v| vec![v]),
+                None => Err(grid_error(format!("{var} in [{name}] has no values"))),
+            }?;
+
+            match values.is_empty() {
+                true => Err(grid_error(format!("{var} in [{name}] has no values"))),
+                false => Ok((var.to_string(), values)),
+            }
+        })
+        .collect()
+}
+
+/// Returns `value` as it is written to an env file.
+///
+/// ## Errors
+/// - Returns an `EnvError` if `value` is not a string, integer, float or boolean
+fn scalar(var: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.value().clone()),
+        Value::Integer(i) => Ok(i.value().to_string()),
+        Value::Float(f) => Ok(f.value().to_string()),
+        Value::Boolean(b) => Ok(b.value().to_string()),
+        _ => Err(grid_error(format!(
+            "value of {var} must be a string, integer, float or boolean"
+        ))),
+    }
+}
+
+/// Checks that all variables of a coupled group have the same number of values.
+///
+/// ## Errors
+/// - Returns an `EnvError` if the group has no variables
+/// - Returns an `EnvError` otherwise, listing the length of every variable
+fn check_group_lengths(name: &str, group: &CoupledGroup) -> Result<()> {
+    if group.is_empty() {
+        return Err(grid_error(format!("[{name}] has no variables")));
+    }
+
+    let first = group.first().map(|(_, values)| values.len());
+    if group.iter().all(|(_, values)| Some(values.len()) == first) {
+        return Ok(());
+    }
+
+    let lengths: Vec<String> = group
+        .iter()
+        .map(|(var, values)| format!("{var}: {}", values.len()))
+        .collect();
+    Err(grid_error(format!(
+        "all variables in [{name}] need the same number of values ({})",
+        lengths.join(", ")
+    )))
+}
+
+fn grid_error(reason: String) -> Error {
+    Error::EnvError {
+        reason: format!("Invalid grid file: {reason}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(container: &EnvironmentContainer, var: &str) -> Vec<String> {
+        container
+            .to_environments()
+            .iter()
+            .map(|env| env.get_env_val(var).unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn grid_product_with_coupled_pair() {
+        let spec = r#"
+[product]
+THREADS = [1, 2]
+
+[coupled.gpu]
+BACKEND = ["cuda", "rocm"]
+DRIVER = ["nvidia", "amdgpu"]
+"#;
+        let container = environments_from_grid(spec).unwrap();
+        assert_eq!(container.environment_count(), 4);
+
+        let backends = values(&container, "BACKEND");
+        let drivers = values(&container, "DRIVER");
+        for (backend, driver) in backends.iter().zip(&drivers) {
+            let expected = match backend.as_str() {
+                "cuda" => "nvidia",
+                _ => "amdgpu",
+            };
+            assert_eq!(driver, expected);
+        }
+
+        let mut combinations: Vec<(String, String)> = values(&container, "THREADS")
+            .into_iter()
+            .zip(backends)
+            .collect();
+        combinations.sort();
+        assert_eq!(
+            combinations,
+            vec![
+                ("1".to_string(), "cuda".to_string()),
+                ("1".to_string(), "rocm".to_string()),
+                ("2".to_string(), "cuda".to_string()),
+                ("2".to_string(), "rocm".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_coupled_only() {
+        let spec = "[coupled.size]\nX = [1, 2, 3]\nY = [1.5, 2.5, 3.5]\nVERBOSE = true\n";
+        let err = environments_from_grid(spec).unwrap_err();
+        assert!(err.to_string().contains("same number of values"));
+
+        let spec = "[coupled.size]\nX = [1, 2, 3]\nY = [1.5, 2.5, 3.5]\n";
+        let container = environments_from_grid(spec).unwrap();
+        assert_eq!(values(&container, "X"), vec!["1", "2", "3"]);
+        assert_eq!(values(&container, "Y"), vec!["1.5", "2.5", "3.5"]);
+    }
+
+    #[test]
+    fn grid_invalid_specs() {
+        for (spec, reason) in [
+            ("[product\nA = [1]", "Invalid grid file"),
+            ("[sweep]\nA = [1]", "unknown table [sweep]"),
+            ("", "no variables"),
+            ("[product]\nA = []", "A in [product] has no values"),
+            (
+                "[product]\nA = [1, 2]\n[coupled.gpu]",
+                "[coupled.gpu] has no variables",
+            ),
+            ("[product]\nA = [[1]]", "must be a string"),
+            (
+                "[product]\nA = [1]\n[coupled.x]\nA = [2]",
+                "A is set more than once",
+            ),
+            ("[product]\nREPETITION = [1]", "reserved"),
+        ] {
+            let err = environments_from_grid(spec).unwrap_err().to_string();
+            assert!(err.contains(reason), "{err} does not contain {reason}");
+        }
+    }
+}
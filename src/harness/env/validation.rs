@@ -0,0 +1,258 @@
+//! Validation of environment variable names and values
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use super::ExomatEnvironment;
+
+/// "Environment variable names [...] consist solely of uppercase letters, digits,
+/// and the underscore [...] and do not begin with a digit."
+static VALID_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z_][0-9A-Z_]*$").expect("Could not create Regex"));
+
+/// [VALID_NAME], but lower case letters are allowed as well
+static VALID_NAME_LOWERCASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][0-9A-Za-z_]*$").expect("Could not create Regex"));
+
+/// A single reason why an Environment is invalid
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// Name does not consist of upper case alphanumeric characters and `_` only
+    /// (or any alphanumeric characters, if `allow_lowercase` was set)
+    InvalidName { var: String, allow_lowercase: bool },
+    /// Variable is set by the exomat itself (see `ExomatEnvironment::RESERVED_ENV_VARS`)
+    Reserved { var: String },
+    /// Value is not one of the allowed values of this variable
+    NotAllowed {
+        var: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+    /// Value does not match the pattern of this variable
+    NoMatch {
+        var: String,
+        value: String,
+        pattern: String,
+    },
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::InvalidName {
+                var,
+                allow_lowercase,
+            } => {
+                let allowed = match allow_lowercase {
+                    true => "alphanumeric and _",
+                    false => "upper case alphanumeric and _",
+                };
+                write!(f, "invalid name '{var}', only {allowed} allowed")
+            }
+            Violation::Reserved { var } => write!(f, "'{var}' is reserved by the exomat"),
+            Violation::NotAllowed {
+                var,
+                value,
+                allowed,
+            } => write!(f, "{var}='{value}' is not one of {allowed:?}"),
+            Violation::NoMatch {
+                var,
+                value,
+                pattern,
+            } => write!(f, "{var}='{value}' does not match '{pattern}'"),
+        }
+    }
+}
+
+/// Constraints checked by `Environment::validate()`
+///
+/// The default checks variable names and reserved variables, without any
+/// constraints on the values.
+#[derive(Debug, Clone)]
+pub struct ValidationRules {
+    /// Names have to consist of upper case alphanumeric characters and `_`
+    pub check_names: bool,
+    /// Names may contain lower case letters as well, if `check_names` is set
+    pub allow_lowercase: bool,
+    /// Variables reserved by the exomat must not be set
+    pub check_reserved: bool,
+    /// Values each variable may have, variables not listed may have any value
+    pub allowed_values: HashMap<String, Vec<String>>,
+    /// Patterns a variable has to match, always matched against the whole value
+    /// (as if surrounded by `^` and `$`)
+    pub patterns: HashMap<String, Regex>,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        ValidationRules {
+            check_names: true,
+            allow_lowercase: false,
+            check_reserved: true,
+            allowed_values: HashMap::new(),
+            patterns: HashMap::new(),
+        }
+    }
+}
+
+impl ValidationRules {
+    /// Returns all violations of these rules by `vars`, in the order of `vars`.
+    ///
+    /// Every variable may cause multiple violations, e.g. if it has an invalid
+    /// name and a value that is not allowed.
+    pub fn violations<'a>(
+        &self,
+        vars: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Vec<Violation> {
+        let mut violations = vec![];
+        let anchored: HashMap<&String, Regex> = self
+            .patterns
+            .iter()
+            .map(|(var, pattern)| {
+                let whole = Regex::new(&format!("^(?:{})$", pattern.as_str()));
+                (
+                    var,
+                    whole.expect("anchoring a valid pattern keeps it valid"),
+                )
+            })
+            .collect();
+
+        let valid_name = match self.allow_lowercase {
+            true => &VALID_NAME_LOWERCASE,
+            false => &VALID_NAME,
+        };
+
+        for (var, value) in vars {
+            if self.check_names && !valid_name.is_match(var) {
+                violations.push(Violation::InvalidName {
+                    var: var.clone(),
+                    allow_lowercase: self.allow_lowercase,
+                });
+            }
+
+            if self.check_reserved && ExomatEnvironment::RESERVED_ENV_VARS.contains(&var.as_str()) {
+                violations.push(Violation::Reserved { var: var.clone() });
+            }
+
+            if let Some(allowed) = self.allowed_values.get(var) {
+                if !allowed.contains(value) {
+                    violations.push(Violation::NotAllowed {
+                        var: var.clone(),
+                        value: value.clone(),
+                        allowed: allowed.clone(),
+                    });
+                }
+            }
+
+            if let Some(pattern) = anchored.get(var) {
+                if !pattern.is_match(value) {
+                    violations.push(Violation::NoMatch {
+                        var: var.clone(),
+                        value: value.clone(),
+                        pattern: self.patterns[var].to_string(),
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::env::Environment;
+    use crate::helper::errors::Error;
+
+    fn env(list: &[(&str, &str)]) -> Environment {
+        Environment::from_env_list(
+            list.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn validate_default_rules() {
+        assert!(env(&[("FOO", "anything"), ("_BAR2", "")])
+            .validate(&ValidationRules::default())
+            .is_ok());
+
+        let rules = ValidationRules {
+            check_reserved: false,
+            ..Default::default()
+        };
+        assert!(env(&[("REPETITION", "1")]).validate(&rules).is_ok());
+
+        assert!(env(&[("batch_size", "1")])
+            .validate(&ValidationRules::default())
+            .is_err());
+        let rules = ValidationRules {
+            allow_lowercase: true,
+            ..Default::default()
+        };
+        assert!(env(&[("batch_size", "1")]).validate(&rules).is_ok());
+        let message = env(&[("1batch", "1")])
+            .validate(&rules)
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("invalid name '1batch', only alphanumeric and _ allowed"));
+    }
+
+    #[test]
+    fn validate_reports_all_violations() {
+        let rules = ValidationRules {
+            allowed_values: HashMap::from([(
+                "MODE".to_string(),
+                vec!["fast".to_string(), "slow".to_string()],
+            )]),
+            patterns: HashMap::from([("THREADS".to_string(), Regex::new("1|[0-9]+").unwrap())]),
+            ..Default::default()
+        };
+
+        let environment = env(&[
+            ("lower", "1"),
+            ("MODE", "medium"),
+            ("THREADS", "4x"),
+            ("REPETITION", "0"),
+            ("OK", "fine"),
+        ]);
+
+        let Err(Error::ValidationError { violations }) = environment.validate(&rules) else {
+            panic!("environment must be invalid");
+        };
+        assert_eq!(
+            violations,
+            vec![
+                Violation::InvalidName {
+                    var: "lower".to_string(),
+                    allow_lowercase: false,
+                },
+                Violation::NotAllowed {
+                    var: "MODE".to_string(),
+                    value: "medium".to_string(),
+                    allowed: vec!["fast".to_string(), "slow".to_string()],
+                },
+                Violation::NoMatch {
+                    var: "THREADS".to_string(),
+                    value: "4x".to_string(),
+                    pattern: "1|[0-9]+".to_string(),
+                },
+                Violation::Reserved {
+                    var: "REPETITION".to_string()
+                },
+            ]
+        );
+
+        let message = environment.validate(&rules).unwrap_err().to_string();
+        assert!(
+            message.contains("invalid name 'lower', only upper case alphanumeric and _ allowed")
+        );
+        assert!(message.contains("THREADS='4x' does not match '1|[0-9]+'"));
+
+        // alternatives are matched against the whole value as well
+        assert!(env(&[("THREADS", "16")]).validate(&rules).is_ok());
+    }
+}
@@ -0,0 +1,18 @@
+# Helper functions for run.sh, provided by exomat.
+# Load them in your run.sh with:
+#   . "$EXOMAT_LIB"
+
+# exomat_out KEY VALUE...
+#
+# Appends VALUE as a new line to out_KEY in the run directory ($EXP_RUN_DIR),
+# so it is collected by `exomat make-table` as the output KEY.
+exomat_out() {
+    if [ "$#" -lt 2 ]; then
+        echo "usage: exomat_out KEY VALUE..." >&2
+        return 1
+    fi
+
+    exomat_out_key="$1"
+    shift
+    printf '%s\n' "$*" >> "${EXP_RUN_DIR:-.}/out_$exomat_out_key"
+}
@@ -1,12 +1,22 @@
 //! harness run subcommand
 
 use chrono::Local;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use log::{info, trace};
-use std::path::PathBuf;
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use itertools::Itertools;
+use log::{debug, error, info, trace, warn};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
-use crate::experiment::{ExperimentSeries, ExperimentSource, FileReader, FileWriter, Runner};
-use crate::helper::errors::Result;
+use crate::experiment::{
+    cache::RunCache, checkpoint::Checkpoint, experiment_run::RunStatus, ExperimentSeries,
+    ExperimentSource, FileReader, FileWriter, RunOptions, Runner,
+};
+use crate::harness::table::diff;
+use crate::helper::errors::{Error, Result};
+use crate::helper::fs_names::{SERIES_RUNS_DIR, SERIES_WARNINGS_LOG};
+use crate::helper::shebang::posix_shell;
+use crate::helper::warnings;
 
 /// Creates an experiment series/run directory for the given `experiment`.
 /// Then executes the `run.sh` file for this experiment and dumps the output in
@@ -16,6 +26,17 @@ use crate::helper::errors::Result;
 /// or whatever is defined in `output`.
 ///
 /// Requires a directory called `[experiment]` to be present in the current location.
+/// Every run is executed according to `options`. If `options.list_outputs` is set,
+/// a summary of all produced output keys is printed afterwards. If `options.notify`
+/// is set, the user is notified once all runs have finished. If `options.keep_going`
+/// is set, failed runs do not abort the series, but an error is returned once all
+/// runs have been executed.
+///
+/// All warnings logged while the runs were executed are written to
+/// `[series]/runs/warnings.log`, their number is reported at the end.
+///
+/// If the experiment yields no runs, an error is returned before the series
+/// directory is created (see `check_runs_exist()`).
 ///
 /// Wrapper around `build_series_directory` and `execute_exp_repetitions`.
 pub fn experiment(
@@ -23,39 +44,376 @@ pub fn experiment(
     output: Option<PathBuf>,
     log_progress_handler: MultiProgress,
     is_trial: bool,
+    options: &RunOptions,
 ) -> Result<()> {
     let output = match output {
         Some(x) => x,
-        None => ExperimentSeries::generate_series_filepath(&experiment.location())?,
+        None => ExperimentSeries::generate_series_filepath(experiment.location())?,
+    };
+
+    // only report warnings of this series
+    warnings::take();
+
+    let mut series = ExperimentSeries::from_source(experiment)?;
+    series.set_run_options(options.clone());
+    series.generate_runs()?;
+    check_runs_exist(&series)?;
+    series.persist(&output)?;
+
+    execute_series(
+        experiment,
+        &mut series,
+        &output,
+        log_progress_handler,
+        is_trial,
+        options,
+    )
+}
+
+/// Continues the interrupted Experiment Series in `series_dir`, that was created
+/// from `experiment` earlier.
+///
+/// Only runs that are missing or did not finish (see `ExperimentRun::is_complete()`)
+/// are executed, everything else works like [experiment()].
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` if `series_dir` is not an experiment series
+/// - Same errors as `experiment()`
+pub fn resume(
+    experiment: &ExperimentSource,
+    series_dir: &Path,
+    log_progress_handler: MultiProgress,
+    options: &RunOptions,
+) -> Result<()> {
+    // only report warnings of this series
+    warnings::take();
+
+    let mut series = ExperimentSeries::from_source(experiment)?;
+    series.set_run_options(options.clone());
+    series.generate_runs()?;
+    check_runs_exist(&series)?;
+
+    let complete = series.resume(series_dir)?;
+    if series.runs().is_empty() {
+        info!(
+            "All {complete} runs in {} are complete, nothing to resume",
+            series_dir.display()
+        );
+        return Ok(());
+    }
+    info!(
+        "Resuming {}: {complete} runs are complete, executing the remaining {}",
+        series_dir.display(),
+        series.runs().len()
+    );
+
+    execute_series(
+        experiment,
+        &mut series,
+        series_dir,
+        log_progress_handler,
+        false,
+        options,
+    )
+}
+
+/// Executes all runs of `series`, which has been written to `output`, then reports
+/// the results.
+///
+/// Writes all warnings to `runs/warnings.log`, handles `options.compress_logs`,
+/// `options.notify`, `options.notify_url`, `options.notify_command` and
+/// `options.list_outputs`. The warnings, `options.notify_url` and
+/// `options.notify_command` are also handled if a failed run aborted the series.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` if a run failed
+/// - Returns a `HarnessRunError` if the series was aborted after `options.max_failures`
+fn execute_series(
+    experiment: &ExperimentSource,
+    series: &mut ExperimentSeries,
+    output: &Path,
+    log_progress_handler: MultiProgress,
+    is_trial: bool,
+    options: &RunOptions,
+) -> Result<()> {
+    let mut summary = SeriesSummary::default();
+    let result = execute_exp_repetitions(
+        series,
+        log_progress_handler,
+        is_trial,
+        options,
+        &mut summary,
+    );
+    // a failed run aborted the series, it is reported like a completed one
+    if result.is_err() {
+        summary.aborted = true;
+    }
+
+    let collected = warnings::take();
+    let warnings_log = output.join(SERIES_RUNS_DIR).join(SERIES_WARNINGS_LOG);
+    std::fs::write(
+        &warnings_log,
+        collected.iter().map(|w| format!("{w}\n")).join(""),
+    )?;
+    info!(
+        "{}",
+        warnings::summary(&collected, Some(&warnings_log.display().to_string()))
+    );
+
+    let series_name = output
+        .file_name()
+        .unwrap_or(output.as_os_str())
+        .to_string_lossy()
+        .to_string();
+    if let Some(url) = &options.notify_url {
+        notify_url(url, &series_name, &summary);
+    }
+    if let Some(command) = &options.notify_command {
+        notify_command(command, &series_name, &summary);
+    }
+    result?;
+
+    if options.compress_logs {
+        series.compress_logs()?;
+        info!(
+            "Compressed the log files in {}",
+            output.join(SERIES_RUNS_DIR).display()
+        );
+    }
+
+    if options.notify {
+        notify(&completion_message(&experiment.name()?, &summary));
+    }
+
+    if summary.aborted {
+        return Err(Error::HarnessRunError {
+            experiment: experiment.name()?,
+            err: format!(
+                "Aborted after {} failed runs, {} of {} runs completed before",
+                summary.failed,
+                summary.succeeded + summary.timed_out,
+                series.runs().len()
+            ),
+        });
+    }
+
+    if options.list_outputs {
+        let finished = ExperimentSeries::parse(output)?;
+        println!("{}", output_summary(&finished));
+    }
+
+    // all runs have been executed, but the caller still has to know about failures
+    if options.keep_going && summary.failed > 0 {
+        return Err(Error::HarnessRunError {
+            experiment: experiment.name()?,
+            err: format!("{} of {} runs failed", summary.failed, summary.attempted()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Creates the experiment series directory and all run directories for `experiment`,
+/// without executing any of the runs.
+///
+/// The series directory is named like in [experiment()]. Every run directory
+/// contains its run script and environment, so the runs can be executed later,
+/// e.g. on another machine.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` if there are no runs to create
+/// - Returns an error if the series could not be written to `output`
+pub fn provision(experiment: &ExperimentSource, output: Option<PathBuf>) -> Result<()> {
+    let output = match output {
+        Some(x) => x,
+        None => ExperimentSeries::generate_series_filepath(experiment.location())?,
     };
 
     let mut series = ExperimentSeries::from_source(experiment)?;
     series.generate_runs()?;
+    check_runs_exist(&series)?;
     series.persist(&output)?;
 
-    execute_exp_repetitions(&mut series, log_progress_handler, is_trial)
+    info!(
+        "Created {} run directories in {}, not executing them",
+        series.runs().len(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Prints the env file and repetition of every run of `experiment`, in the order
+/// they would be executed, without creating any directories or executing `run.sh`.
+///
+/// Uses `options.seed`, so the printed order matches a real `exomat run` with
+/// the same seed.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` if there are no runs to execute
+pub fn dry_run(experiment: &ExperimentSource, options: &RunOptions) -> Result<()> {
+    let mut series = ExperimentSeries::from_source(experiment)?;
+    series.set_run_options(options.clone());
+    series.generate_runs()?;
+    check_runs_exist(&series)?;
+
+    println!("{}", schedule_table(&series.schedule()));
+    info!(
+        "{} runs would be executed, not executing them",
+        series.runs().len()
+    );
+    Ok(())
+}
+
+/// Returns a table listing the env file and repetition of every planned run.
+fn schedule_table(schedule: &[(PathBuf, u64)]) -> String {
+    let mut table_builder = tabled::builder::Builder::default();
+    table_builder.push_record(["#", "env file", "repetition"]);
+    for (i, (env_file, rep)) in schedule.iter().enumerate() {
+        table_builder.push_record([
+            (i + 1).to_string(),
+            env_file.display().to_string(),
+            rep.to_string(),
+        ]);
+    }
+
+    let mut table = table_builder.build();
+    table.with(tabled::settings::Style::sharp());
+    table.to_string()
+}
+
+/// Returns a table listing every output key of `series`, together with the
+/// number of runs that produced it.
+fn output_summary(series: &ExperimentSeries) -> String {
+    let run_count = series.runs().len();
+
+    let mut table_builder = tabled::builder::Builder::default();
+    table_builder.push_record(["key", "runs", "coverage"]);
+    for (key, produced) in series.key_coverage() {
+        let percent = match run_count {
+            0 => 0.0,
+            n => produced as f64 / n as f64 * 100.0,
+        };
+        table_builder.push_record([
+            key,
+            format!("{produced}/{run_count}"),
+            format!("{percent:.0}%"),
+        ]);
+    }
+
+    let mut table = table_builder.build();
+    table.with(tabled::settings::Style::sharp());
+    table.to_string()
+}
+
+/// Executes all runs of `experiment` twice and compares the outputs of both series.
+///
+/// The first series is written to `output` (or the default location), the second
+/// one next to it, with the suffix `-verify`. Outputs that look like timing
+/// information (see `diff::is_timing_key`) are only compared if `include_timing`
+/// is set.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` listing all differences if the outputs differ
+/// - Same errors as `experiment()`
+pub fn verify_reproducible(
+    experiment: &ExperimentSource,
+    output: Option<PathBuf>,
+    log_progress_handler: MultiProgress,
+    options: &RunOptions,
+    include_timing: bool,
+) -> Result<()> {
+    let first = match output {
+        Some(x) => x,
+        None => ExperimentSeries::generate_series_filepath(experiment.location())?,
+    };
+    let mut second = first.clone().into_os_string();
+    second.push("-verify");
+    let second = PathBuf::from(second);
+
+    info!("First execution into {}", first.display());
+    self::experiment(
+        experiment,
+        Some(first.clone()),
+        log_progress_handler.clone(),
+        false,
+        options,
+    )?;
+    info!("Second execution into {}", second.display());
+    self::experiment(
+        experiment,
+        Some(second.clone()),
+        log_progress_handler,
+        false,
+        options,
+    )?;
+
+    let differences = diff::diff_series(
+        &ExperimentSeries::parse(&first)?,
+        &ExperimentSeries::parse(&second)?,
+        |key| !include_timing && diff::is_timing_key(key),
+    );
+
+    match differences.is_empty() {
+        true => {
+            info!("Experiment is reproducible, outputs of both executions match");
+            Ok(())
+        }
+        false => Err(Error::HarnessRunError {
+            experiment: experiment.name()?,
+            err: format!(
+                "Experiment is not reproducible, found {} difference(s):\n{}",
+                differences.len(),
+                differences.iter().map(|d| d.to_string()).join("\n")
+            ),
+        }),
+    }
 }
 
 /// Creates an experiment series/run directory for the given `experiment`.
 /// Then executes the `run.sh` file for this experiment once and collects any
 /// output/errors/results.
 ///
-/// The new experiment series directory will be created as a tempdir.
-pub fn trial(experiment: &ExperimentSource, log_progress_handler: MultiProgress) -> Result<()> {
+/// The new experiment series directory will be created in `trial_dir`, or in the
+/// temp dir of the system (usually `$TMPDIR` or `/tmp`) if no `trial_dir` is given.
+///
+/// ## Errors
+/// - Returns a `HarnessCreateError` if `trial_dir` is no writable directory
+/// - Same errors as `experiment()`
+pub fn trial(
+    experiment: &ExperimentSource,
+    trial_dir: Option<&Path>,
+    log_progress_handler: MultiProgress,
+    options: &RunOptions,
+) -> Result<()> {
+    let trial_dir = match trial_dir {
+        Some(dir) => {
+            check_writable_dir(dir)?;
+            dir.to_path_buf()
+        }
+        None => std::env::temp_dir(),
+    };
+
     let format = &Local::now()
         .format("exomat_trial-%Y-%m-%d-%H-%M-%S")
         .to_string();
-    let trial_dir_path = std::env::temp_dir().join(format);
+    let trial_dir_path = trial_dir.join(format);
     let trial = experiment.to_trial_source();
 
     crate::disable_console_log();
 
+    // the single run of a trial is never a warmup
+    let options = RunOptions {
+        warmup: 0,
+        ..options.clone()
+    };
+
     // run experiment once
     let res = self::experiment(
         &trial,
         Some(trial_dir_path.clone()),
         log_progress_handler,
         true,
+        &options,
     );
 
     // flush exomat log
@@ -69,6 +427,75 @@ pub fn trial(experiment: &ExperimentSource, log_progress_handler: MultiProgress)
     res
 }
 
+/// Makes sure that `dir` is a directory, in which new files can be created.
+fn check_writable_dir(dir: &Path) -> Result<()> {
+    let to_create_error = |reason: String| Error::HarnessCreateError {
+        entry: dir.display().to_string(),
+        reason,
+    };
+
+    if !dir.is_dir() {
+        return Err(to_create_error("is not a directory".to_string()));
+    }
+
+    let probe = dir.join(".exomat_write_probe");
+    std::fs::write(&probe, "")
+        .map_err(|e| to_create_error(format!("directory is not writable: {e}")))?;
+    std::fs::remove_file(&probe)?;
+
+    Ok(())
+}
+
+/// Checks the run script of `experiment` for syntax errors, without executing it.
+///
+/// The script is passed to the interpreter from its shebang in no-exec mode
+/// (`sh -n`). Only POSIX shells support this, scripts with any other interpreter
+/// are skipped with a warning.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` containing the reported syntax errors
+/// - Returns a `HarnessRunError` if the interpreter could not be executed
+pub fn syntax_check(experiment: &ExperimentSource) -> Result<()> {
+    let name = experiment.name().unwrap_or_else(|_| "run.sh".to_string());
+    let to_run_error = |err: String| Error::HarnessRunError {
+        experiment: name.clone(),
+        err,
+    };
+
+    let Some(shell) = posix_shell(experiment.run_script()) else {
+        warn!("{name}: syntax check is only supported for POSIX shell scripts, skipping it");
+        return Ok(());
+    };
+
+    debug!("checking syntax of run script with {shell} -n");
+    let mut check = Command::new(&shell)
+        .arg("-n")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| to_run_error(format!("Cannot execute {shell}: {e}")))?;
+
+    // stdin is closed when dropped, so the shell stops reading
+    check
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(experiment.run_script().as_bytes())?;
+    let result = check.wait_with_output()?;
+
+    match result.status.success() {
+        true => {
+            info!("{name}: no syntax errors found in run script");
+            Ok(())
+        }
+        false => Err(to_run_error(format!(
+            "Syntax error in run script:\n{}",
+            String::from_utf8_lossy(&result.stderr).trim_end()
+        ))),
+    }
+}
+
 /// Runs the experiment defined in `exp_source_dir` `repetitions` times for each
 /// environment.
 ///
@@ -76,25 +503,32 @@ pub fn trial(experiment: &ExperimentSource, log_progress_handler: MultiProgress)
 ///
 /// This functions assumes that `build_series_directory` has been called before it.
 /// Otherwise it will fail, because the files it expects to be there are not.
+///
+/// Writes a checkpoint into the series directory every `options.checkpoint_interval`
+/// finished runs. Runs found in `options.skip_cached` are not executed, their
+/// outputs are copied instead. No more runs are started once `options.max_failures`
+/// runs have failed, or a run failed without `options.keep_going`. Both are
+/// recorded in `summary`, which is filled even if an error is returned.
+///
+/// Unless this is a trial, a summary of all runs is printed to stdout once the
+/// progress bar has finished (see `series_report()`).
+///
+/// ## Errors
+/// - Returns the error of the first failed run, after the logs of all
+///   executed runs have been written
 fn execute_exp_repetitions(
     series: &mut ExperimentSeries,
     log_progress_handler: MultiProgress,
     is_trial: bool,
+    options: &RunOptions,
+    summary: &mut SeriesSummary,
 ) -> Result<()> {
-    // if series
-    //     Error::HarnessRunError {
-    //         experiment: exp_source_dir.display().to_string(),
-    //         err: format!(
-    //             "No environments found in {}",
-    //             exp_source_dir.join(SRC_ENV_DIR).display()
-    //         ),
-    //     }
-    // })?;
+    check_runs_exist(series)?;
 
     let prog_bar = if is_trial {
         ProgressBar::new(1)
     } else {
-        ProgressBar::new(series.repetition_count() + 1)
+        ProgressBar::new(series.runs().len() as u64 + 1)
     };
 
     prog_bar.set_style(
@@ -112,14 +546,53 @@ fn execute_exp_repetitions(
 
     let mut stdout = String::new();
     let mut stderr = String::new();
+    let mut run_error = None;
+
+    let series_dir = series.location().clone().ok_or_else(|| {
+        Error::Empty("Experiment Series has not been written to disk".to_string())
+    })?;
+    let mut checkpoint = Checkpoint::new(series.runs().len(), options.checkpoint_interval);
+
+    let cache = match &options.skip_cached {
+        Some(cache_dir) => RunCache::index(cache_dir, &series_dir)?,
+        None => RunCache::default(),
+    };
+    let mut reused = 0;
 
     for mut run in series.iter() {
         trace!("Using envs: {:?}", run.environment());
 
-        let (out, err) = run.execute(&series.experiment_name()?)?;
+        let (out, err) = match cache.get(&run.env_hash()) {
+            Some(cached_run) => {
+                info!(
+                    "{}: reusing outputs of {}",
+                    run.run_dir_name(),
+                    cached_run.display()
+                );
+                run.reuse_outputs(cached_run)?;
+                reused += 1;
+                (String::new(), String::new())
+            }
+            None => match run.execute(&series.experiment_name()?) {
+                Ok(logs) => logs,
+                Err(e) => {
+                    run_error = Some(e);
+                    (String::new(), String::new())
+                }
+            },
+        };
+        summary.stderr.record(&err);
+        summary.stdout.record(&out);
+        match run.status() {
+            RunStatus::Success => summary.succeeded += 1,
+            RunStatus::Timeout => summary.timed_out += 1,
+            _ => summary.failed += 1,
+        }
         stderr.push_str(&err);
         stdout.push_str(&out);
 
+        checkpoint.finish_run(run.run_dir_name(), run.status().clone(), &series_dir)?;
+
         // update progress
         prog_bar.inc(1);
 
@@ -127,6 +600,28 @@ fn execute_exp_repetitions(
         if is_trial {
             break;
         }
+
+        if run_error.is_some() {
+            summary.aborted = true;
+            break;
+        }
+
+        if options
+            .max_failures
+            .is_some_and(|max| summary.failed >= max)
+        {
+            error!(
+                "{} runs failed, not starting any more runs (--max-failures)",
+                summary.failed
+            );
+            summary.aborted = true;
+            break;
+        }
+    }
+
+    checkpoint.write(&series_dir)?;
+    if options.skip_cached.is_some() {
+        info!("Reused the outputs of {reused} cached runs");
     }
 
     info!("Serializing logs...");
@@ -140,7 +635,212 @@ fn execute_exp_repetitions(
 
     prog_bar.inc(1);
     prog_bar.finish();
-    Ok(())
+
+    summary.elapsed = prog_bar.elapsed();
+    if !is_trial {
+        println!("{}", series_report(summary, &series_dir));
+    }
+
+    info!(
+        "Runs wrote {} to stdout and {} to stderr",
+        summary.stdout, summary.stderr
+    );
+
+    match run_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Makes sure that `series` contains at least one run to execute.
+///
+/// ## Errors
+/// - Returns a `HarnessRunError` explaining why no runs were generated
+fn check_runs_exist(series: &ExperimentSeries) -> Result<()> {
+    if !series.runs().is_empty() {
+        return Ok(());
+    }
+
+    let source = series.source();
+    let reason = match (*source.repetitions(), source.envs().len()) {
+        (0, _) => "repetitions is set to 0".to_string(),
+        (_, 0) => "no environments were found".to_string(),
+        (_, n) => format!("{n} env file(s) were found, but they yield zero combinations"),
+    };
+
+    Err(Error::HarnessRunError {
+        experiment: series.experiment_name()?,
+        err: format!("No runs to execute, {reason}"),
+    })
+}
+
+/// Outcome of all runs of an Experiment Series
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SeriesSummary {
+    succeeded: usize,
+    failed: usize,
+    timed_out: usize,
+    stdout: StreamStats,
+    stderr: StreamStats,
+    elapsed: std::time::Duration,
+    /// stopped early because of `RunOptions::max_failures` or a failed run
+    aborted: bool,
+}
+
+impl SeriesSummary {
+    /// Number of runs that have been executed (or reused from a cache)
+    fn attempted(&self) -> usize {
+        self.succeeded + self.failed + self.timed_out
+    }
+}
+
+/// Returns the summary printed at the end of an Experiment Series in `series_dir`.
+fn series_report(summary: &SeriesSummary, series_dir: &Path) -> String {
+    let mut failed = summary.failed.to_string();
+    if summary.timed_out > 0 {
+        failed.push_str(&format!(" (+{} timed out)", summary.timed_out));
+    }
+
+    [
+        ("runs attempted:", summary.attempted().to_string()),
+        ("succeeded:", summary.succeeded.to_string()),
+        ("failed:", failed),
+        ("elapsed:", HumanDuration(summary.elapsed).to_string()),
+        ("series:", series_dir.display().to_string()),
+    ]
+    .iter()
+    .map(|(label, value)| format!("{label:<16}{value}"))
+    .join("\n")
+}
+
+/// Returns the message shown by `--notify` once `experiment` has finished.
+fn completion_message(experiment: &str, summary: &SeriesSummary) -> String {
+    let message = format!(
+        "{experiment} finished: {} of {} runs successful, {} failed",
+        summary.succeeded,
+        summary.attempted(),
+        summary.failed
+    );
+
+    match summary.timed_out {
+        0 => message,
+        n => format!("{message}, {n} timed out"),
+    }
+}
+
+/// Tells the user that an experiment has finished.
+///
+/// Rings the terminal bell and, if exomat was built with the `desktop-notify`
+/// feature, shows `message` as a desktop notification. Failing to show the
+/// notification (e.g. without a desktop session) is not an error.
+fn notify(message: &str) {
+    eprint!("\x07");
+
+    #[cfg(feature = "desktop-notify")]
+    if let Err(e) = notify_rust::Notification::new()
+        .summary("exomat")
+        .body(message)
+        .show()
+    {
+        debug!("could not show desktop notification: {e}");
+    }
+
+    info!("{message}");
+}
+
+/// Returns the JSON payload sent by `--notify-url` for `series`.
+fn notify_payload(series: &str, summary: &SeriesSummary) -> String {
+    serde_json::json!({
+        "series": series,
+        "succeeded": summary.succeeded,
+        "failed": summary.failed,
+        "timed_out": summary.timed_out,
+        "duration_s": summary.elapsed.as_secs_f64(),
+        "aborted": summary.aborted,
+    })
+    .to_string()
+}
+
+/// POSTs the summary of `series` as JSON to `url` with `curl`.
+///
+/// Failing to send it only logs a warning, the results of the series are not
+/// affected by an unreachable webhook.
+fn notify_url(url: &str, series: &str, summary: &SeriesSummary) {
+    let send = || -> std::result::Result<(), String> {
+        let mut curl = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--max-time", "30"])
+            .args(["--header", "Content-Type: application/json"])
+            .args(["--data-binary", "@-", url])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("cannot execute curl: {e}"))?;
+
+        curl.stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(notify_payload(series, summary).as_bytes())
+            .map_err(|e| e.to_string())?;
+        let result = curl.wait_with_output().map_err(|e| e.to_string())?;
+
+        match result.status.success() {
+            true => Ok(()),
+            false => Err(String::from_utf8_lossy(&result.stderr).trim().to_string()),
+        }
+    };
+
+    match send() {
+        Ok(()) => debug!("sent summary of {series} to {url}"),
+        Err(e) => warn!("Could not notify {url}: {e}"),
+    }
+}
+
+/// Executes `command` with `sh -c`, the summary of `series` is passed via
+/// `EXOMAT_SERIES`, `EXOMAT_SUCCEEDED`, `EXOMAT_FAILED`, `EXOMAT_DURATION` and
+/// `EXOMAT_ABORTED`.
+///
+/// A failing command only logs a warning.
+fn notify_command(command: &str, series: &str, summary: &SeriesSummary) {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("EXOMAT_SERIES", series)
+        .env("EXOMAT_SUCCEEDED", summary.succeeded.to_string())
+        .env("EXOMAT_FAILED", summary.failed.to_string())
+        .env(
+            "EXOMAT_DURATION",
+            format!("{:.3}", summary.elapsed.as_secs_f64()),
+        )
+        .env("EXOMAT_ABORTED", summary.aborted.to_string())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => debug!("executed notify command for {series}"),
+        Ok(status) => warn!("Notify command \"{command}\" failed with {status}"),
+        Err(e) => warn!("Could not execute notify command \"{command}\": {e}"),
+    }
+}
+
+/// Amount of output written to one stream (stdout/stderr) by all runs
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct StreamStats {
+    lines: usize,
+    bytes: usize,
+}
+
+impl StreamStats {
+    /// Adds the output `text` of one run to the stats.
+    fn record(&mut self, text: &str) {
+        self.lines += text.lines().count();
+        self.bytes += text.len();
+    }
+}
+
+impl std::fmt::Display for StreamStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} lines ({} bytes)", self.lines, self.bytes)
+    }
 }
 
 #[cfg(test)]
@@ -151,11 +851,123 @@ mod tests {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::experiment::experiment_run::EXIT_CODE_OUT_FILE;
     use crate::experiment::{ExperimentRun, ExperimentSource, FileWriter};
     use crate::harness::env::{Environment, ExomatEnvironment};
+    use crate::helper::archivist::read_maybe_gzipped;
     use crate::helper::fs_names::*;
     use crate::helper::test_helper::read_log;
 
+    #[test]
+    fn notify_message() {
+        let summary = SeriesSummary {
+            succeeded: 3,
+            failed: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            completion_message("loadavg", &summary),
+            "loadavg finished: 3 of 4 runs successful, 1 failed"
+        );
+
+        let summary = SeriesSummary {
+            timed_out: 2,
+            ..summary
+        };
+        assert_eq!(
+            completion_message("loadavg", &summary),
+            "loadavg finished: 3 of 6 runs successful, 1 failed, 2 timed out"
+        );
+    }
+
+    #[test]
+    fn notify_url_payload() {
+        let summary = SeriesSummary {
+            succeeded: 3,
+            failed: 1,
+            elapsed: std::time::Duration::from_millis(1500),
+            ..Default::default()
+        };
+        let payload: serde_json::Value =
+            serde_json::from_str(&notify_payload("loadavg-2025-01-01-00-00-00", &summary)).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "series": "loadavg-2025-01-01-00-00-00",
+                "succeeded": 3,
+                "failed": 1,
+                "timed_out": 0,
+                "duration_s": 1.5,
+                "aborted": false,
+            })
+        );
+    }
+
+    #[test]
+    fn report_at_end_of_series() {
+        let summary = SeriesSummary {
+            succeeded: 3,
+            failed: 1,
+            timed_out: 2,
+            elapsed: std::time::Duration::from_secs(90),
+            ..Default::default()
+        };
+        assert_eq!(
+            series_report(&summary, Path::new("loadavg-2025-01-01-00-00-00")),
+            "runs attempted: 6\n\
+             succeeded:      3\n\
+             failed:         1 (+2 timed out)\n\
+             elapsed:        2 minutes\n\
+             series:         loadavg-2025-01-01-00-00-00"
+        );
+    }
+
+    #[test]
+    fn no_runs_to_execute() {
+        let tmpdir = TempDir::new().unwrap();
+        let mut src = ExperimentSource::new();
+        src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.path().join("Foo"), 0));
+
+        let mut series = ExperimentSeries::from_source(&src).unwrap();
+        series.generate_runs().unwrap();
+        let err = check_runs_exist(&series).unwrap_err().to_string();
+        assert!(err.contains("No runs to execute, repetitions is set to 0"));
+
+        // no series directory is left behind
+        let output = tmpdir.path().join("FooSeries");
+        src.persist(&tmpdir.path().join("Foo")).unwrap();
+        let err = experiment(
+            &src,
+            Some(output.clone()),
+            MultiProgress::new(),
+            false,
+            &RunOptions::default(),
+        );
+        assert!(err.unwrap_err().to_string().contains("No runs to execute"));
+        assert!(!output.exists());
+
+        src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.path().join("Foo"), 1));
+        let mut series = ExperimentSeries::from_source(&src).unwrap();
+        series.generate_runs().unwrap();
+        assert!(check_runs_exist(&series).is_ok());
+    }
+
+    #[test]
+    fn syntax_check_reports_errors() {
+        let mut src = ExperimentSource::new();
+
+        src.set_run_script("#!/bin/sh\nif true; then\n    echo ok\nfi\n".to_string());
+        assert!(syntax_check(&src).is_ok());
+
+        src.set_run_script("#!/bin/sh\nif true; then\n    echo missing fi\n".to_string());
+        let err = syntax_check(&src).unwrap_err().to_string();
+        assert!(err.contains("Syntax error in run script"));
+
+        // no check mode available, skipped
+        src.set_run_script("#!/usr/bin/env python3\nif True\n".to_string());
+        assert!(syntax_check(&src).is_ok());
+    }
+
     rusty_fork_test! {
         #[test]
         fn test_run() {
@@ -168,13 +980,13 @@ mod tests {
 
             // write something in run.sh
             let mut src = ExperimentSource::new();
-            src.set_run_script(format!("#!/bin/bash\necho $EXP_SRC_DIR\necho $EXP_SRC_DIR >> out_file"));
-            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 1));
-            src.persist(&exp_source).unwrap();
+            src.set_run_script("#!/bin/bash\necho $EXP_SRC_DIR\necho $EXP_SRC_DIR >> out_file".to_string());
+            src.set_exomat_envs(ExomatEnvironment::new(exp_source, 1));
+            src.persist(exp_source).unwrap();
 
             let mut ser = ExperimentSeries::from_source(&src).unwrap();
             ser.generate_runs().unwrap();
-            ser.persist(&exp_series).unwrap();
+            ser.persist(exp_series).unwrap();
 
             // run experiment
             assert_eq!(ser.runs().len(), 1);
@@ -205,7 +1017,7 @@ mod tests {
             // Write something to run.sh that uses env var
             // make multiple .env files that set $FOO to different values
             let mut src = ExperimentSource::new();
-            src.set_run_script(format!("#!/bin/bash\necho $FOO\necho $FOO >> out_file"));
+            src.set_run_script("#!/bin/bash\necho $FOO\necho $FOO >> out_file".to_string());
             src.set_envs(HashMap::from([
                 (PathBuf::from("0.env"), Environment::from_env_list(vec![("FOO".to_string(), "BAR".to_string())])),
                 (PathBuf::from("1.env"), Environment::from_env_list(vec![("FOO".to_string(), "Z".to_string())])),
@@ -218,7 +1030,8 @@ mod tests {
                 &src,
                 Some(PathBuf::from(out_name)),
                 MultiProgress::new(), // empty
-                false
+                false,
+                &RunOptions::default(),
             )
             .unwrap();
 
@@ -232,11 +1045,108 @@ mod tests {
             assert!(stdout_log.contains("BAR"));
 
             // take one out_file and check its content
-            let output = read_log(tmpdir.join(out_name), format!("run_0_rep0/out_file").as_str());
+            let output = read_log(tmpdir.join(out_name), "run_0_rep0/out_file".to_string().as_str());
             assert_eq!(output.lines().count(), 1);
             assert!(output.contains("BAR"));
         }
 
+        #[test]
+        fn harness_run_stream_stats() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho hello\necho world\necho oops >&2".to_string());
+            src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("TestSource"), 2));
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+
+            let mut series = ExperimentSeries::from_source(&src).unwrap();
+            series.generate_runs().unwrap();
+            series.persist(&tmpdir.join("out")).unwrap();
+
+            let mut summary = SeriesSummary::default();
+            execute_exp_repetitions(&mut series, MultiProgress::new(), false, &RunOptions::default(), &mut summary).unwrap();
+            assert_eq!(summary.stdout, StreamStats { lines: 4, bytes: 24 });
+            assert_eq!(summary.stderr, StreamStats { lines: 2, bytes: 10 });
+            assert_eq!(summary.stdout.to_string(), "4 lines (24 bytes)");
+            assert_eq!(summary.succeeded, 2);
+        }
+
+        #[test]
+        fn harness_run_list_outputs() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            // only one environment produces out_extra
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho $FOO >> out_foo\n[ $FOO = Z ] && echo 1 >> out_extra\ntrue".to_string());
+            src.set_envs(HashMap::from([
+                (PathBuf::from("0.env"), Environment::from_env_list(vec![("FOO".to_string(), "BAR".to_string())])),
+                (PathBuf::from("1.env"), Environment::from_env_list(vec![("FOO".to_string(), "Z".to_string())])),
+            ])).unwrap();
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+
+            let options = RunOptions { list_outputs: true, ..Default::default() };
+            experiment(&src, Some(PathBuf::from("out")), MultiProgress::new(), false, &options).unwrap();
+
+            let summary = output_summary(&ExperimentSeries::parse(&tmpdir.join("out")).unwrap());
+            let foo_line = summary.lines().find(|l| l.contains("foo")).unwrap();
+            let extra_line = summary.lines().find(|l| l.contains("extra")).unwrap();
+            assert!(foo_line.contains("2/2") && foo_line.contains("100%"));
+            assert!(extra_line.contains("1/2") && extra_line.contains("50%"));
+        }
+
+        #[test]
+        fn harness_run_checkpoint() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            // every run records the checkpoint it sees while running
+            let mut src = ExperimentSource::new();
+            src.set_run_script(format!("#!/bin/bash\ngrep finished ../../{SERIES_CHECKPOINT} > out_seen\ntrue"));
+            src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("TestSource"), 3));
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+
+            let options = RunOptions { checkpoint_interval: 1, ..Default::default() };
+            experiment(&src, Some(PathBuf::from("out")), MultiProgress::new(), false, &options).unwrap();
+
+            let series = ExperimentSeries::parse(&tmpdir.join("out")).unwrap();
+            let seen = &series.to_columns()["seen"];
+            assert!(seen.contains(&"finished: 1".to_string()));
+            assert!(seen.contains(&"finished: 2".to_string()));
+
+            let checkpoint = std::fs::read_to_string(tmpdir.join("out").join(SERIES_CHECKPOINT)).unwrap();
+            assert!(checkpoint.starts_with("total: 3\nfinished: 3\n"));
+        }
+
+        #[test]
+        fn harness_run_verify_reproducible() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let mut src = ExperimentSource::new();
+            src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("TestSource"), 2));
+
+            // deterministic, apart from timing
+            src.set_run_script("#!/bin/bash\necho $REPETITION > out_result\necho $RANDOM > out_runtime".to_string());
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+            verify_reproducible(&src, Some(PathBuf::from("same")), MultiProgress::new(), &RunOptions::default(), false).unwrap();
+            assert!(tmpdir.join("same-verify").is_dir());
+
+            // timing is compared on request
+            let err = verify_reproducible(&src, Some(PathBuf::from("timing")), MultiProgress::new(), &RunOptions::default(), true).unwrap_err();
+            assert!(err.to_string().contains("runtime differs"));
+
+            // nondeterministic result
+            src.set_run_script("#!/bin/bash\necho $RANDOM$RANDOM > out_result".to_string());
+            let err = verify_reproducible(&src, Some(PathBuf::from("random")), MultiProgress::new(), &RunOptions::default(), false).unwrap_err();
+            assert!(err.to_string().contains("not reproducible, found 2 difference(s)"));
+        }
+
         #[test]
         fn trial_e2e() {
             // create ouput dir
@@ -248,7 +1158,7 @@ mod tests {
             // Write something to run.sh that uses env var
             // make multiple .env files that set $FOO to different values
             let mut src = ExperimentSource::new();
-            src.set_run_script(format!("#!/bin/bash\necho $FOO\necho $FOO >> out_file"));
+            src.set_run_script("#!/bin/bash\necho $FOO\necho $FOO >> out_file".to_string());
             src.set_envs(HashMap::from([
                 (PathBuf::from("0.env"),Environment::from_env_list(vec![("FOO".to_string(), "BAR".to_string())])),
                 (PathBuf::from("1.env"),Environment::from_env_list(vec![("FOO".to_string(), "Z".to_string())])),
@@ -256,7 +1166,406 @@ mod tests {
             src.persist(&tmpdir.join("TestSource")).unwrap();
 
             // no error
-            trial(&src, MultiProgress::new()).unwrap();
+            trial(&src, None, MultiProgress::new(), &RunOptions::default()).unwrap();
+        }
+
+        #[test]
+        fn harness_run_keep_going() {
+            crate::activate_logging(log::LevelFilter::Info);
+
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho done > out_done\necho oops >&2\nexit $CODE".to_string());
+            src.persist(&exp_source).unwrap();
+            for (i, code) in [0, 3, 0].iter().enumerate() {
+                std::fs::write(exp_source.join(SRC_ENV_DIR).join(format!("{i}.env")), format!("CODE={code}")).unwrap();
+            }
+            let src = ExperimentSource::parse(&exp_source).unwrap();
+
+            // aborts on the first failure by default, ordered so only 0.env runs before
+            let ordered = RunOptions { ordered: true, ..Default::default() };
+            assert!(experiment(&src, Some(tmpdir.join("abort")), MultiProgress::new(), false, &ordered).is_err());
+            // the warning of the run before the failure is kept nonetheless
+            let warnings = read_log(tmpdir.join("abort"), SERIES_WARNINGS_LOG);
+            let warnings: Vec<String> = warnings.lines().map(String::from).collect();
+            assert_eq!(warnings.len(), 1);
+            assert!(warnings[0].ends_with("run_0_rep0 produced stderr output"));
+            assert_eq!(crate::helper::warnings::summary(&warnings, None), "Completed with 1 warning.");
+            assert!(crate::helper::warnings::take().is_empty());
+
+            let options = RunOptions {
+                keep_going: true,
+                ..Default::default()
+            };
+            let err = experiment(&src, Some(tmpdir.join("series")), MultiProgress::new(), false, &options).unwrap_err();
+            assert!(err.to_string().contains("1 of 3 runs failed"));
+
+            let runs = tmpdir.join("series").join(SERIES_RUNS_DIR);
+            for run in ["run_0_rep0", "run_1_rep0", "run_2_rep0"] {
+                assert!(runs.join(run).join("out_done").is_file());
+            }
+            assert_eq!(std::fs::read_to_string(runs.join("run_1_rep0").join(EXIT_CODE_OUT_FILE)).unwrap(), "3");
+            assert_eq!(std::fs::read_to_string(runs.join("run_0_rep0").join(EXIT_CODE_OUT_FILE)).unwrap(), "0");
+        }
+
+        #[test]
+        fn harness_run_warmup() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            // every execution is counted in the series directory
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash
+echo $(wc -l < ../../executions 2>/dev/null || echo 0) > out_order
+echo x >> ../../executions".to_string());
+            src.set_envs(HashMap::from([
+                (PathBuf::from("0.env"), Environment::from_env_list(vec![("FOO".to_string(), "BAR".to_string())])),
+                (PathBuf::from("1.env"), Environment::from_env_list(vec![("FOO".to_string(), "Z".to_string())])),
+            ])).unwrap();
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+            src.persist(&exp_source).unwrap();
+
+            let options = RunOptions {
+                warmup: 1,
+                ..Default::default()
+            };
+            let series = tmpdir.join("series");
+            experiment(&src, Some(series.clone()), MultiProgress::new(), false, &options).unwrap();
+
+            // warmups are executed first
+            let runs = series.join(SERIES_RUNS_DIR);
+            let order = |run: &str| std::fs::read_to_string(runs.join(run).join("out_order")).unwrap().trim().parse::<usize>().unwrap();
+            for warmup in ["warmup_0_rep0", "warmup_1_rep0"] {
+                assert!(order(warmup) < 2);
+            }
+            for run in ["run_0_rep0", "run_0_rep1", "run_1_rep0", "run_1_rep1"] {
+                assert!(order(run) >= 2);
+            }
+
+            // but not collected
+            let parsed = ExperimentSeries::parse(&series).unwrap();
+            assert_eq!(parsed.runs().len(), 4);
+        }
+
+        #[test]
+        fn harness_run_resume() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            // every execution is counted in the series directory
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho $FOO\necho $FOO > out_foo\necho x >> ../../executions".to_string());
+            src.set_envs(HashMap::from([
+                (PathBuf::from("0.env"), Environment::from_env_list(vec![("FOO".to_string(), "BAR".to_string())])),
+                (PathBuf::from("1.env"), Environment::from_env_list(vec![("FOO".to_string(), "Z".to_string())])),
+            ])).unwrap();
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+            src.persist(&exp_source).unwrap();
+
+            let series = tmpdir.join("series");
+            let runs = series.join(SERIES_RUNS_DIR);
+            let executions = || std::fs::read_to_string(series.join("executions")).unwrap().lines().count();
+            experiment(&src, Some(series.clone()), MultiProgress::new(), false, &RunOptions::default()).unwrap();
+            assert_eq!(executions(), 4);
+
+            // simulate an interruption: one run has no results, one was never created
+            for entry in std::fs::read_dir(runs.join("run_0_rep1")).unwrap() {
+                let file = entry.unwrap().path();
+                let name = file.file_name().unwrap().to_str().unwrap();
+                if name.starts_with("out_") || name == RUN_STDOUT_LOG || name == RUN_STDERR_LOG {
+                    std::fs::remove_file(&file).unwrap();
+                }
+            }
+            std::fs::remove_dir_all(runs.join("run_1_rep1")).unwrap();
+            assert!(!ExperimentRun::is_complete(&runs.join("run_0_rep1")));
+            assert!(ExperimentRun::is_complete(&runs.join("run_0_rep0")));
+
+            resume(&src, &series, MultiProgress::new(), &RunOptions::default()).unwrap();
+            assert_eq!(executions(), 6);
+            for run in ["run_0_rep0", "run_0_rep1", "run_1_rep0", "run_1_rep1"] {
+                assert!(runs.join(run).join("out_foo").is_file());
+            }
+            // output of the first execution is kept
+            assert_eq!(read_log(series.clone(), SERIES_STDOUT_LOG).lines().count(), 6);
+
+            // nothing left to do
+            resume(&src, &series, MultiProgress::new(), &RunOptions::default()).unwrap();
+            assert_eq!(executions(), 6);
+
+            assert!(resume(&src, &tmpdir, MultiProgress::new(), &RunOptions::default()).is_err());
+        }
+
+        #[test]
+        fn harness_run_compress_logs() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho $REPETITION\necho 1 > out_x".to_string());
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+            src.persist(&exp_source).unwrap();
+
+            let options = RunOptions {
+                compress_logs: true,
+                ..Default::default()
+            };
+            let series = tmpdir.join("series");
+            let runs = series.join(SERIES_RUNS_DIR);
+            experiment(&src, Some(series.clone()), MultiProgress::new(), false, &options).unwrap();
+            for log in SERIES_LOG_FILES {
+                assert!(!runs.join(log).exists());
+                assert!(runs.join(format!("{log}.gz")).is_file());
+            }
+
+            // compressed logs are read transparently
+            let parsed = ExperimentSeries::parse(&series).unwrap();
+            assert_eq!(parsed.runs().len(), 2);
+            let stdout = read_maybe_gzipped(&runs.join(SERIES_STDOUT_LOG)).unwrap();
+            assert_eq!(stdout.lines().count(), 2);
+
+            // resuming appends to the compressed logs
+            std::fs::remove_dir_all(runs.join("run_0_rep1")).unwrap();
+            resume(&src, &series, MultiProgress::new(), &options).unwrap();
+            assert!(!runs.join(SERIES_STDOUT_LOG).exists());
+            let stdout = read_maybe_gzipped(&runs.join(SERIES_STDOUT_LOG)).unwrap();
+            assert_eq!(stdout.lines().count(), 3);
+        }
+
+        #[test]
+        fn harness_run_max_failures() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho x >> ../../executions\nexit 1".to_string());
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 10));
+            src.persist(&exp_source).unwrap();
+
+            let options = RunOptions {
+                keep_going: true,
+                max_failures: Some(3),
+                // unreachable webhooks do not change the result
+                notify_url: Some("http://127.0.0.1:9/".to_string()),
+                notify_command: Some("echo $EXOMAT_SERIES $EXOMAT_FAILED $EXOMAT_ABORTED > notified".to_string()),
+                ..Default::default()
+            };
+            let err = experiment(&src, Some(tmpdir.join("series")), MultiProgress::new(), false, &options).unwrap_err();
+            assert!(err.to_string().contains("Aborted after 3 failed runs, 0 of 10 runs completed before"));
+
+            let executions = std::fs::read_to_string(tmpdir.join("series").join("executions")).unwrap();
+            assert_eq!(executions.lines().count(), 3);
+            assert_eq!(std::fs::read_to_string(tmpdir.join("notified")).unwrap(), "series 3 true\n");
+        }
+
+        #[test]
+        fn harness_run_notify_on_failure() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\nexit 3".to_string());
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+            src.persist(&exp_source).unwrap();
+
+            // without --keep-going, the first failed run aborts the series
+            let options = RunOptions {
+                notify_command: Some("echo $EXOMAT_SERIES $EXOMAT_FAILED $EXOMAT_ABORTED > notified".to_string()),
+                ..Default::default()
+            };
+            assert!(experiment(&src, Some(tmpdir.join("series")), MultiProgress::new(), false, &options).is_err());
+            assert_eq!(std::fs::read_to_string(tmpdir.join("notified")).unwrap(), "series 1 true\n");
+        }
+
+        #[test]
+        fn harness_run_dry_run() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho $FOO $REPETITION >> ../../order".to_string());
+            src.set_envs(HashMap::from_iter(["A", "B", "C"].iter().enumerate().map(|(i, foo)| {
+                (PathBuf::from(format!("{i}.env")), Environment::from_env_list(vec![("FOO".to_string(), foo.to_string())]))
+            }))).unwrap();
+            src.set_exomat_envs(ExomatEnvironment::new(&exp_source, 2));
+            src.persist(&exp_source).unwrap();
+
+            let options = RunOptions { seed: Some(7), ..Default::default() };
+            dry_run(&src, &options).unwrap();
+            assert_eq!(std::fs::read_dir(&tmpdir).unwrap().count(), 1);
+
+            let mut series = ExperimentSeries::from_source(&src).unwrap();
+            series.set_run_options(options.clone());
+            let schedule = series.schedule();
+            assert_eq!(schedule.len(), 6);
+            assert!(schedule_table(&schedule).contains("│ 6 │"));
+
+            // the real execution uses the printed order
+            experiment(&src, Some(tmpdir.join("series")), MultiProgress::new(), false, &options).unwrap();
+            let foo = |env_file: &PathBuf| ["A", "B", "C"][env_file.file_prefix().unwrap().to_str().unwrap().parse::<usize>().unwrap()];
+            let expected: Vec<String> = schedule.iter().map(|(env_file, rep)| format!("{} {rep}", foo(env_file))).collect();
+            let order = std::fs::read_to_string(tmpdir.join("series").join("order")).unwrap();
+            assert_eq!(order.lines().collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn harness_run_warnings_log() {
+            crate::activate_logging(log::LevelFilter::Info);
+
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho oops >&2".to_string());
+            src.persist(&exp_source).unwrap();
+            for i in 0..3 {
+                std::fs::write(exp_source.join(SRC_ENV_DIR).join(format!("{i}.env")), format!("VAR={i}")).unwrap();
+            }
+            let src = ExperimentSource::parse(&exp_source).unwrap();
+
+            let series_dir = tmpdir.join("series");
+            experiment(&src, Some(series_dir.clone()), MultiProgress::new(), false, &RunOptions::default()).unwrap();
+
+            // one warning per run with stderr output
+            let warnings = read_log(series_dir, SERIES_WARNINGS_LOG);
+            assert_eq!(warnings.lines().count(), 3);
+            assert!(warnings.lines().all(|w| w.ends_with("produced stderr output")));
+        }
+
+        #[test]
+        fn harness_run_skip_cached() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let exp_source = tmpdir.join("TestSource");
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho $RANDOM$RANDOM$RANDOM > out_value\necho executed".to_string());
+            src.persist(&exp_source).unwrap();
+            std::fs::write(exp_source.join(SRC_ENV_DIR).join("0.env"), "VAR=1").unwrap();
+            let src = ExperimentSource::parse(&exp_source).unwrap();
+
+            let cached = tmpdir.join("cached");
+            experiment(&src, Some(cached.clone()), MultiProgress::new(), false, &RunOptions::default()).unwrap();
+
+            // second series with one known and one new environment
+            std::fs::write(exp_source.join(SRC_ENV_DIR).join("1.env"), "VAR=2").unwrap();
+            let src = ExperimentSource::parse(&exp_source).unwrap();
+            let options = RunOptions {
+                skip_cached: Some(tmpdir.clone()),
+                ..Default::default()
+            };
+            let series_dir = tmpdir.join("new");
+            experiment(&src, Some(series_dir.clone()), MultiProgress::new(), false, &options).unwrap();
+
+            let value = |series: &PathBuf, run: &str| {
+                std::fs::read_to_string(series.join(SERIES_RUNS_DIR).join(run).join("out_value")).unwrap()
+            };
+            assert_eq!(value(&cached, "run_0_rep0"), value(&series_dir, "run_0_rep0"));
+            assert!(series_dir.join(SERIES_RUNS_DIR).join("run_1_rep0").join("out_value").is_file());
+
+            // only the new environment was executed
+            let stdout = read_log(series_dir.clone(), SERIES_STDOUT_LOG);
+            assert_eq!(stdout.matches("executed").count(), 1);
+        }
+
+        #[test]
+        fn provision_without_execution() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho foo > out_file\necho bar".to_string());
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+            src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("TestSource"), 2));
+
+            let series_dir = tmpdir.join("provisioned");
+            provision(&src, Some(series_dir.clone())).unwrap();
+
+            let runs_dir = series_dir.join(SERIES_RUNS_DIR);
+            let run_dirs: Vec<PathBuf> = std::fs::read_dir(&runs_dir)
+                .unwrap()
+                .map(|e| e.unwrap().path())
+                .filter(|p| p.is_dir())
+                .collect();
+            assert_eq!(run_dirs.len(), 2);
+            for run_dir in run_dirs {
+                assert!(run_dir.join(RUN_RUN_FILE).is_file());
+                assert!(run_dir.join(RUN_ENV_FILE).is_file());
+                assert!(!run_dir.join("out_file").exists());
+            }
+
+            for log in [SERIES_STDOUT_LOG, SERIES_STDERR_LOG] {
+                assert!(std::fs::read_to_string(runs_dir.join(log)).unwrap().is_empty());
+            }
+        }
+
+        #[test]
+        fn provision_run_standalone() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            // default template, which loads the helper functions
+            let mut src = ExperimentSource::new();
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+            src.set_exomat_envs(ExomatEnvironment::new(&tmpdir.join("TestSource"), 1));
+
+            let series_dir = tmpdir.join("provisioned");
+            provision(&src, Some(series_dir.clone())).unwrap();
+
+            // executed by hand, without the variables set by exomat
+            let run_dir = series_dir.join(SERIES_RUNS_DIR).join("run_0_rep0");
+            let output = std::process::Command::new(run_dir.join(RUN_RUN_FILE))
+                .current_dir(&run_dir)
+                .env_remove(ExomatEnvironment::LIB_ENV_VAR)
+                .env_remove(ExomatEnvironment::RUN_DIR_ENV_VAR)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            assert!(run_dir.join("out_nproc").is_file());
+        }
+
+        #[test]
+        fn trial_custom_dir() {
+            let tmpdir = TempDir::new().unwrap();
+            let tmpdir = tmpdir.path().to_path_buf();
+            std::env::set_current_dir(&tmpdir).unwrap();
+
+            let mut src = ExperimentSource::new();
+            src.set_run_script("#!/bin/bash\necho foo > out_file".to_string());
+            src.persist(&tmpdir.join("TestSource")).unwrap();
+
+            let trial_dir = tmpdir.join("trials");
+            let err = trial(&src, Some(&trial_dir), MultiProgress::new(), &RunOptions::default()).unwrap_err();
+            assert!(err.to_string().contains("is not a directory"));
+
+            std::fs::create_dir(&trial_dir).unwrap();
+            trial(&src, Some(&trial_dir), MultiProgress::new(), &RunOptions::default()).unwrap();
+
+            let trials: Vec<_> = std::fs::read_dir(&trial_dir).unwrap().map(|e| e.unwrap().path()).collect();
+            assert_eq!(trials.len(), 1);
+            assert!(trials[0].file_name().unwrap().to_str().unwrap().starts_with("exomat_trial-"));
+            let series = ExperimentSeries::parse(&trials[0]).unwrap();
+            assert_eq!(series.to_columns()["file"], vec!["foo"]);
         }
     }
 }
@@ -1,22 +1,370 @@
 //! harness skeleton subcommand
 
-use std::path::Path;
+use log::{debug, info, warn};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use crate::experiment::{ExperimentSource, FileWriter};
-use crate::helper::errors::Result;
+use crate::helper::archivist::{copy_harness_dir, SymlinkMode};
+use crate::helper::errors::{Error, Result};
+use crate::helper::fs_names::{find_run_file, SRC_README, SRC_RUN_FILE, SRC_TEMPLATE_DIR};
+
+/// Location of a custom template, relative to `$HOME`
+pub const CUSTOM_TEMPLATE_DIR: &str = ".config/exomat/template";
+
+/// Interpreter of the run script of a new experiment
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Runner {
+    #[default]
+    Sh,
+    Python,
+    Fish,
+}
+
+impl FromStr for Runner {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sh" => Ok(Runner::Sh),
+            "python" => Ok(Runner::Python),
+            "fish" => Ok(Runner::Fish),
+            _ => Err(Error::HarnessCreateError {
+                entry: s.to_string(),
+                reason: "unknown runner, use sh, python or fish".to_string(),
+            }),
+        }
+    }
+}
+
+impl Runner {
+    /// File name of the run script in SRC_TEMPLATE_DIR
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Runner::Sh => SRC_RUN_FILE,
+            Runner::Python => "run.py",
+            Runner::Fish => "run.fish",
+        }
+    }
+
+    /// Content of a new run script
+    pub fn template(&self) -> &'static str {
+        match self {
+            Runner::Sh => include_str!("run.sh.template"),
+            Runner::Python => include_str!("run.py.template"),
+            Runner::Fish => include_str!("run.fish.template"),
+        }
+    }
+}
 
 /// entrypoint for skeleton binary
-pub fn main(exp_src_dir: &Path) -> Result<()> {
+///
+/// The run script is created for `runner`, e.g. `template/run.py` for
+/// `Runner::Python`. If `$HOME/.config/exomat/template` exists, its content is
+/// copied into `template/` instead (see `apply_custom_template()`).
+///
+/// All `{{KEY}}` placeholders in the created template files (and README) are
+/// replaced by the `VALUE` given for `KEY` in `template_vars`.
+///
+/// With `force`, an existing experiment source is regenerated instead: the run
+/// script is replaced, all other existing files are kept (see
+/// `ExperimentSource::regenerate()`).
+pub fn main(
+    exp_src_dir: &Path,
+    template_vars: &[(String, String)],
+    force: bool,
+    runner: Runner,
+) -> Result<()> {
     let mut src = ExperimentSource::new();
-    src.persist(exp_src_dir)?;
+    src.set_run_file_name(runner.file_name());
+    src.set_run_script(runner.template().to_string());
+
+    // regenerating keeps all existing files, a custom template would overwrite them
+    let custom_template = custom_template_dir().filter(|_| !force);
+    if let Some(custom) = &custom_template {
+        if let Some(run_file) = find_run_file(custom) {
+            src.set_run_file_name(&run_file.file_name().unwrap().to_string_lossy());
+            src.set_run_script(std::fs::read_to_string(&run_file)?);
+        }
+    }
+
+    match force {
+        true => src.regenerate(exp_src_dir)?,
+        false => src.persist(exp_src_dir)?,
+    }
+    if let Some(custom) = &custom_template {
+        apply_custom_template(custom, &exp_src_dir.join(SRC_TEMPLATE_DIR))?;
+    }
+
+    substitute_source(exp_src_dir, template_vars)?;
 
     println!();
     println!("next steps:");
     println!("1. add variables with:");
     println!("   exomat env --add COUNT 1 2 3");
-    println!("2. adjust script in template/run.sh");
+    println!("2. adjust script in template/{}", src.run_file_name());
     println!("3. execute experiment with:");
     println!("   exomat run {}", exp_src_dir.display());
 
     Ok(())
 }
+
+/// Returns `$HOME/.config/exomat/template`, if it is a directory.
+pub fn custom_template_dir() -> Option<PathBuf> {
+    let dir = PathBuf::from(std::env::var_os("HOME")?).join(CUSTOM_TEMPLATE_DIR);
+    dir.is_dir().then_some(dir)
+}
+
+/// Copies everything inside of the custom template `custom` into `template_dir`.
+///
+/// The run script of the custom template replaces the built-in one and is
+/// made executable, even if the file in `custom` is not. Without a run script
+/// in `custom`, the built-in one is kept.
+fn apply_custom_template(custom: &Path, template_dir: &Path) -> Result<()> {
+    info!("Using custom template {}", custom.display());
+    copy_harness_dir(custom, template_dir, SymlinkMode::Follow)?;
+
+    if let Some(run_file) = find_run_file(template_dir) {
+        let mut permissions = std::fs::metadata(&run_file)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        std::fs::set_permissions(&run_file, permissions)?;
+    }
+
+    Ok(())
+}
+
+/// Parses a `KEY=VALUE` argument of `--template-var`.
+///
+/// ## Errors
+/// - Returns an error message if there is no `=` or `KEY` is empty
+pub fn parse_template_var(arg: &str) -> std::result::Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got \"{arg}\"")),
+    }
+}
+
+/// Replaces every `{{KEY}}` in `content` with its value from `vars`.
+///
+/// Returns the new content and the names of all placeholders that are left.
+pub fn substitute(content: &str, vars: &[(String, String)]) -> (String, Vec<String>) {
+    let mut content = content.to_string();
+    for (key, value) in vars {
+        content = content.replace(&format!("{{{{{key}}}}}"), value);
+    }
+
+    let mut unsubstituted = vec![];
+    let mut rest = content.as_str();
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+
+        let name = &rest[..end];
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            unsubstituted.push(name.to_string());
+            rest = &rest[end + 2..];
+        }
+    }
+
+    (content, unsubstituted)
+}
+
+/// Substitutes the placeholders in the template directory and README of the
+/// Experiment Source at `exp_src_dir`.
+fn substitute_source(exp_src_dir: &Path, vars: &[(String, String)]) -> Result<()> {
+    substitute_dir(&exp_src_dir.join(SRC_TEMPLATE_DIR), vars)?;
+
+    let readme = exp_src_dir.join(SRC_README);
+    match readme.is_file() {
+        true => substitute_file(&readme, vars),
+        false => Ok(()),
+    }
+}
+
+/// Substitutes the placeholders in all files inside of `dir`, see [substitute_file].
+fn substitute_dir(dir: &Path, vars: &[(String, String)]) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => substitute_dir(&path, vars)?,
+            false => substitute_file(&path, vars)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Substitutes the placeholders in `file` and warns about all placeholders that
+/// are left. Files that are not valid UTF-8 are not changed.
+fn substitute_file(file: &Path, vars: &[(String, String)]) -> Result<()> {
+    let Ok(content) = String::from_utf8(std::fs::read(file)?) else {
+        debug!(
+            "not substituting placeholders in binary file {}",
+            file.display()
+        );
+        return Ok(());
+    };
+
+    let (substituted, unsubstituted) = substitute(&content, vars);
+    for name in unsubstituted {
+        warn!(
+            "Placeholder {{{{{name}}}}} in {} was not substituted, set it with --template-var {name}=VALUE",
+            file.display()
+        );
+    }
+
+    if substituted != content {
+        std::fs::write(file, substituted)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::FileReader;
+    use faccess::PathExt;
+    use rstest::rstest;
+    use rusty_fork::rusty_fork_test;
+    use tempfile::TempDir;
+
+    fn vars(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("AUTHOR=Jane Doe", Ok(("AUTHOR", "Jane Doe")))]
+    #[case("URL=a=b", Ok(("URL", "a=b")))]
+    #[case("EMPTY=", Ok(("EMPTY", "")))]
+    #[case("=value", Err(()))]
+    #[case("AUTHOR", Err(()))]
+    fn parse_key_value(#[case] arg: &str, #[case] expected: std::result::Result<(&str, &str), ()>) {
+        let parsed = parse_template_var(arg).map_err(|_| ());
+        assert_eq!(
+            parsed,
+            expected.map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("sh", Runner::Sh, "run.sh", "#!/usr/bin/env bash")]
+    #[case("python", Runner::Python, "run.py", "#!/usr/bin/env python3")]
+    #[case("fish", Runner::Fish, "run.fish", "#!/usr/bin/env fish")]
+    fn parse_runner(
+        #[case] arg: &str,
+        #[case] runner: Runner,
+        #[case] file_name: &str,
+        #[case] shebang: &str,
+    ) {
+        assert_eq!(arg.parse::<Runner>().unwrap(), runner);
+        assert_eq!(runner.file_name(), file_name);
+        assert!(runner.template().starts_with(shebang));
+    }
+
+    #[test]
+    fn parse_unknown_runner() {
+        let err = "perl".parse::<Runner>().unwrap_err().to_string();
+        assert!(err.contains("unknown runner"), "{err}");
+    }
+
+    #[test]
+    fn skeleton_with_runner() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("exp");
+
+        main(&exp, &[], false, Runner::Python).unwrap();
+
+        let template_dir = exp.join(SRC_TEMPLATE_DIR);
+        assert!(!template_dir.join(SRC_RUN_FILE).exists());
+        assert!(template_dir.join("run.py").executable());
+        assert_eq!(
+            std::fs::read_to_string(template_dir.join("run.py")).unwrap(),
+            Runner::Python.template()
+        );
+    }
+
+    rusty_fork_test! {
+        #[test]
+        fn skeleton_with_custom_template() {
+            let tmpdir = TempDir::new().unwrap();
+            let home = tmpdir.path().join("home");
+            std::env::set_var("HOME", &home);
+
+            // without a config dir, the built-in template is used
+            main(&tmpdir.path().join("default"), &[], false, Runner::Sh).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(tmpdir.path().join("default").join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE)).unwrap(),
+                Runner::Sh.template()
+            );
+
+            let custom = home.join(CUSTOM_TEMPLATE_DIR);
+            std::fs::create_dir_all(custom.join("data")).unwrap();
+            // not executable in the config dir
+            std::fs::write(custom.join("run.py"), "#!/usr/bin/env python3\n# {{AUTHOR}}\n").unwrap();
+            std::fs::write(custom.join("data").join("input"), "1 2 3").unwrap();
+
+            let exp = tmpdir.path().join("exp");
+            main(&exp, &[("AUTHOR".to_string(), "Jane".to_string())], false, Runner::Sh).unwrap();
+
+            let template_dir = exp.join(SRC_TEMPLATE_DIR);
+            assert!(!template_dir.join(SRC_RUN_FILE).exists());
+            assert!(template_dir.join("run.py").executable());
+            assert_eq!(
+                std::fs::read_to_string(template_dir.join("run.py")).unwrap(),
+                "#!/usr/bin/env python3\n# Jane\n"
+            );
+            assert_eq!(std::fs::read_to_string(template_dir.join("data").join("input")).unwrap(), "1 2 3");
+            assert_eq!(ExperimentSource::parse(&exp).unwrap().run_file_name(), "run.py");
+        }
+    }
+
+    #[test]
+    fn substitute_placeholders() {
+        let (content, left) = substitute(
+            "# by {{AUTHOR}} ({{AUTHOR}})\n# {{DESCRIPTION}}\n${{x}} {{}}",
+            &vars(&[("AUTHOR", "Jane")]),
+        );
+        assert_eq!(content, "# by Jane (Jane)\n# {{DESCRIPTION}}\n${{x}} {{}}");
+        assert_eq!(left, vec!["DESCRIPTION", "x"]);
+    }
+
+    #[test]
+    fn substitute_template_files() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("exp");
+
+        let mut src = ExperimentSource::new();
+        src.set_run_script("#!/bin/sh\n# {{DESCRIPTION}}\n".to_string());
+        src.persist(&exp).unwrap();
+
+        let notes = exp.join(SRC_TEMPLATE_DIR).join("data").join("notes");
+        std::fs::create_dir(notes.parent().unwrap()).unwrap();
+        std::fs::write(&notes, "{{AUTHOR}}, {{DATE}}").unwrap();
+        std::fs::write(exp.join(SRC_README), "written by {{AUTHOR}}").unwrap();
+
+        substitute_source(
+            &exp,
+            &vars(&[("AUTHOR", "Jane"), ("DESCRIPTION", "measures things")]),
+        )
+        .unwrap();
+
+        let run_file = exp.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE);
+        assert_eq!(
+            std::fs::read_to_string(&run_file).unwrap(),
+            "#!/bin/sh\n# measures things\n"
+        );
+        assert_eq!(std::fs::read_to_string(&notes).unwrap(), "Jane, {{DATE}}");
+        assert_eq!(
+            std::fs::read_to_string(exp.join(SRC_README)).unwrap(),
+            "written by Jane"
+        );
+        // rewriting keeps run.sh executable
+        assert!(run_file.executable());
+    }
+}
@@ -0,0 +1,180 @@
+//! harness status subcommand
+
+use itertools::{Itertools, MinMaxResult};
+use std::path::{Path, PathBuf};
+
+use crate::experiment::{ExperimentSeries, FileReader};
+use crate::harness::env::Environment;
+use crate::harness::table::wide::environment;
+use crate::helper::archivist::{find_marker, find_marker_pwd};
+use crate::helper::errors::Result;
+use crate::helper::fs_names::*;
+
+/// Overview of an Experiment Series directory
+#[derive(Debug, Clone, PartialEq)]
+struct SeriesStatus {
+    name: String,
+    runs: usize,
+    succeeded: usize,
+    failed: usize,
+    /// runs without an exit code
+    not_executed: usize,
+    environments: usize,
+    /// fewest and most runs of any environment
+    repetitions: (usize, usize),
+    /// table created by `make-table`, if there is one
+    table: Option<PathBuf>,
+}
+
+impl SeriesStatus {
+    /// Collects the status of the Experiment Series at `series_dir`.
+    ///
+    /// A run succeeded if its exit code is 0, and failed if it has any other exit
+    /// code. Warmup runs are not counted.
+    ///
+    /// ## Errors
+    /// - Returns the errors of `ExperimentSeries::parse()`
+    fn parse(series_dir: &Path) -> Result<Self> {
+        let series = ExperimentSeries::parse(series_dir)?;
+        let name = file_name_string(series_dir);
+
+        // runs without an exit code have an empty or NA value after balancing
+        let exit_codes: Vec<Option<i32>> = series
+            .runs()
+            .iter()
+            .map(|run| {
+                run.out_var("exit_code")
+                    .and_then(|values| values.first())
+                    .and_then(|code| code.trim().parse().ok())
+            })
+            .collect();
+        let not_executed = exit_codes.iter().filter(|code| code.is_none()).count();
+        let succeeded = exit_codes.iter().filter(|code| **code == Some(0)).count();
+
+        // runs of each environment
+        let mut groups: Vec<(Environment, usize)> = vec![];
+        for env in series.runs().iter().map(environment) {
+            match groups.iter_mut().find(|(other, _)| *other == env) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((env, 1)),
+            }
+        }
+        let repetitions = match groups.iter().map(|(_, count)| *count).minmax() {
+            MinMaxResult::NoElements => (0, 0),
+            MinMaxResult::OneElement(count) => (count, count),
+            MinMaxResult::MinMax(min, max) => (min, max),
+        };
+
+        let table = series_dir.join(format!("{name}.csv"));
+
+        Ok(SeriesStatus {
+            runs: series.runs().len(),
+            succeeded,
+            failed: exit_codes.len() - succeeded - not_executed,
+            not_executed,
+            environments: groups.len(),
+            repetitions,
+            table: table.is_file().then_some(table),
+            name,
+        })
+    }
+}
+
+impl std::fmt::Display for SeriesStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let repetitions = match self.repetitions {
+            (min, max) if min == max => format!("{min} per environment"),
+            (min, max) => format!("{min} to {max} per environment"),
+        };
+        let table = match &self.table {
+            Some(table) => file_name_string(table),
+            None => "not generated yet, create it with `exomat make-table`".to_string(),
+        };
+
+        writeln!(f, "series:       {}", self.name)?;
+        writeln!(
+            f,
+            "runs:         {} ({} succeeded, {} failed, {} not executed)",
+            self.runs, self.succeeded, self.failed, self.not_executed
+        )?;
+        writeln!(f, "environments: {}", self.environments)?;
+        writeln!(f, "repetitions:  {repetitions}")?;
+        write!(f, "table:        {table}")
+    }
+}
+
+/// entrypoint for status binary
+///
+/// Prints an overview of the Experiment Series at (or containing) `series_dir`,
+/// or the one containing the current directory. Nothing is changed.
+///
+/// ## Errors
+/// - Returns a `FindMarkerError` if no Experiment Series is found
+/// - Returns the errors of `ExperimentSeries::parse()`
+pub fn main(series_dir: Option<&Path>) -> Result<()> {
+    let series_dir = match series_dir {
+        Some(dir) => find_marker(dir, MARKER_SERIES)?,
+        None => find_marker_pwd(MARKER_SERIES)?,
+    };
+
+    println!("{}", SeriesStatus::parse(&series_dir)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::test_helper::create_run;
+    use tempfile::TempDir;
+
+    #[test]
+    fn status_of_series() {
+        let tmpdir = TempDir::new().unwrap();
+        let series_dir = tmpdir.path().join("Foo-2025-01-01-00-00-00");
+
+        for (size, rep, exit_code) in [
+            ("S", 0, Some("0")),
+            ("S", 1, Some("1")),
+            ("L", 0, Some("0")),
+            ("L", 1, Some("0")),
+            ("L", 2, None),
+        ] {
+            let outs: Vec<_> = exit_code
+                .map(|code| ("out_exit_code", code))
+                .into_iter()
+                .collect();
+            create_run(
+                &series_dir,
+                &format!("run_{size}_rep{rep}"),
+                &format!("SIZE={size}\nREPETITION={rep}"),
+                &outs,
+            );
+        }
+
+        let status = SeriesStatus::parse(&series_dir).unwrap();
+        assert_eq!(
+            status,
+            SeriesStatus {
+                name: "Foo-2025-01-01-00-00-00".to_string(),
+                runs: 5,
+                succeeded: 3,
+                failed: 1,
+                not_executed: 1,
+                environments: 2,
+                repetitions: (2, 3),
+                table: None,
+            }
+        );
+        assert!(status
+            .to_string()
+            .contains("runs:         5 (3 succeeded, 1 failed, 1 not executed)\n"));
+
+        let table = series_dir.join("Foo-2025-01-01-00-00-00.csv");
+        std::fs::write(&table, "SIZE\n").unwrap();
+        let status = SeriesStatus::parse(&series_dir).unwrap();
+        assert_eq!(status.table, Some(table));
+        assert!(status
+            .to_string()
+            .ends_with("table:        Foo-2025-01-01-00-00-00.csv"));
+    }
+}
@@ -1,12 +1,23 @@
 //! harness make-table command
 
 use log::info;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use crate::helper::errors::Result;
+pub mod aggregate;
+pub mod diff;
+pub mod gate;
+pub mod merge;
+pub mod pivot;
+pub mod stats;
+pub mod wide;
+
+use crate::helper::errors::{Error, Result};
 use crate::helper::fs_names::*;
+use crate::helper::warnings;
 
-use crate::experiment::{CsvWriter, ExperimentSeries, FileReader};
+use crate::experiment::experiment_series::create_csv_file;
+use crate::experiment::{ExperimentSeries, TableOptions};
 
 /// Entrypoint for table binary
 ///
@@ -31,15 +42,34 @@ use crate::experiment::{CsvWriter, ExperimentSeries, FileReader};
 /// 42, true
 /// 300,false
 /// ```
-pub fn main() -> Result<()> {
+///
+/// If a `baseline` table is given, every rule in `gates` is checked against it
+/// after the table has been written (see `gate::GateRule`). The verdict is written
+/// to `series_dir/[series_dir]_gate.csv`.
+///
+/// Outputs missing in some runs are filled according to `options`. With
+/// `options.streaming`, only one run is kept in memory at a time (see
+/// `ExperimentSeries::stream_csv`), no other tables can be written then.
+///
+/// If `pivot_keys` (row, column, value) are given, a pivot table aggregated by `agg`
+/// is written to `series_dir/[series_dir]_pivot.csv` as well (see `pivot::pivot`).
+///
+/// If `wide_per_rep` is set, a table with one row per environment is written to
+/// `series_dir/[series_dir]_wide.csv` as well (see `wide::wide_per_rep`).
+///
+/// If `statistics` are given, a table with these statistics per environment is
+/// written to `series_dir/[series_dir]_aggregate.csv` as well (see `aggregate::aggregate`).
+pub fn main(
+    baseline: Option<PathBuf>,
+    gates: Vec<String>,
+    pivot_keys: Vec<String>,
+    agg: String,
+    wide_per_rep: bool,
+    statistics: Vec<aggregate::Statistic>,
+    options: TableOptions,
+) -> Result<()> {
     let series_dir = crate::find_marker_pwd(MARKER_SERIES)?;
-
-    // collect all output from every run in series_dir
-    let reader = ExperimentSeries::parse(&series_dir)?;
-
-    let keys = reader.keys();
-    info!("Collected output for {} keys", keys.len());
-    info!("Found keys: {:?}", keys);
+    warnings::take();
 
     // output file will be "series_dir/[series_dir].csv"
     let mut out_file = PathBuf::from(
@@ -49,6 +79,132 @@ pub fn main() -> Result<()> {
     );
     out_file.set_extension("csv");
 
+    if options.streaming {
+        let keys = ExperimentSeries::stream_csv(
+            &series_dir,
+            table_writer(&series_dir.join(&out_file), &options)?,
+            &options,
+        )?;
+        info!("Collected output for {} keys", keys.len());
+        info!("Found keys: {:?}", keys);
+
+        info!("{}", warnings::summary(&warnings::take(), None));
+        return Ok(());
+    }
+
+    // collect all output from every run in series_dir
+    let reader = ExperimentSeries::parse_with(&series_dir, &options)?;
+
+    let keys = reader.keys();
+    info!("Collected output for {} keys", keys.len());
+    info!("Found keys: {:?}", keys);
+
     // serialize data and write to file
-    reader.to_csv(&series_dir.join(out_file))
+    reader.to_csv_writer(
+        table_writer(&series_dir.join(&out_file), &options)?,
+        &options,
+    )?;
+
+    if let [row, col, value] = pivot_keys.as_slice() {
+        let table = pivot::pivot(&reader, row, col, value, agg.parse()?, &options.na_value)?;
+
+        let mut pivot_file = out_file.file_stem().unwrap().to_os_string();
+        pivot_file.push("_pivot.csv");
+        pivot::write_csv(&table, &series_dir.join(&pivot_file), options.delimiter)?;
+        info!("Wrote pivot table to {}", pivot_file.display());
+    }
+
+    if wide_per_rep {
+        let table = wide::wide_per_rep(&reader, &options.na_value);
+
+        let mut wide_file = out_file.file_stem().unwrap().to_os_string();
+        wide_file.push("_wide.csv");
+        pivot::write_csv(&table, &series_dir.join(&wide_file), options.delimiter)?;
+        info!("Wrote wide table to {}", wide_file.display());
+    }
+
+    if !statistics.is_empty() {
+        let table = aggregate::aggregate(&reader, &statistics, &options.na_value);
+
+        let mut aggregate_file = out_file.file_stem().unwrap().to_os_string();
+        aggregate_file.push("_aggregate.csv");
+        pivot::write_csv(&table, &series_dir.join(&aggregate_file), options.delimiter)?;
+        info!("Wrote aggregated table to {}", aggregate_file.display());
+    }
+
+    if let Some(baseline) = baseline {
+        let rules = gates
+            .iter()
+            .map(|rule| gate::GateRule::parse(rule))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut verdict_file = out_file.file_stem().unwrap().to_os_string();
+        verdict_file.push("_gate.csv");
+
+        gate::run_gate(
+            &rules,
+            &gate::read_columns(&baseline)?,
+            &reader.to_columns(),
+            &series_dir.join(verdict_file),
+        )?;
+    }
+
+    info!("{}", warnings::summary(&warnings::take(), None));
+    Ok(())
+}
+
+/// Entrypoint for `make-table --merge`
+///
+/// Collects the outputs of every Experiment Series in `series_dirs` like [main]
+/// and writes them into one table `merged.csv` in the current directory, with
+/// the rows of each series in the given order (see `merge::merge`).
+///
+/// ## Errors
+/// - Returns a `FindMarkerError` if a directory is not an Experiment Series
+/// - Returns a `CsvError` if the table could not be written
+pub fn merge_series(series_dirs: Vec<PathBuf>, options: TableOptions) -> Result<()> {
+    warnings::take();
+
+    let mut tables = vec![];
+    for series_dir in &series_dirs {
+        if !series_dir.join(MARKER_SERIES).is_file() {
+            return Err(Error::FindMarkerError(format!(
+                "{} is not an experiment series directory",
+                series_dir.display()
+            )));
+        }
+
+        let reader = ExperimentSeries::parse_with(series_dir, &options)?;
+        info!(
+            "Collected output of {} runs from {}",
+            reader.runs().len(),
+            series_dir.display()
+        );
+        tables.push(reader.to_rows(options.with_env));
+    }
+
+    let table = merge::merge(&tables, &options.na_value);
+    pivot::write_csv_to(
+        &table,
+        table_writer(Path::new(MERGED_TABLE), &options)?,
+        options.delimiter,
+    )?;
+    if !options.to_stdout {
+        info!("Wrote merged table to {MERGED_TABLE}");
+    }
+
+    info!("{}", warnings::summary(&warnings::take(), None));
+    Ok(())
+}
+
+/// Returns the destination of the main table: stdout if `options.to_stdout` is
+/// set, `csv_file` otherwise.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `csv_file` could not be created
+fn table_writer(csv_file: &Path, options: &TableOptions) -> Result<Box<dyn Write>> {
+    match options.to_stdout {
+        true => Ok(Box::new(std::io::stdout().lock())),
+        false => Ok(Box::new(create_csv_file(csv_file)?)),
+    }
 }
@@ -0,0 +1,252 @@
+//! Aggregated tables, with one row per environment and statistics over its repetitions
+
+use itertools::Itertools;
+use log::warn;
+use std::str::FromStr;
+
+use super::stats::{self, numeric_values};
+use super::wide::environment;
+use crate::experiment::{ExperimentRun, ExperimentSeries};
+use crate::harness::env::Environment;
+use crate::helper::errors::{Error, Result};
+
+/// Statistic computed over all values of an output within one environment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Mean,
+    /// sample standard deviation
+    Std,
+    Min,
+    Max,
+}
+
+impl FromStr for Statistic {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mean" => Ok(Statistic::Mean),
+            "std" => Ok(Statistic::Std),
+            "min" => Ok(Statistic::Min),
+            "max" => Ok(Statistic::Max),
+            _ => Err(Error::CsvError {
+                reason: format!("unknown statistic \"{s}\", use mean, std, min or max"),
+            }),
+        }
+    }
+}
+
+impl Statistic {
+    /// Suffix of the column containing this statistic, e.g. `runtime_mean`
+    fn suffix(&self) -> &'static str {
+        match self {
+            Statistic::Mean => "mean",
+            Statistic::Std => "std",
+            Statistic::Min => "min",
+            Statistic::Max => "max",
+        }
+    }
+
+    /// Computes this statistic over `values`, `None` if there are not enough values.
+    fn apply(&self, values: &[f64]) -> Option<f64> {
+        match self {
+            Statistic::Mean => stats::mean(values),
+            Statistic::Std => stats::std_dev(values),
+            Statistic::Min => stats::min(values),
+            Statistic::Max => stats::max(values),
+        }
+    }
+}
+
+/// How an output ends up in the aggregated table
+enum Column {
+    /// numeric, one column per statistic
+    Aggregated,
+    /// same value in all repetitions of an environment, one value per environment
+    Passed(Vec<String>),
+    /// differs between repetitions
+    Dropped,
+}
+
+/// Returns `true` if `value` is neither empty nor `na_value`.
+fn is_recorded(value: &str, na_value: &str) -> bool {
+    !value.is_empty() && value != na_value
+}
+
+/// Reshapes `series` into a table with one row per environment (ignoring the
+/// repetition).
+///
+/// Every row starts with the variables of its environment. Outputs where every
+/// recorded value is a number get one column `[key]_[statistic]` per entry of
+/// `statistics`, computed over all values of all repetitions. Other outputs are
+/// kept as column `[key]` if they have the same value in every repetition of an
+/// environment, and are dropped with a warning otherwise.
+///
+/// Cells without any values are filled with `na_value`.
+///
+/// Returns all rows including the header row.
+pub fn aggregate(
+    series: &ExperimentSeries,
+    statistics: &[Statistic],
+    na_value: &str,
+) -> Vec<Vec<String>> {
+    let runs: Vec<&ExperimentRun> = series
+        .runs()
+        .iter()
+        .sorted_by_key(|run| run.run_dir_name().to_string())
+        .collect();
+
+    // runs of each environment, in the order of the first run
+    let mut groups: Vec<(Environment, Vec<&ExperimentRun>)> = vec![];
+    for run in runs {
+        let env = environment(run);
+        match groups.iter_mut().find(|(other, _)| *other == env) {
+            Some((_, group)) => group.push(run),
+            None => groups.push((env, vec![run])),
+        }
+    }
+
+    let variables: Vec<String> = groups
+        .iter()
+        .flat_map(|(env, _)| env.get_env_vars())
+        .cloned()
+        .sorted()
+        .dedup()
+        .collect();
+
+    // all values of every key per environment
+    let keys = series.keys();
+    let values: Vec<Vec<Vec<String>>> = groups
+        .iter()
+        .map(|(_, group)| {
+            keys.iter()
+                .map(|key| {
+                    group
+                        .iter()
+                        .filter_map(|run| run.out_var(key))
+                        .flatten()
+                        .cloned()
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut header: Vec<String> = variables.clone();
+    let mut columns: Vec<Column> = vec![];
+    for (k, key) in keys.iter().enumerate() {
+        let numeric = values.iter().all(|group| {
+            group[k]
+                .iter()
+                .filter(|val| is_recorded(val, na_value))
+                .all(|val| val.trim().parse::<f64>().is_ok())
+        });
+        if numeric {
+            header.extend(statistics.iter().map(|s| format!("{key}_{}", s.suffix())));
+            columns.push(Column::Aggregated);
+            continue;
+        }
+
+        let passed: Option<Vec<String>> = values
+            .iter()
+            .map(|group| match group[k].iter().all_equal_value() {
+                Ok(value) => Some(value.clone()),
+                Err(None) => Some(na_value.to_string()),
+                Err(Some(_)) => None,
+            })
+            .collect();
+        match passed {
+            Some(passed) => {
+                header.push(key.to_string());
+                columns.push(Column::Passed(passed));
+            }
+            None => {
+                warn!("Dropping {key} from the aggregated table, its values differ between repetitions");
+                columns.push(Column::Dropped);
+            }
+        }
+    }
+
+    let mut table = vec![header];
+    for (g, (env, _)) in groups.iter().enumerate() {
+        let mut row: Vec<String> = variables
+            .iter()
+            .map(|var| {
+                env.get_env_val(var)
+                    .cloned()
+                    .unwrap_or_else(|| na_value.to_string())
+            })
+            .collect();
+
+        for (k, column) in columns.iter().enumerate() {
+            match column {
+                Column::Aggregated => {
+                    let numbers = numeric_values(&values[g][k]);
+                    row.extend(statistics.iter().map(|s| {
+                        s.apply(&numbers)
+                            .map(|r| r.to_string())
+                            .unwrap_or_else(|| na_value.to_string())
+                    }));
+                }
+                Column::Passed(passed) => row.push(passed[g].clone()),
+                Column::Dropped => (),
+            }
+        }
+
+        table.push(row);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::FileReader;
+    use crate::helper::test_helper::create_run;
+    use tempfile::TempDir;
+
+    #[test]
+    fn aggregate_repetitions() {
+        let tmpdir = TempDir::new().unwrap();
+
+        for (size, rep, runtime, host, note) in [
+            ("S", 0, "1", "a", "x"),
+            ("S", 1, "3", "a", "y"),
+            ("S", 2, "NA", "a", "x"),
+            ("L", 0, "10", "b", "z"),
+            ("L", 1, "14", "b", "z"),
+        ] {
+            create_run(
+                tmpdir.path(),
+                &format!("run_{size}_rep{rep}"),
+                &format!("SIZE={size}\nREPETITION={rep}"),
+                &[
+                    ("out_runtime", runtime),
+                    ("out_host", host),
+                    ("out_note", note),
+                ],
+            );
+        }
+
+        let series = ExperimentSeries::parse(tmpdir.path()).unwrap();
+        let statistics = ["mean", "std", "min", "max"].map(|s| s.parse().unwrap());
+
+        // note differs within S and is dropped
+        assert_eq!(
+            aggregate(&series, &statistics, "NA"),
+            vec![
+                vec![
+                    "SIZE",
+                    "host",
+                    "runtime_mean",
+                    "runtime_std",
+                    "runtime_min",
+                    "runtime_max"
+                ],
+                vec!["L", "b", "12", "2.8284271247461903", "10", "14"],
+                vec!["S", "a", "2", "1.4142135623730951", "1", "3"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Comparison of the outputs of two Experiment Series
+
+use std::collections::BTreeSet;
+
+use crate::experiment::{ExperimentRun, ExperimentSeries};
+
+/// Parts of output names that hint at values depending on time, that can
+/// not be reproduced
+const TIMING_HINTS: [&str; 6] = ["time", "duration", "elapsed", "date", "clock", "latency"];
+
+/// A difference between two Experiment Series
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Run only exists in one of the series
+    MissingRun { run: String },
+    /// Output key only exists in one of the series
+    MissingKey { key: String },
+    /// Run produced different values for an output key
+    Values {
+        run: String,
+        key: String,
+        left: Vec<String>,
+        right: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::MissingRun { run } => write!(f, "{run} only exists in one series"),
+            Difference::MissingKey { key } => write!(f, "{key} only exists in one series"),
+            Difference::Values {
+                run,
+                key,
+                left,
+                right,
+            } => write!(f, "{run}: {key} differs ({left:?} vs. {right:?})"),
+        }
+    }
+}
+
+/// Returns `true` if `key` most likely contains timing information, which differs
+/// across executions by nature (e.g. `runtime`, `duration_ms`, `start_date`).
+pub fn is_timing_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    TIMING_HINTS.iter().any(|hint| key.contains(hint))
+}
+
+/// Compares the outputs of all runs in `left` and `right`.
+///
+/// Runs are matched by their name, keys for which `ignore` returns `true` are
+/// not compared. Returns all differences found, sorted by run and key.
+pub fn diff_series(
+    left: &ExperimentSeries,
+    right: &ExperimentSeries,
+    ignore: impl Fn(&str) -> bool,
+) -> Vec<Difference> {
+    let mut differences = vec![];
+
+    let left_keys: BTreeSet<&str> = left.keys().into_iter().filter(|k| !ignore(k)).collect();
+    let right_keys: BTreeSet<&str> = right.keys().into_iter().filter(|k| !ignore(k)).collect();
+    for key in left_keys.symmetric_difference(&right_keys) {
+        differences.push(Difference::MissingKey {
+            key: key.to_string(),
+        });
+    }
+
+    let find_run = |series: &'_ ExperimentSeries, name: &str| -> Option<ExperimentRun> {
+        series
+            .runs()
+            .iter()
+            .find(|run| run.run_dir_name() == name)
+            .cloned()
+    };
+
+    let run_names: BTreeSet<&str> = left
+        .runs()
+        .iter()
+        .chain(right.runs())
+        .map(|run| run.run_dir_name())
+        .collect();
+
+    for name in run_names {
+        let (Some(left_run), Some(right_run)) = (find_run(left, name), find_run(right, name))
+        else {
+            differences.push(Difference::MissingRun {
+                run: name.to_string(),
+            });
+            continue;
+        };
+
+        for key in left_keys.intersection(&right_keys) {
+            let left_vals = left_run.out_var(key).cloned().unwrap_or_default();
+            let right_vals = right_run.out_var(key).cloned().unwrap_or_default();
+
+            if left_vals != right_vals {
+                differences.push(Difference::Values {
+                    run: name.to_string(),
+                    key: key.to_string(),
+                    left: left_vals,
+                    right: right_vals,
+                });
+            }
+        }
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::FileReader;
+    use crate::helper::fs_names::RUN_RUN_FILE;
+    use crate::helper::test_helper::create_out_file;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    #[rstest]
+    #[case("runtime", true)]
+    #[case("Duration_ms", true)]
+    #[case("start_date", true)]
+    #[case("result", false)]
+    #[case("count", false)]
+    fn detect_timing_keys(#[case] key: &str, #[case] timing: bool) {
+        assert_eq!(is_timing_key(key), timing);
+    }
+
+    #[test]
+    fn diff_two_series() {
+        let left_dir = TempDir::new().unwrap();
+        let right_dir = TempDir::new().unwrap();
+
+        for (dir, result, runtime) in [(&left_dir, "42", "1.3"), (&right_dir, "43", "1.5")] {
+            create_out_file(dir.path(), Some("run_0_rep0"), "out_same", "a\nb");
+            create_out_file(dir.path(), Some("run_0_rep0"), "out_result", result);
+            create_out_file(dir.path(), Some("run_0_rep0"), "out_runtime", runtime);
+        }
+        create_out_file(left_dir.path(), Some("run_1_rep0"), "out_same", "c");
+        for run in ["run_0_rep0", "run_1_rep0"] {
+            create_out_file(left_dir.path(), Some(run), RUN_RUN_FILE, "");
+        }
+        create_out_file(right_dir.path(), Some("run_0_rep0"), RUN_RUN_FILE, "");
+
+        let left = ExperimentSeries::parse(left_dir.path()).unwrap();
+        let right = ExperimentSeries::parse(right_dir.path()).unwrap();
+
+        assert!(diff_series(&left, &left, |_| false).is_empty());
+
+        let differences = diff_series(&left, &right, is_timing_key);
+        assert_eq!(
+            differences,
+            vec![
+                Difference::Values {
+                    run: "run_0_rep0".to_string(),
+                    key: "result".to_string(),
+                    // single values are repeated to match `out_same`
+                    left: vec!["42".to_string(); 2],
+                    right: vec!["43".to_string(); 2],
+                },
+                Difference::MissingRun {
+                    run: "run_1_rep0".to_string()
+                },
+            ]
+        );
+
+        // timing is compared if not ignored
+        assert_eq!(diff_series(&left, &right, |_| false).len(), 3);
+    }
+}
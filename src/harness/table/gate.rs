@@ -0,0 +1,378 @@
+//! Regression gate, comparing the results of an Experiment Series against a baseline
+
+use csv::{Reader, Writer};
+use log::{error, info};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::stats::{mean, numeric_values};
+use crate::helper::errors::{Error, Result};
+
+/// Maps column names to all values in that column
+pub type Columns = HashMap<String, Vec<String>>;
+
+/// Which change of a metric counts as a regression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Direction {
+    /// The metric must not grow (e.g. runtime)
+    Increase,
+    /// The metric must not shrink (e.g. throughput)
+    Decrease,
+}
+
+/// How far a metric may change before it counts as a regression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Limit {
+    /// relative to the baseline, in percent
+    Percent(f64),
+    /// in the unit of the metric
+    Absolute(f64),
+}
+
+/// One rule of the regression gate, parsed from `KEY:[+-]LIMIT[%]`
+///
+/// - `latency:+10%`: fails if the mean of `latency` grew by more than 10%
+/// - `throughput:-5%`: fails if the mean of `throughput` shrunk by more than 5%
+/// - `latency:0.5`: fails if the mean of `latency` grew by more than 0.5
+#[derive(Debug, Clone, PartialEq)]
+pub struct GateRule {
+    pub key: String,
+    pub direction: Direction,
+    pub limit: Limit,
+}
+
+/// Result of checking one GateRule
+#[derive(Debug, Clone, PartialEq)]
+pub struct GateVerdict {
+    pub rule: GateRule,
+    pub baseline: f64,
+    pub current: f64,
+    pub bound: f64,
+    pub passed: bool,
+}
+
+impl GateRule {
+    /// Parses a gate rule from `KEY:[+-]LIMIT[%]`.
+    ///
+    /// The direction defaults to `+` (increases are regressions).
+    ///
+    /// ## Errors
+    /// - Returns a `GateError` if `rule` does not follow the format above
+    pub fn parse(rule: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::GateError {
+            reason: format!("invalid rule \"{rule}\": {reason}"),
+        };
+
+        let (key, limit) = rule
+            .rsplit_once(':')
+            .ok_or_else(|| invalid("expected KEY:LIMIT"))?;
+        if key.is_empty() {
+            return Err(invalid("key is empty"));
+        }
+
+        let (direction, limit) = match limit.strip_prefix('-') {
+            Some(rest) => (Direction::Decrease, rest),
+            None => (
+                Direction::Increase,
+                limit.strip_prefix('+').unwrap_or(limit),
+            ),
+        };
+
+        let (is_percent, limit) = match limit.strip_suffix('%') {
+            Some(rest) => (true, rest),
+            None => (false, limit),
+        };
+
+        let value = limit
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .ok_or_else(|| invalid("limit is not a positive number"))?;
+
+        Ok(Self {
+            key: key.to_string(),
+            direction,
+            limit: match is_percent {
+                true => Limit::Percent(value),
+                false => Limit::Absolute(value),
+            },
+        })
+    }
+
+    /// Returns the worst value the metric may reach, given its `baseline`.
+    pub fn bound(&self, baseline: f64) -> f64 {
+        let delta = match self.limit {
+            Limit::Percent(p) => baseline.abs() * p / 100.0,
+            Limit::Absolute(a) => a,
+        };
+
+        match self.direction {
+            Direction::Increase => baseline + delta,
+            Direction::Decrease => baseline - delta,
+        }
+    }
+
+    /// Compares this rule against the mean of `baseline` and `current`.
+    ///
+    /// ## Errors
+    /// - Returns a `GateError` if the key is missing in either table
+    /// - Returns a `GateError` if the key has no numeric values in either table
+    pub fn check(&self, baseline: &Columns, current: &Columns) -> Result<GateVerdict> {
+        let baseline = self.aggregate(baseline, "baseline")?;
+        let current = self.aggregate(current, "current series")?;
+        let bound = self.bound(baseline);
+
+        let passed = match self.direction {
+            Direction::Increase => current <= bound,
+            Direction::Decrease => current >= bound,
+        };
+
+        Ok(GateVerdict {
+            rule: self.clone(),
+            baseline,
+            current,
+            bound,
+            passed,
+        })
+    }
+
+    /// Mean of all numeric values of this rule's key in `table`.
+    ///
+    /// The key may be given with or without the `out_` prefix.
+    fn aggregate(&self, table: &Columns, table_name: &str) -> Result<f64> {
+        let values = table
+            .get(&self.key)
+            .or_else(|| table.get(self.key.strip_prefix("out_")?))
+            .ok_or_else(|| Error::GateError {
+                reason: format!("{} not found in {table_name}", self.key),
+            })?;
+
+        mean(&numeric_values(values)).ok_or_else(|| Error::GateError {
+            reason: format!("{} has no numeric values in {table_name}", self.key),
+        })
+    }
+}
+
+impl std::fmt::Display for GateRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.direction {
+            Direction::Increase => '+',
+            Direction::Decrease => '-',
+        };
+
+        match self.limit {
+            Limit::Percent(p) => write!(f, "{}:{sign}{p}%", self.key),
+            Limit::Absolute(a) => write!(f, "{}:{sign}{a}", self.key),
+        }
+    }
+}
+
+impl GateVerdict {
+    /// Relative change from baseline to current, in percent
+    pub fn change_percent(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline.abs() * 100.0
+    }
+}
+
+/// Reads a CSV file (as written by `make-table`) into its columns.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `file` could not be read or is not a valid CSV file
+pub fn read_columns(file: &Path) -> Result<Columns> {
+    let to_csv_error = |e: csv::Error| Error::CsvError {
+        reason: format!("{}: {e}", file.display()),
+    };
+
+    let mut rdr = Reader::from_path(file).map_err(to_csv_error)?;
+    let header: Vec<String> = rdr
+        .headers()
+        .map_err(to_csv_error)?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+
+    let mut columns: Columns = header.iter().map(|h| (h.clone(), Vec::new())).collect();
+    for record in rdr.records() {
+        let record = record.map_err(to_csv_error)?;
+        for (key, value) in header.iter().zip(record.iter()) {
+            columns.get_mut(key).unwrap().push(value.to_string());
+        }
+    }
+
+    Ok(columns)
+}
+
+/// Checks every rule in `rules` and logs the result.
+///
+/// Writes one line per rule to `verdict_file` (CSV) and returns an Error
+/// if any rule failed.
+///
+/// ## Errors
+/// - Returns a `GateError` if any rule could not be checked or did not pass
+/// - Returns a `CsvError` if `verdict_file` could not be written
+pub fn run_gate(
+    rules: &[GateRule],
+    baseline: &Columns,
+    current: &Columns,
+    verdict_file: &Path,
+) -> Result<()> {
+    let verdicts = rules
+        .iter()
+        .map(|rule| rule.check(baseline, current))
+        .collect::<Result<Vec<_>>>()?;
+
+    let to_csv_error = |e: csv::Error| Error::CsvError {
+        reason: e.to_string(),
+    };
+    let mut wtr = Writer::from_path(verdict_file).map_err(to_csv_error)?;
+    wtr.write_record([
+        "rule",
+        "baseline",
+        "current",
+        "change_percent",
+        "bound",
+        "verdict",
+    ])
+    .map_err(to_csv_error)?;
+
+    for verdict in &verdicts {
+        let result = match verdict.passed {
+            true => "pass",
+            false => "fail",
+        };
+
+        let report = format!(
+            "{}: baseline {}, current {} ({:+.2}%), bound {} -> {result}",
+            verdict.rule,
+            verdict.baseline,
+            verdict.current,
+            verdict.change_percent(),
+            verdict.bound,
+        );
+        match verdict.passed {
+            true => info!("{report}"),
+            false => error!("{report}"),
+        }
+
+        wtr.write_record([
+            verdict.rule.to_string(),
+            verdict.baseline.to_string(),
+            verdict.current.to_string(),
+            format!("{:.2}", verdict.change_percent()),
+            verdict.bound.to_string(),
+            result.to_string(),
+        ])
+        .map_err(to_csv_error)?;
+    }
+    wtr.flush()?;
+    info!("Wrote gate verdict to {}", verdict_file.display());
+
+    let failed: Vec<String> = verdicts
+        .iter()
+        .filter(|v| !v.passed)
+        .map(|v| v.rule.to_string())
+        .collect();
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(Error::GateError {
+            reason: format!("regression in {}", failed.join(", ")),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    fn columns(key: &str, values: &[&str]) -> Columns {
+        HashMap::from([(
+            key.to_string(),
+            values.iter().map(|v| v.to_string()).collect(),
+        )])
+    }
+
+    #[rstest]
+    #[case("latency:+10%", Direction::Increase, Limit::Percent(10.0))]
+    #[case("latency:10%", Direction::Increase, Limit::Percent(10.0))]
+    #[case("latency:-2.5", Direction::Decrease, Limit::Absolute(2.5))]
+    #[case("out_latency:+0", Direction::Increase, Limit::Absolute(0.0))]
+    fn parse_rule(#[case] rule: &str, #[case] direction: Direction, #[case] limit: Limit) {
+        let parsed = GateRule::parse(rule).unwrap();
+        assert_eq!(parsed.direction, direction);
+        assert_eq!(parsed.limit, limit);
+    }
+
+    #[rstest]
+    #[case("latency")]
+    #[case(":10%")]
+    #[case("latency:ten")]
+    #[case("latency:+-10")]
+    fn parse_rule_invalid(#[case] rule: &str) {
+        assert!(GateRule::parse(rule).is_err());
+    }
+
+    #[rstest]
+    #[case("latency:+10%", &["10", "10"], &["10.5", "12.5"], false)]
+    #[case("latency:+10%", &["10", "10"], &["10.5", "10.5"], true)]
+    #[case("latency:-10%", &["10"], &["9.5"], true)]
+    #[case("latency:-10%", &["10"], &["8"], false)]
+    #[case("latency:1", &["10"], &["11"], true)]
+    #[case("out_latency:1", &["10", "NA"], &["11.5"], false)]
+    fn check_rule(
+        #[case] rule: &str,
+        #[case] baseline: &[&str],
+        #[case] current: &[&str],
+        #[case] passed: bool,
+    ) {
+        let rule = GateRule::parse(rule).unwrap();
+        let verdict = rule
+            .check(&columns("latency", baseline), &columns("latency", current))
+            .unwrap();
+
+        assert_eq!(verdict.passed, passed);
+    }
+
+    #[test]
+    fn check_rule_missing_key() {
+        let rule = GateRule::parse("power:+5%").unwrap();
+        assert!(rule
+            .check(&columns("latency", &["1"]), &columns("latency", &["1"]))
+            .is_err());
+        assert!(rule
+            .check(&columns("power", &["NA"]), &columns("power", &["1"]))
+            .is_err());
+    }
+
+    #[test]
+    fn gate_writes_verdict() {
+        let tmpdir = TempDir::new().unwrap();
+        let baseline_file = tmpdir.path().join("baseline.csv");
+        let verdict_file = tmpdir.path().join("verdict.csv");
+        std::fs::write(&baseline_file, "latency,word\n10,a\n12,b\n").unwrap();
+
+        let baseline = read_columns(&baseline_file).unwrap();
+        assert_eq!(baseline["latency"], vec!["10", "12"]);
+
+        let rules = vec![
+            GateRule::parse("latency:+10%").unwrap(),
+            GateRule::parse("latency:+50%").unwrap(),
+        ];
+        let current = columns("latency", &["15"]);
+
+        // first rule regressed
+        assert!(run_gate(&rules, &baseline, &current, &verdict_file).is_err());
+
+        let verdict = std::fs::read_to_string(&verdict_file).unwrap();
+        let lines: Vec<&str> = verdict.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("latency:+10%,11,15,"));
+        assert!(lines[1].ends_with(",fail"));
+        assert!(lines[2].ends_with(",pass"));
+
+        // all rules pass
+        assert!(run_gate(&rules[1..], &baseline, &current, &verdict_file).is_ok());
+    }
+}
@@ -0,0 +1,76 @@
+//! Combined tables of multiple Experiment Series
+
+use itertools::Itertools;
+
+/// Concatenates the rows of all `tables`, each starting with its header row.
+///
+/// The header of the result contains every column of any table (sorted by
+/// name). Columns that a table does not have are filled with `na_value` in all
+/// of its rows. Empty tables are skipped.
+///
+/// Returns all rows including the header row, or nothing if all tables are empty.
+pub fn merge(tables: &[Vec<Vec<String>>], na_value: &str) -> Vec<Vec<String>> {
+    let header: Vec<String> = tables
+        .iter()
+        .filter_map(|table| table.first())
+        .flatten()
+        .cloned()
+        .sorted()
+        .dedup()
+        .collect();
+    if header.is_empty() {
+        return vec![];
+    }
+
+    let mut merged = vec![header.clone()];
+    for table in tables.iter().filter(|table| !table.is_empty()) {
+        // position of each merged column in this table
+        let positions: Vec<Option<usize>> = header
+            .iter()
+            .map(|column| table[0].iter().position(|c| c == column))
+            .collect();
+
+        for row in &table[1..] {
+            merged.push(
+                positions
+                    .iter()
+                    .map(|pos| match pos {
+                        Some(pos) => row[*pos].clone(),
+                        None => na_value.to_string(),
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn merge_unions_columns() {
+        let a = table(&[&["THREADS", "runtime"], &["1", "10"], &["2", "6"]]);
+        let b = table(&[&["SIZE", "THREADS", "runtime"], &["L", "4", "3"]]);
+        let empty = table(&[]);
+
+        assert_eq!(
+            merge(&[a, empty, b], "NA"),
+            table(&[
+                &["SIZE", "THREADS", "runtime"],
+                &["NA", "1", "10"],
+                &["NA", "2", "6"],
+                &["L", "4", "3"],
+            ])
+        );
+        assert!(merge(&[table(&[])], "NA").is_empty());
+    }
+}
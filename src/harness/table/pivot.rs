@@ -0,0 +1,290 @@
+//! Pivot tables, reshaping the results of an Experiment Series into a grid
+
+use csv::WriterBuilder;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::stats::{self, numeric_values};
+use crate::experiment::experiment_series::create_csv_file;
+use crate::experiment::{ExperimentRun, ExperimentSeries};
+use crate::helper::errors::{Error, Result};
+
+/// How multiple values that end up in the same cell are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    Mean,
+    Median,
+    Min,
+    Max,
+    /// number of values, also works for non-numeric values
+    Count,
+}
+
+impl FromStr for Aggregation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mean" => Ok(Aggregation::Mean),
+            "median" => Ok(Aggregation::Median),
+            "min" => Ok(Aggregation::Min),
+            "max" => Ok(Aggregation::Max),
+            "count" => Ok(Aggregation::Count),
+            _ => Err(Error::CsvError {
+                reason: format!("unknown aggregation \"{s}\""),
+            }),
+        }
+    }
+}
+
+impl Aggregation {
+    /// Combines `values` into one cell, returns `na_value` if there is nothing to combine.
+    ///
+    /// `na_value` is not counted by `Count`.
+    pub fn apply(&self, values: &[String], na_value: &str) -> String {
+        let numbers = numeric_values(values);
+        let result = match self {
+            Aggregation::Mean => stats::mean(&numbers),
+            Aggregation::Median => stats::median(&numbers),
+            Aggregation::Min => stats::min(&numbers),
+            Aggregation::Max => stats::max(&numbers),
+            Aggregation::Count => {
+                return values
+                    .iter()
+                    .filter(|v| !v.is_empty() && *v != na_value)
+                    .count()
+                    .to_string()
+            }
+        };
+
+        result
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| na_value.to_string())
+    }
+}
+
+/// Returns the `index`th value of `key` in `run`.
+///
+/// `key` may be an output of the run or one of its environment variables.
+fn lookup(run: &ExperimentRun, key: &str, index: usize) -> Option<String> {
+    match run.out_var(key) {
+        Some(vals) => vals.get(index).cloned(),
+        None => run.environment().get_env_val(key).cloned(),
+    }
+}
+
+/// Sorts numerically if all values are numbers, lexicographically otherwise.
+fn sort_values(values: &mut [String]) {
+    match values.iter().all(|v| v.parse::<f64>().is_ok()) {
+        true => values.sort_by(|a, b| a.parse::<f64>().unwrap().total_cmp(&b.parse().unwrap())),
+        false => values.sort(),
+    }
+}
+
+/// Reshapes `series` into a pivot table.
+///
+/// Every distinct value of `row` becomes a row, every distinct value of `col`
+/// a column. Each cell combines all values of `value`, that were recorded with
+/// this combination of `row` and `col`, using `agg`. Cells without any values
+/// are `na_value`.
+///
+/// `row`, `col` and `value` may be output keys or environment variables.
+///
+/// Returns all rows including the header row, which starts with `row`.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `row`, `col` or `value` does not exist in `series`
+pub fn pivot(
+    series: &ExperimentSeries,
+    row: &str,
+    col: &str,
+    value: &str,
+    agg: Aggregation,
+    na_value: &str,
+) -> Result<Vec<Vec<String>>> {
+    for key in [row, col, value] {
+        if !series
+            .runs()
+            .iter()
+            .any(|run| lookup(run, key, 0).is_some())
+        {
+            return Err(Error::CsvError {
+                reason: format!("cannot pivot, {key} is neither an output nor a variable"),
+            });
+        }
+    }
+
+    // collect all values per cell
+    let mut cells: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for run in series.runs() {
+        for i in 0..run.out_files().max_length().max(1) {
+            let (Some(r), Some(c), Some(v)) = (
+                lookup(run, row, i),
+                lookup(run, col, i),
+                lookup(run, value, i),
+            ) else {
+                continue;
+            };
+
+            cells.entry((r, c)).or_default().push(v);
+        }
+    }
+
+    let mut row_values: Vec<String> = cells.keys().map(|(r, _)| r.clone()).unique().collect();
+    let mut col_values: Vec<String> = cells.keys().map(|(_, c)| c.clone()).unique().collect();
+    sort_values(&mut row_values);
+    sort_values(&mut col_values);
+
+    let header = std::iter::once(row.to_string())
+        .chain(col_values.iter().cloned())
+        .collect();
+
+    let mut table = vec![header];
+    for r in &row_values {
+        let mut line = vec![r.clone()];
+        for c in &col_values {
+            line.push(match cells.get(&(r.clone(), c.clone())) {
+                Some(vals) => agg.apply(vals, na_value),
+                None => na_value.to_string(),
+            });
+        }
+        table.push(line);
+    }
+
+    Ok(table)
+}
+
+/// Writes `table` (as returned by [pivot]) to `file`, separating the fields
+/// with `delimiter`.
+///
+/// ## Errors
+/// - Returns a `CsvError` if `file` could not be written
+pub fn write_csv(table: &[Vec<String>], file: &Path, delimiter: u8) -> Result<()> {
+    write_csv_to(table, create_csv_file(file)?, delimiter)
+}
+
+/// Writes `table` to `writer` like [write_csv].
+///
+/// ## Errors
+/// - Returns a `CsvError` if `writer` could not be written
+pub fn write_csv_to(table: &[Vec<String>], writer: impl Write, delimiter: u8) -> Result<()> {
+    let to_csv_error = |e: csv::Error| Error::CsvError {
+        reason: e.to_string(),
+    };
+
+    let mut wtr = WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(writer);
+    for line in table {
+        wtr.write_record(line).map_err(to_csv_error)?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::FileReader;
+    use crate::helper::test_helper::create_run;
+    use rstest::rstest;
+    use tempfile::TempDir;
+
+    /// series with THREADS in [1, 2] and SIZE in [S, M, L], two repetitions each
+    fn grid_series() -> TempDir {
+        let tmpdir = TempDir::new().unwrap();
+
+        for (threads, size, rep, time) in [
+            (1, "S", 0, "1"),
+            (1, "S", 1, "3"),
+            (1, "M", 0, "4"),
+            (1, "L", 0, "8"),
+            (2, "S", 0, "0.5"),
+            (2, "M", 0, "2"),
+            (2, "M", 1, "NA"),
+            (2, "L", 0, "4"),
+        ] {
+            create_run(
+                tmpdir.path(),
+                &format!("run_{threads}{size}_rep{rep}"),
+                &format!("THREADS={threads}\nSIZE={size}"),
+                &[("out_time", time)],
+            );
+        }
+
+        tmpdir
+    }
+
+    #[test]
+    fn pivot_grid() {
+        let tmpdir = grid_series();
+        let series = ExperimentSeries::parse(tmpdir.path()).unwrap();
+
+        let table = pivot(&series, "THREADS", "SIZE", "time", Aggregation::Mean, "NA").unwrap();
+        assert_eq!(
+            table,
+            vec![
+                vec!["THREADS", "L", "M", "S"],
+                vec!["1", "8", "4", "2"],
+                vec!["2", "4", "2", "0.5"],
+            ]
+        );
+
+        let table = pivot(&series, "SIZE", "THREADS", "time", Aggregation::Count, "NA").unwrap();
+        assert_eq!(
+            table,
+            vec![
+                vec!["SIZE", "1", "2"],
+                vec!["L", "1", "1"],
+                vec!["M", "1", "1"],
+                vec!["S", "2", "1"],
+            ]
+        );
+
+        let file = tmpdir.path().join("pivot.csv");
+        write_csv(&table, &file, b',').unwrap();
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .starts_with("SIZE,1,2\nL,1,1\n"));
+
+        write_csv(&table, &file, b';').unwrap();
+        assert!(std::fs::read_to_string(&file)
+            .unwrap()
+            .starts_with("SIZE;1;2\nL;1;1\n"));
+    }
+
+    #[rstest]
+    #[case("NOPE", "SIZE", "time")]
+    #[case("THREADS", "NOPE", "time")]
+    #[case("THREADS", "SIZE", "nope")]
+    fn pivot_unknown_key(#[case] row: &str, #[case] col: &str, #[case] value: &str) {
+        let tmpdir = grid_series();
+        let series = ExperimentSeries::parse(tmpdir.path()).unwrap();
+
+        let err = pivot(&series, row, col, value, Aggregation::Mean, "NA").unwrap_err();
+        assert!(err.to_string().contains("neither an output nor a variable"));
+    }
+
+    #[test]
+    fn aggregation_from_str() {
+        assert_eq!(
+            "median".parse::<Aggregation>().unwrap(),
+            Aggregation::Median
+        );
+        assert!("sum".parse::<Aggregation>().is_err());
+        assert_eq!(
+            Aggregation::Max.apply(&["1".into(), "NA".into(), "3".into()], "NA"),
+            "3"
+        );
+        assert_eq!(Aggregation::Min.apply(&["NA".into()], "NA"), "NA");
+
+        // a custom placeholder is neither counted nor a number
+        let values = ["null".to_string(), "2".to_string()];
+        assert_eq!(Aggregation::Count.apply(&values, "null"), "1");
+        assert_eq!(Aggregation::Mean.apply(&values[..1], "null"), "null");
+    }
+}
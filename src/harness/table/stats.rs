@@ -0,0 +1,99 @@
+//! Statistics over the columns of an output table
+
+/// Parses all values, that represent a number.
+///
+/// Values that are not numeric (e.g. "NA" or empty strings) are skipped.
+pub fn numeric_values(values: &[String]) -> Vec<f64> {
+    values
+        .iter()
+        .filter_map(|val| val.trim().parse::<f64>().ok())
+        .filter(|val| !val.is_nan())
+        .collect()
+}
+
+/// Returns the arithmetic mean of `values`, or `None` if `values` is empty.
+pub fn mean(values: &[f64]) -> Option<f64> {
+    match values.is_empty() {
+        true => None,
+        false => Some(values.iter().sum::<f64>() / values.len() as f64),
+    }
+}
+
+/// Returns the median of `values`, or `None` if `values` is empty.
+pub fn median(values: &[f64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+/// Returns the smallest of `values`, or `None` if `values` is empty.
+pub fn min(values: &[f64]) -> Option<f64> {
+    values.iter().copied().reduce(f64::min)
+}
+
+/// Returns the largest of `values`, or `None` if `values` is empty.
+pub fn max(values: &[f64]) -> Option<f64> {
+    values.iter().copied().reduce(f64::max)
+}
+
+/// Returns the sample standard deviation of `values`, or `None` if there are
+/// less than two values.
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(values)?;
+    let squares: f64 = values.iter().map(|val| (val - mean).powi(2)).sum();
+    Some((squares / (values.len() - 1) as f64).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_values_skip_non_numbers() {
+        let values: Vec<String> = ["1", " 2.5 ", "NA", "", "abc", "-3", "NaN"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        assert_eq!(numeric_values(&values), vec![1.0, 2.5, -3.0]);
+    }
+
+    #[test]
+    fn mean_of_values() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(mean(&[4.0]), Some(4.0));
+        assert_eq!(mean(&[1.0, 2.0, 6.0]), Some(3.0));
+    }
+
+    #[test]
+    fn median_min_max_of_values() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+
+        assert_eq!(min(&[]), None);
+        assert_eq!(min(&[3.0, -1.0, 2.0]), Some(-1.0));
+        assert_eq!(max(&[3.0, -1.0, 2.0]), Some(3.0));
+    }
+
+    #[test]
+    fn std_dev_of_values() {
+        assert_eq!(std_dev(&[]), None);
+        assert_eq!(std_dev(&[4.0]), None);
+        assert_eq!(
+            std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+            Some(32.0_f64.sqrt() / 7.0_f64.sqrt())
+        );
+        assert_eq!(std_dev(&[3.0, 3.0]), Some(0.0));
+    }
+}
@@ -0,0 +1,144 @@
+//! Wide tables, with one row per environment and one column per repetition
+
+use itertools::Itertools;
+
+use crate::experiment::{ExperimentRun, ExperimentSeries};
+use crate::harness::env::Environment;
+
+/// Variable that contains the repetition of a run
+const REPETITION_VAR: &str = "REPETITION";
+
+/// Returns the repetition of `run`.
+///
+/// Taken from its environment, or from the `_rep[n]` suffix of its name for
+/// runs without `REPETITION` in their environment.
+fn repetition(run: &ExperimentRun) -> Option<u64> {
+    match run.environment().get_env_val(REPETITION_VAR) {
+        Some(rep) => rep.parse().ok(),
+        None => run.run_dir_name().rsplit_once("_rep")?.1.parse().ok(),
+    }
+}
+
+/// Returns the environment of `run` without its repetition.
+pub(crate) fn environment(run: &ExperimentRun) -> Environment {
+    Environment::from_env_list(
+        run.environment()
+            .to_env_map()
+            .iter()
+            .filter(|(var, _)| *var != REPETITION_VAR)
+            .map(|(var, val)| (var.clone(), val.clone()))
+            .collect(),
+    )
+}
+
+/// Reshapes `series` into a table with one row per environment.
+///
+/// Every row starts with the variables of its environment, followed by one
+/// column `[key]_rep[n]` per output key and repetition. Runs with multiple
+/// values for a key are joined with `;`. Repetitions that have not been
+/// recorded for an environment are filled with `na_value`.
+///
+/// Returns all rows including the header row.
+pub fn wide_per_rep(series: &ExperimentSeries, na_value: &str) -> Vec<Vec<String>> {
+    let runs: Vec<&ExperimentRun> = series
+        .runs()
+        .iter()
+        .sorted_by_key(|run| run.run_dir_name().to_string())
+        .collect();
+
+    let mut environments: Vec<Environment> = vec![];
+    for env in runs.iter().map(|run| environment(run)) {
+        if !environments.contains(&env) {
+            environments.push(env);
+        }
+    }
+
+    let repetitions: Vec<u64> = runs
+        .iter()
+        .filter_map(|run| repetition(run))
+        .sorted()
+        .dedup()
+        .collect();
+    let variables: Vec<String> = environments
+        .first()
+        .map(|env| env.get_env_vars().into_iter().cloned().collect())
+        .unwrap_or_default();
+    let keys = series.keys();
+
+    let header = variables
+        .iter()
+        .cloned()
+        .chain(
+            keys.iter()
+                .flat_map(|key| repetitions.iter().map(move |rep| format!("{key}_rep{rep}"))),
+        )
+        .collect();
+
+    let mut table = vec![header];
+    for env in &environments {
+        let mut row: Vec<String> = variables
+            .iter()
+            .map(|var| {
+                env.get_env_val(var)
+                    .cloned()
+                    .unwrap_or_else(|| na_value.to_string())
+            })
+            .collect();
+
+        for key in &keys {
+            for rep in &repetitions {
+                let value = runs
+                    .iter()
+                    .find(|run| environment(run) == *env && repetition(run) == Some(*rep))
+                    .and_then(|run| run.out_var(key))
+                    .filter(|vals| !vals.is_empty())
+                    .map(|vals| vals.join(";"))
+                    .unwrap_or_else(|| na_value.to_string());
+                row.push(value);
+            }
+        }
+
+        table.push(row);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::FileReader;
+    use crate::helper::test_helper::create_run;
+    use tempfile::TempDir;
+
+    #[test]
+    fn wide_two_envs_three_reps() {
+        let tmpdir = TempDir::new().unwrap();
+
+        // rep 2 of S is missing
+        for (size, rep, latency) in [
+            ("S", 0, "1"),
+            ("S", 1, "2"),
+            ("L", 0, "10"),
+            ("L", 1, "11"),
+            ("L", 2, "12"),
+        ] {
+            create_run(
+                tmpdir.path(),
+                &format!("run_{size}_rep{rep}"),
+                &format!("SIZE={size}\nREPETITION={rep}"),
+                &[("out_latency", latency)],
+            );
+        }
+
+        let series = ExperimentSeries::parse(tmpdir.path()).unwrap();
+        assert_eq!(
+            wide_per_rep(&series, "NA"),
+            vec![
+                vec!["SIZE", "latency_rep0", "latency_rep1", "latency_rep2"],
+                vec!["L", "10", "11", "12"],
+                vec!["S", "1", "2", "NA"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! harness validate subcommand
+
+use itertools::Itertools;
+use log::info;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::harness::env::{fetch_environment_files, Environment, ValidationRules};
+use crate::helper::archivist::find_marker_pwd;
+use crate::helper::errors::{Error, Result};
+use crate::helper::fs_names::*;
+
+/// entrypoint for validate binary
+///
+/// Checks the experiment source at `exp_source` (or the one containing pwd)
+/// without running anything, see `problems()`.
+///
+/// ## Errors
+/// - Returns a `FindMarkerError` if no experiment is given and pwd is not inside
+///   of an experiment source
+/// - Returns a `HarnessRunError` listing all problems, if there are any
+pub fn main(exp_source: Option<&Path>) -> Result<()> {
+    let exp_source = match exp_source {
+        Some(dir) => dir.to_path_buf(),
+        None => find_marker_pwd(MARKER_SRC)?,
+    };
+
+    let problems = problems(&exp_source);
+    match problems.is_empty() {
+        true => {
+            info!("{} is a valid experiment", exp_source.display());
+            Ok(())
+        }
+        false => Err(Error::HarnessRunError {
+            experiment: exp_source.display().to_string(),
+            err: format!(
+                "found {} problem(s):\n{}",
+                problems.len(),
+                problems.join("\n")
+            ),
+        }),
+    }
+}
+
+/// Returns all problems of the experiment source at `exp_source`, an empty list
+/// if it can be run:
+/// - [MARKER_SRC] has to exist
+/// - there has to be an executable run script in [SRC_TEMPLATE_DIR] (see `find_run_file()`)
+/// - [SRC_ENV_DIR] has to contain at least one env file, all env files have to be
+///   readable and valid (see `ValidationRules::default()`)
+fn problems(exp_source: &Path) -> Vec<String> {
+    let mut problems = vec![];
+
+    if !exp_source.join(MARKER_SRC).is_file() {
+        problems.push(format!("{MARKER_SRC} is missing, not an experiment source"));
+    }
+
+    let template_dir = exp_source.join(SRC_TEMPLATE_DIR);
+    match find_run_file(&template_dir) {
+        None => problems.push(format!(
+            "no run script in {SRC_TEMPLATE_DIR}/ (e.g. {SRC_RUN_FILE})"
+        )),
+        Some(run_file) if !is_executable(&run_file) => problems.push(format!(
+            "{SRC_TEMPLATE_DIR}/{} is not executable",
+            file_name_string(&run_file)
+        )),
+        Some(_) => (),
+    }
+
+    let env_dir = exp_source.join(SRC_ENV_DIR);
+    let env_files: Vec<PathBuf> = match env_dir.is_dir() {
+        true => fetch_environment_files(&env_dir).unwrap_or_default(),
+        false => vec![],
+    };
+    if env_files.is_empty() {
+        problems.push(format!("{SRC_ENV_DIR}/ contains no env files"));
+    }
+
+    let rules = ValidationRules::default();
+    for env_file in env_files.iter().map(|file| file_name_string(file)).sorted() {
+        let env = Environment::from_file(&env_dir.join(&env_file));
+        match env.and_then(|env| env.validate(&rules)) {
+            Ok(()) => (),
+            Err(Error::ValidationError { violations }) => problems.extend(
+                violations
+                    .iter()
+                    .map(|violation| format!("{SRC_ENV_DIR}/{env_file}: {violation}")),
+            ),
+            Err(e) => problems.push(format!("{SRC_ENV_DIR}/{env_file}: {e}")),
+        }
+    }
+
+    problems
+}
+
+/// Returns `true` if anyone may execute `file`.
+fn is_executable(file: &Path) -> bool {
+    std::fs::metadata(file).is_ok_and(|meta| meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::experiment::{ExperimentSource, FileWriter};
+    use tempfile::TempDir;
+
+    #[test]
+    fn validate_skeleton() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("Foo");
+        ExperimentSource::new().persist(&exp).unwrap();
+
+        assert_eq!(problems(&exp), Vec::<String>::new());
+        assert!(main(Some(&exp)).is_ok());
+    }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let tmpdir = TempDir::new().unwrap();
+        let exp = tmpdir.path().join("Foo");
+        ExperimentSource::new().persist(&exp).unwrap();
+
+        let run_file = exp.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE);
+        std::fs::set_permissions(&run_file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        std::fs::write(exp.join(SRC_ENV_DIR).join("0.env"), "lower=1\nREPETITION=2").unwrap();
+        std::fs::write(exp.join(SRC_ENV_DIR).join("1.env"), "A=1").unwrap();
+
+        assert_eq!(
+            problems(&exp),
+            vec![
+                "template/run.sh is not executable",
+                "envs/0.env: invalid name 'lower', only upper case alphanumeric and _ allowed",
+                "envs/0.env: 'REPETITION' is reserved by the exomat",
+            ]
+        );
+
+        std::fs::remove_file(exp.join(MARKER_SRC)).unwrap();
+        std::fs::remove_file(run_file).unwrap();
+        std::fs::remove_dir_all(exp.join(SRC_ENV_DIR)).unwrap();
+        assert_eq!(
+            problems(&exp),
+            vec![
+                ".exomat_source is missing, not an experiment source",
+                "no run script in template/ (e.g. run.sh)",
+                "envs/ contains no env files",
+            ]
+        );
+
+        let err = main(Some(&exp)).unwrap_err().to_string();
+        assert!(err.contains("found 3 problem(s)"), "{err}");
+    }
+}
@@ -0,0 +1,146 @@
+//! Pinning of Experiment Runs to a set of CPU cores
+
+use std::process::Command;
+use std::str::FromStr;
+
+use crate::helper::errors::{Error, Result};
+
+/// Set of CPU cores, given like for `taskset -c`, e.g. `0-7` or `0,2,4`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CpuSet {
+    /// sorted, without duplicates
+    cpus: Vec<usize>,
+}
+
+impl CpuSet {
+    /// Returns the indices of all cores in this set.
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+}
+
+impl FromStr for CpuSet {
+    type Err = Error;
+
+    /// ## Errors
+    /// - Returns a `HarnessRunError` if `s` is not a comma separated list of core
+    ///   indices and ranges
+    fn from_str(s: &str) -> Result<Self> {
+        let affinity_error = |reason: String| Error::HarnessRunError {
+            experiment: s.to_string(),
+            err: format!("Invalid CPU affinity: {reason}"),
+        };
+        let parse_cpu = |cpu: &str| {
+            cpu.trim()
+                .parse::<usize>()
+                .map_err(|_| affinity_error(format!("\"{cpu}\" is not a core index")))
+        };
+
+        let mut cpus = vec![];
+        for part in s.split(',') {
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (parse_cpu(first)?, parse_cpu(last)?);
+                    if first > last {
+                        return Err(affinity_error(format!("range {part} is empty")));
+                    }
+                    cpus.extend(first..=last);
+                }
+                None => cpus.push(parse_cpu(part)?),
+            }
+        }
+
+        cpus.sort();
+        cpus.dedup();
+        if let Some(cpu) = cpus.iter().find(|&&cpu| cpu >= MAX_CPUS) {
+            return Err(affinity_error(format!(
+                "core {cpu} is out of range, at most {MAX_CPUS} cores are supported"
+            )));
+        }
+
+        Ok(CpuSet { cpus })
+    }
+}
+
+impl std::fmt::Display for CpuSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list: Vec<String> = self.cpus.iter().map(|cpu| cpu.to_string()).collect();
+        write!(f, "{}", list.join(","))
+    }
+}
+
+/// Number of cores that fit into a `cpu_set_t`
+#[cfg(target_os = "linux")]
+const MAX_CPUS: usize = libc::CPU_SETSIZE as usize;
+#[cfg(not(target_os = "linux"))]
+const MAX_CPUS: usize = 1024;
+
+/// Restricts the process started by `command` to the cores in `cpus`.
+///
+/// Uses `sched_setaffinity` in the child before the run script is executed, so
+/// all processes started by the script inherit the affinity. Spawning `command`
+/// fails if none of the cores exist.
+#[cfg(target_os = "linux")]
+pub fn pin_command(command: &mut Command, cpus: &CpuSet) {
+    use std::os::unix::process::CommandExt;
+
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus.cpus() {
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+
+    // only a single syscall, which is safe to do between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            match libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) {
+                0 => Ok(()),
+                _ => Err(std::io::Error::last_os_error()),
+            }
+        });
+    }
+}
+
+/// CPU affinity is only supported on Linux, elsewhere `command` is not changed.
+#[cfg(not(target_os = "linux"))]
+pub fn pin_command(_command: &mut Command, cpus: &CpuSet) {
+    log::warn!("CPU affinity is only supported on Linux, not pinning run to cores {cpus}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("0", vec![0])]
+    #[case("0-3", vec![0, 1, 2, 3])]
+    #[case("0,2,4", vec![0, 2, 4])]
+    #[case("6-7,0,2-3,7", vec![0, 2, 3, 6, 7])]
+    fn parse_cpu_set(#[case] arg: &str, #[case] cpus: Vec<usize>) {
+        assert_eq!(arg.parse::<CpuSet>().unwrap().cpus(), cpus);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("3-1")]
+    #[case("0,")]
+    #[case("-1")]
+    #[case("99999")]
+    fn parse_invalid_cpu_set(#[case] arg: &str) {
+        let err = arg.parse::<CpuSet>().unwrap_err().to_string();
+        assert!(err.contains("Invalid CPU affinity"), "{err}");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn pinned_command() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "grep Cpus_allowed_list /proc/self/status"]);
+        pin_command(&mut command, &"0".parse().unwrap());
+
+        let output = command.output().unwrap();
+        let status = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(status.split_whitespace().last(), Some("0"));
+    }
+}
@@ -1,10 +1,7 @@
 //! Functions that touch the filesystem.
 
 use chrono::{DateTime, Local};
-use fs_extra::{
-    dir::{copy as copy_dir, CopyOptions as DCopyOptions},
-    file::{copy as copy_file, CopyOptions as FCopyOptions},
-};
+use fs_extra::file::{copy as copy_file, CopyOptions as FCopyOptions};
 use log::debug;
 use std::fs::{create_dir_all, File};
 use std::path::{Path, PathBuf};
@@ -60,6 +57,23 @@ pub fn create_harness_file(file: &PathBuf) -> Result<PathBuf> {
     Ok(file.to_owned())
 }
 
+/// Replaces the content of `file` with `content` atomically.
+///
+/// Writes to a temporary file next to `file` first, which is then renamed. Crashing
+/// while writing therefore leaves either the old or the new content, never a mix.
+///
+/// ## Errors
+/// - Returns an `IoError` if writing or renaming failed
+pub fn write_atomic(file: &Path, content: &str) -> Result<()> {
+    let mut tmp_name = file.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_file = file.with_file_name(tmp_name);
+
+    std::fs::write(&tmp_file, content)?;
+    std::fs::rename(&tmp_file, file)?;
+    Ok(())
+}
+
 /// Copies the content of one file to another.
 ///
 /// Both files have to exist prior to calling this function.
@@ -75,20 +89,295 @@ pub fn copy_harness_file(from: &PathBuf, to: &PathBuf) -> Result<()> {
     }
 }
 
+/// How symlinks are handled when copying directories
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SymlinkMode {
+    /// copy the target of the link
+    Follow,
+    /// create a link to the same target, relative targets are made absolute
+    #[default]
+    Preserve,
+    /// do not copy links at all
+    Skip,
+}
+
+impl std::str::FromStr for SymlinkMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "follow" => Ok(SymlinkMode::Follow),
+            "preserve" => Ok(SymlinkMode::Preserve),
+            "skip" => Ok(SymlinkMode::Skip),
+            _ => Err(Error::HarnessCreateError {
+                entry: s.to_string(),
+                reason: "unknown symlink mode, use follow, preserve or skip".to_string(),
+            }),
+        }
+    }
+}
+
+/// Compression of an archive created by `archive_dir()`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Compression {
+    #[default]
+    Gzip,
+    Zstd,
+}
+
+impl std::str::FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(Error::WriterError {
+                dir: s.to_string(),
+                reason: "unknown compression, use gzip or zstd".to_string(),
+            }),
+        }
+    }
+}
+
+impl Compression {
+    /// Extension of an archive with this compression, e.g. `tar.gz`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "tar.gz",
+            Compression::Zstd => "tar.zst",
+        }
+    }
+
+    /// Option of `tar` that selects this compression
+    fn tar_option(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "--gzip",
+            Compression::Zstd => "--zstd",
+        }
+    }
+}
+
+/// Packs `dir` (including the directory itself) into a new compressed tar
+/// archive at `archive`.
+///
+/// Uses the `tar` executable, which keeps hidden files (like marker files),
+/// permissions and symlinks. [Compression::Zstd] requires `zstd` to be installed.
+///
+/// ## Errors
+/// - Returns a `WriterError` if `archive` exists already or `dir` is no directory
+/// - Returns a `WriterError` if `tar` could not be executed or failed
+pub fn archive_dir(dir: &Path, archive: &Path, compression: Compression) -> Result<()> {
+    let to_writer_error = |reason: String| Error::WriterError {
+        dir: archive.display().to_string(),
+        reason,
+    };
+
+    if archive.exists() {
+        return Err(to_writer_error("already exists".to_string()));
+    }
+    let (Some(parent), Some(name)) = (dir.parent(), dir.file_name()) else {
+        return Err(to_writer_error(format!("cannot archive {}", dir.display())));
+    };
+    if !dir.is_dir() {
+        return Err(to_writer_error(format!(
+            "{} is no directory",
+            dir.display()
+        )));
+    }
+
+    debug!("archiving {} to {}", dir.display(), archive.display());
+    let output = std::process::Command::new("tar")
+        .arg(compression.tar_option())
+        .arg("--create")
+        .arg("--file")
+        .arg(archive)
+        .arg("--directory")
+        .arg(parent)
+        .arg(name)
+        .output()
+        .map_err(|e| to_writer_error(format!("cannot execute tar: {e}")))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => {
+            // do not leave a broken archive behind
+            let _ = std::fs::remove_file(archive);
+            Err(to_writer_error(format!(
+                "tar failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// Extension of files compressed by `gzip_file()`
+pub const GZIP_EXTENSION: &str = "gz";
+
+/// Returns `file` with [GZIP_EXTENSION] appended, e.g. `stdout.log.gz`.
+pub fn gzipped_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(format!(".{GZIP_EXTENSION}"));
+    PathBuf::from(name)
+}
+
+/// Runs `gzip` with `args`.
+///
+/// ## Errors
+/// - Returns a `WriterError` if `gzip` could not be executed or failed
+fn run_gzip(file: &Path, args: &[&str]) -> Result<std::process::Output> {
+    let to_writer_error = |reason: String| Error::WriterError {
+        dir: file.display().to_string(),
+        reason,
+    };
+
+    let output = std::process::Command::new("gzip")
+        .args(args)
+        .arg("--")
+        .arg(file)
+        .output()
+        .map_err(|e| to_writer_error(format!("cannot execute gzip: {e}")))?;
+
+    match output.status.success() {
+        true => Ok(output),
+        false => Err(to_writer_error(format!(
+            "gzip failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))),
+    }
+}
+
+/// Compresses `file` with `gzip`, replacing it by `gzipped_path(file)`.
+///
+/// If `gzipped_path(file)` exists already, the compressed content of `file` is
+/// appended to it. Decompressing it results in both contents, one after the other.
+///
+/// ## Errors
+/// - Returns a `WriterError` if `gzip` could not be executed or failed
+/// - Returns an `IoError` if the compressed file could not be written
+pub fn gzip_file(file: &Path) -> Result<()> {
+    debug!("compressing {}", file.display());
+    let output = run_gzip(file, &["--stdout"])?;
+
+    let mut gzipped = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(gzipped_path(file))?;
+    std::io::Write::write_all(&mut gzipped, &output.stdout)?;
+    std::fs::remove_file(file)?;
+
+    Ok(())
+}
+
+/// Decompresses `gzipped_path(file)` into `file`, appending to `file` if it
+/// exists. The compressed file is removed. Does nothing if there is no
+/// compressed file.
+///
+/// ## Errors
+/// - Returns a `WriterError` if `gzip` could not be executed or failed
+/// - Returns an `IoError` if `file` could not be written
+pub fn gunzip_file(file: &Path) -> Result<()> {
+    let gzipped = gzipped_path(file);
+    if !gzipped.is_file() {
+        return Ok(());
+    }
+
+    debug!("decompressing {}", gzipped.display());
+    let output = run_gzip(&gzipped, &["--decompress", "--stdout"])?;
+
+    let mut plain = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)?;
+    std::io::Write::write_all(&mut plain, &output.stdout)?;
+    std::fs::remove_file(gzipped)?;
+
+    Ok(())
+}
+
+/// Returns the content of `file`, or the decompressed content of
+/// `gzipped_path(file)` if only that exists (see `gzip_file()`).
+///
+/// ## Errors
+/// - Returns an `IoError` if neither file exists or `file` could not be read
+/// - Returns a `WriterError` if `gzip` could not be executed or failed
+/// - Returns a `ReaderError` if the content is not valid UTF-8
+pub fn read_maybe_gzipped(file: &Path) -> Result<String> {
+    let gzipped = gzipped_path(file);
+    if file.exists() || !gzipped.is_file() {
+        return Ok(std::fs::read_to_string(file)?);
+    }
+
+    let output = run_gzip(&gzipped, &["--decompress", "--stdout"])?;
+    String::from_utf8(output.stdout).map_err(|e| Error::ReaderError {
+        dir: gzipped.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
 /// Copies the content of one direcory into another, without creating a new folder
 /// in the destination directory.
 ///
+/// Symlinks are handled according to `symlinks`. With [SymlinkMode::Preserve],
+/// large linked data (e.g. a shared dataset) is not duplicated, the copy links
+/// to the same target.
+///
 /// Both directories have to exist prior to calling this function.
 ///
 /// Retruns a `HarnessCreateError` if something went wrong.
-pub fn copy_harness_dir(from: &PathBuf, to: &PathBuf) -> Result<()> {
-    match copy_dir(from, to, &DCopyOptions::new().content_only(true)) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(Error::HarnessCreateError {
-            entry: to.display().to_string(),
-            reason: e.to_string(),
-        }),
+pub fn copy_harness_dir(from: &Path, to: &Path, symlinks: SymlinkMode) -> Result<()> {
+    copy_dir_content(from, to, symlinks).map_err(|e| Error::HarnessCreateError {
+        entry: to.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Recursively copies everything inside `from` into `to`, see `copy_harness_dir()`.
+fn copy_dir_content(from: &Path, to: &Path, symlinks: SymlinkMode) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let source = entry.path();
+        let dest = to.join(entry.file_name());
+
+        if entry.file_type()?.is_symlink() {
+            match symlinks {
+                SymlinkMode::Skip => {
+                    debug!("skipping symlink {}", source.display());
+                    continue;
+                }
+                SymlinkMode::Preserve => {
+                    // keep relative links working from their new location
+                    let target = from.join(std::fs::read_link(&source)?);
+                    std::os::unix::fs::symlink(target, &dest)?;
+                    continue;
+                }
+                SymlinkMode::Follow => (),
+            }
+        }
+
+        // follows symlinks, so linked directories are copied as directories
+        if source.is_dir() {
+            std::fs::create_dir(&dest)?;
+            copy_dir_content(&source, &dest, symlinks)?;
+        } else {
+            std::fs::copy(&source, &dest)?;
+        }
     }
+
+    Ok(())
+}
+
+/// Returns the absolute path of `path`, with all symlinks resolved.
+///
+/// Like [canonicalize](std::fs::canonicalize), but the error names the path.
+///
+/// ## Errors
+/// - Returns a `ReaderError` if `path` does not exist or cannot be accessed
+pub fn canonicalize(path: &Path) -> Result<PathBuf> {
+    path.canonicalize().map_err(|e| Error::ReaderError {
+        dir: path.display().to_string(),
+        reason: format!("cannot resolve absolute path: {e}"),
+    })
 }
 
 /// find the parent dir containing the given marker file, starting at pwd
@@ -106,7 +395,7 @@ pub fn find_marker_pwd(marker_name: &str) -> Result<PathBuf> {
 /// Uses PWD if location is not given.
 pub fn find_marker(location: &Path, marker_name: &str) -> Result<PathBuf> {
     if !location.is_absolute() {
-        let location = location.to_path_buf().canonicalize()?;
+        let location = canonicalize(location)?;
         return find_marker(&location, marker_name);
     }
 
@@ -141,6 +430,120 @@ mod tests {
 
     const TEST_FMT: &str = "test_fmt-%Y-%m-%d-%H-%M-%S";
 
+    #[test]
+    fn canonicalize_missing_path() {
+        let tmpdir = TempDir::new().unwrap();
+        let missing = tmpdir.path().join("missing");
+
+        let err = canonicalize(&missing).unwrap_err();
+        assert!(matches!(err, Error::ReaderError { .. }));
+        assert!(err.to_string().contains(&missing.display().to_string()));
+
+        assert!(matches!(
+            find_marker(Path::new("does/not/exist"), ".my_marker"),
+            Err(Error::ReaderError { .. })
+        ));
+    }
+
+    #[test]
+    fn archive_keeps_markers_and_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmpdir = TempDir::new().unwrap();
+        let dir = tmpdir.path().join("series");
+        std::fs::create_dir_all(dir.join("runs")).unwrap();
+        std::fs::write(dir.join(".marker"), "").unwrap();
+        std::fs::write(dir.join("runs").join("run.sh"), "").unwrap();
+        std::fs::set_permissions(
+            dir.join("runs").join("run.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let archive = tmpdir.path().join("series.tar.gz");
+        archive_dir(&dir, &archive, Compression::Gzip).unwrap();
+        assert!(archive_dir(&dir, &archive, Compression::Gzip).is_err());
+
+        let unpacked = tmpdir.path().join("unpacked");
+        std::fs::create_dir(&unpacked).unwrap();
+        let status = std::process::Command::new("tar")
+            .arg("-xzf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&unpacked)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert!(unpacked.join("series").join(".marker").is_file());
+        let mode = std::fs::metadata(unpacked.join("series").join("runs").join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[test]
+    fn gzip_roundtrip() {
+        let tmpdir = TempDir::new().unwrap();
+        let log = tmpdir.path().join("stdout.log");
+        let gzipped = tmpdir.path().join("stdout.log.gz");
+
+        std::fs::write(&log, "first\n").unwrap();
+        gzip_file(&log).unwrap();
+        assert!(!log.exists() && gzipped.is_file());
+        assert_eq!(read_maybe_gzipped(&log).unwrap(), "first\n");
+
+        // compressing again appends
+        std::fs::write(&log, "second\n").unwrap();
+        assert_eq!(read_maybe_gzipped(&log).unwrap(), "second\n");
+        gzip_file(&log).unwrap();
+        assert_eq!(read_maybe_gzipped(&log).unwrap(), "first\nsecond\n");
+
+        std::fs::write(&log, "plain\n").unwrap();
+        gunzip_file(&log).unwrap();
+        assert!(!gzipped.exists());
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "plain\nfirst\nsecond\n"
+        );
+
+        assert!(read_maybe_gzipped(&tmpdir.path().join("missing.log")).is_err());
+    }
+
+    #[test]
+    fn copy_dir_symlinks() {
+        let tmpdir = TempDir::new().unwrap();
+        let from = tmpdir.path().join("from");
+        std::fs::create_dir_all(from.join("sub")).unwrap();
+        std::fs::write(from.join("sub").join("file"), "content").unwrap();
+        std::fs::write(tmpdir.path().join("dataset"), "huge").unwrap();
+        std::os::unix::fs::symlink("../dataset", from.join("data")).unwrap();
+
+        let copy = |mode: SymlinkMode| {
+            let to = tmpdir.path().join(format!("{mode:?}"));
+            std::fs::create_dir(&to).unwrap();
+            copy_harness_dir(&from, &to, mode).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(to.join("sub").join("file")).unwrap(),
+                "content"
+            );
+            to
+        };
+
+        // link still points to the dataset
+        let to = copy(SymlinkMode::Preserve);
+        assert!(to.join("data").is_symlink());
+        assert_eq!(std::fs::read_to_string(to.join("data")).unwrap(), "huge");
+
+        let to = copy(SymlinkMode::Follow);
+        assert!(!to.join("data").is_symlink());
+        assert_eq!(std::fs::read_to_string(to.join("data")).unwrap(), "huge");
+
+        let to = copy(SymlinkMode::Skip);
+        assert!(!to.join("data").exists());
+    }
+
     #[test]
     fn default_name_generation() {
         let now = Local::now();
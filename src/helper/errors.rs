@@ -16,6 +16,10 @@ pub enum Error {
     #[error(transparent)]
     LoggerError(#[from] spdlog::re_export::log::SetLoggerError),
 
+    /// Occurs when an unknown log format was requested.
+    #[error("Cannot configure logging: {reason}")]
+    LoggerFormatError { reason: String },
+
     /// Occurs when the harness command failed to create files/directories.
     #[error("Cannot create {entry:?}: {reason:?}")]
     HarnessCreateError { entry: String, reason: String },
@@ -27,10 +31,20 @@ pub enum Error {
     #[error("Something went wrong in .env generation: {reason:?}")]
     EnvError { reason: String },
 
+    /// Occurs when variables of an Environment violate the validation rules.
+    #[error("Invalid environment: {}", violations.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("; "))]
+    ValidationError {
+        violations: Vec<crate::harness::env::Violation>,
+    },
+
     /// Occurs when the make-table command could not generate CSV output.
     #[error("CSV conversion failed: {reason}")]
     CsvError { reason: String },
 
+    /// Occurs when the results of make-table regressed compared to a baseline.
+    #[error("Regression gate failed: {reason}")]
+    GateError { reason: String },
+
     #[error("Cannot generate autocompletion file: {err}")]
     CompletionError { err: String },
 
@@ -1,13 +1,16 @@
 //! Default file/directory names for the harness file structure
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // experiment source folder
 pub const SRC_TEMPLATE_DIR: &str = "template";
 pub const SRC_RUN_FILE: &str = "run.sh";
 pub const SRC_ENV_DIR: &str = "envs";
 pub const SRC_ENV_FILE: &str = "0.env";
+// shared variables inside of SRC_TEMPLATE_DIR, merged into every env file
+pub const SRC_BASE_ENV_FILE: &str = "base.env";
 pub const SRC_README: &str = "README";
+pub const SRC_GITIGNORE: &str = ".gitignore";
 
 // experiment series folder
 pub const SERIES_SRC_DIR: &str = ".src";
@@ -15,10 +18,27 @@ pub const SERIES_RUNS_DIR: &str = "runs";
 pub const SERIES_EXOMAT_LOG: &str = "exomat.log";
 pub const SERIES_STDERR_LOG: &str = "stderr.log";
 pub const SERIES_STDOUT_LOG: &str = "stdout.log";
+// log files compressed by `run --compress-logs`
+pub const SERIES_LOG_FILES: [&str; 3] = [SERIES_STDOUT_LOG, SERIES_STDERR_LOG, SERIES_EXOMAT_LOG];
+pub const SERIES_CHECKPOINT: &str = ".exomat_checkpoint";
+pub const SERIES_WARNINGS_LOG: &str = "warnings.log";
+pub const SERIES_SEED: &str = ".exomat_seed";
+pub const SERIES_HOST_INFO: &str = "host.json";
+pub const SERIES_PROVENANCE: &str = "provenance.env";
+pub const SERIES_MANIFEST: &str = "series.json";
+pub const SERIES_RUN_PREFIX: &str = "run_";
+pub const SERIES_WARMUP_PREFIX: &str = "warmup_";
 
 // experiment run folder
 pub const RUN_RUN_FILE: &str = "run.sh";
 pub const RUN_ENV_FILE: &str = "environment.env";
+pub const RUN_LIB_FILE: &str = "exomat_lib.sh";
+pub const RUN_ENV_HASH: &str = ".env_hash";
+pub const RUN_STDOUT_LOG: &str = "stdout.log";
+pub const RUN_STDERR_LOG: &str = "stderr.log";
+// stdout of a run, as written by older versions
+pub const RUN_STDOUT_LOG_LEGACY: &str = ".exomat_stdout";
+pub const RUN_COMMAND_FILE: &str = "command.txt";
 
 // names for marker files
 pub const MARKER_SRC: &str = ".exomat_source";
@@ -26,14 +46,20 @@ pub const MARKER_SRC_CP: &str = ".exomat_source_copy";
 pub const MARKER_SERIES: &str = ".exomat_series";
 pub const MARKER_RUN: &str = ".exomat_run";
 
+// tables written outside of a series
+pub const MERGED_TABLE: &str = "merged.csv";
+
 // names used in tests
 pub const TEST_RUN_REP_DIR0: &str = "run_x_rep0";
 pub const TEST_RUN_REP_DIR1: &str = "run_x_rep1";
 pub const TEST_RUN_REP_DIR2: &str = "run_y_rep0";
 pub const TEST_RUN_REP_DIR3: &str = "run_y_rep4";
 
-// required files in a structure
-pub const REQUIRED_RUN_FILES: [&str; 3] = [MARKER_RUN, RUN_ENV_FILE, RUN_RUN_FILE];
+// required files in a structure (and a run script, see `find_run_file()`)
+pub const REQUIRED_RUN_FILES: [&str; 2] = [MARKER_RUN, RUN_ENV_FILE];
+
+/// Prefix of all run scripts, e.g. `run.sh` (the default) or `run.py`
+pub const RUN_FILE_PREFIX: &str = "run.";
 
 /// Returns the last part of a path (which is the file-/directory name).
 ///
@@ -46,3 +72,95 @@ pub fn file_name_string(file: &Path) -> String {
         .unwrap_or_else(|| panic!("Could stringify filename for {}", file.display()))
         .to_string()
 }
+
+/// Returns `true` if `name` is the name of a run script, i.e. [RUN_FILE_PREFIX]
+/// followed by an extension.
+pub fn is_run_file(name: &str) -> bool {
+    name.strip_prefix(RUN_FILE_PREFIX)
+        .is_some_and(|ext| !ext.is_empty())
+}
+
+/// Returns the run script in `dir`: [SRC_RUN_FILE] if it exists, otherwise the
+/// first other file (by name) that `is_run_file()`.
+///
+/// Returns `None` if `dir` contains no run script.
+pub fn find_run_file(dir: &Path) -> Option<PathBuf> {
+    let default = dir.join(SRC_RUN_FILE);
+    if default.is_file() {
+        return Some(default);
+    }
+
+    let mut run_files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(is_run_file)
+        })
+        .collect();
+    run_files.sort();
+    run_files.into_iter().next()
+}
+
+/// Returns all pairs of `names` that only differ in case.
+///
+/// Such names refer to the same file on case-insensitive filesystems (the default
+/// on macOS and Windows), so one would silently replace the other.
+pub fn case_collisions<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<(String, String)> {
+    let mut seen: Vec<&str> = vec![];
+    let mut collisions = vec![];
+
+    for name in names {
+        if let Some(other) = seen
+            .iter()
+            .find(|other| **other != name && other.to_lowercase() == name.to_lowercase())
+        {
+            collisions.push((other.to_string(), name.to_string()));
+        }
+        seen.push(name);
+    }
+
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_run_scripts() {
+        let tmpdir = tempfile::TempDir::new().unwrap();
+        let dir = tmpdir.path();
+        assert_eq!(find_run_file(dir), None);
+
+        std::fs::write(dir.join("runner.py"), "").unwrap();
+        std::fs::create_dir(dir.join("run.d")).unwrap();
+        assert_eq!(find_run_file(dir), None);
+
+        std::fs::write(dir.join("run.py"), "").unwrap();
+        std::fs::write(dir.join("run.fish"), "").unwrap();
+        assert_eq!(find_run_file(dir), Some(dir.join("run.fish")));
+
+        // run.sh is preferred
+        std::fs::write(dir.join(SRC_RUN_FILE), "").unwrap();
+        assert_eq!(find_run_file(dir), Some(dir.join(SRC_RUN_FILE)));
+
+        assert!(is_run_file("run.sh") && is_run_file("run.py"));
+        assert!(!is_run_file("run.") && !is_run_file("run") && !is_run_file("rerun.sh"));
+    }
+
+    #[test]
+    fn detect_case_collisions() {
+        assert!(case_collisions(["foo", "bar", "foo_1"]).is_empty());
+        assert_eq!(
+            case_collisions(["Foo", "bar", "foo", "BAR"]),
+            vec![
+                ("Foo".to_string(), "foo".to_string()),
+                ("bar".to_string(), "BAR".to_string())
+            ]
+        );
+    }
+}
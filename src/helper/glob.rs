@@ -0,0 +1,70 @@
+//! Matching of file names against shell-like wildcard patterns
+
+/// Returns `true` if `pattern` matches all of `name`.
+///
+/// `*` matches any number of characters (including none), `?` matches exactly
+/// one character. All other characters only match themselves.
+///
+/// ## Example
+/// ```
+/// use exomat::helper::glob::matches;
+///
+/// assert!(matches("*high*", "3_high_load.env"));
+/// assert!(matches("?.env", "3.env"));
+/// assert!(!matches("?.env", "13.env"));
+/// ```
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // position after the last `*` in pattern and the part of name it consumed
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, n));
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            // let the last `*` consume one more character
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("3.env", "3.env", true)]
+    #[case("3.env", "13.env", false)]
+    #[case("*", "", true)]
+    #[case("*", "0.env", true)]
+    #[case("*high*", "high.env", true)]
+    #[case("*high*", "cpu_high_2.env", true)]
+    #[case("*high*", "low.env", false)]
+    #[case("?.env", "7.env", true)]
+    #[case("?.env", ".env", false)]
+    #[case("a*b*c", "aXbYbZc", true)]
+    #[case("a*b*c", "aXbYbZ", false)]
+    #[case("", "x", false)]
+    fn match_patterns(#[case] pattern: &str, #[case] name: &str, #[case] expected: bool) {
+        assert_eq!(matches(pattern, name), expected);
+    }
+}
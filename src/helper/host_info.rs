@@ -0,0 +1,116 @@
+//! Description of the machine an Experiment Series is executed on
+
+use serde::Serialize;
+use std::ffi::CStr;
+
+/// Fingerprint of the host, written to `host.json` of every Experiment Series
+///
+/// Every field that could not be determined on this system is `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HostInfo {
+    pub hostname: Option<String>,
+    /// Name and release of the kernel, e.g. `Linux 6.1.0`
+    pub kernel: Option<String>,
+    pub cpu_model: Option<String>,
+    /// Number of logical cores available to the exomat
+    pub cores: Option<usize>,
+    pub memory_bytes: Option<u64>,
+    pub exomat_version: String,
+}
+
+impl HostInfo {
+    /// Collects the information about the current host.
+    pub fn collect() -> Self {
+        let (hostname, kernel) = uname();
+
+        HostInfo {
+            hostname,
+            kernel,
+            cpu_model: cpu_model(),
+            cores: std::thread::available_parallelism()
+                .ok()
+                .map(|cores| cores.get()),
+            memory_bytes: memory_bytes(),
+            exomat_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Returns the fingerprint as (pretty printed) JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("HostInfo only contains plain values")
+    }
+}
+
+/// Returns the hostname and `[sysname] [release]` of the kernel.
+fn uname() -> (Option<String>, Option<String>) {
+    let mut info: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut info) } != 0 {
+        return (None, None);
+    }
+
+    let field = |chars: &[libc::c_char]| {
+        // fields are null terminated, the buffer was zeroed before
+        unsafe { CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .to_string()
+    };
+
+    (
+        Some(field(&info.nodename)),
+        Some(format!("{} {}", field(&info.sysname), field(&info.release))),
+    )
+}
+
+/// Returns the model name of the first CPU in `/proc/cpuinfo`.
+fn cpu_model() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    parse_cpu_model(&cpuinfo)
+}
+
+/// Returns the value of the first `model name` (or `Model`, e.g. on ARM) line.
+fn parse_cpu_model(cpuinfo: &str) -> Option<String> {
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        match key.trim() {
+            "model name" | "Model" => Some(value.trim().to_string()),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the total physical memory.
+fn memory_bytes() -> Option<u64> {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+    match (u64::try_from(pages), u64::try_from(page_size)) {
+        (Ok(pages), Ok(page_size)) if pages > 0 => Some(pages * page_size),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cpuinfo() {
+        let cpuinfo = "processor\t: 0\nvendor_id\t: GenuineIntel\nmodel name\t: Intel(R) Xeon(R) CPU\n\nprocessor\t: 1\nmodel name\t: other\n";
+        assert_eq!(
+            parse_cpu_model(cpuinfo),
+            Some("Intel(R) Xeon(R) CPU".to_string())
+        );
+        assert_eq!(parse_cpu_model("processor : 0\n"), None);
+    }
+
+    #[test]
+    fn collect_host_info() {
+        let info = HostInfo::collect();
+        assert!(info.hostname.is_some());
+        assert!(info.cores.is_some_and(|cores| cores > 0));
+        assert_eq!(info.exomat_version, env!("CARGO_PKG_VERSION"));
+
+        let json: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(json["exomat_version"], env!("CARGO_PKG_VERSION"));
+    }
+}
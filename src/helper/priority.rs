@@ -0,0 +1,80 @@
+//! Scheduling priority (nice value) of Experiment Runs
+
+use log::warn;
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::sync::Once;
+
+/// Range of valid nice values, from highest to lowest priority
+pub const NICE_RANGE: std::ops::RangeInclusive<i32> = -20..=19;
+
+/// Starts the process of `command` with the nice value `nice`.
+///
+/// The value is set with `setpriority` in the child before the run script is
+/// executed, so all processes started by the script inherit it. If the exomat
+/// is not allowed to raise the priority (negative `nice` without privileges),
+/// it warns once and the runs are started with the default priority instead.
+pub fn nice_command(command: &mut Command, nice: i32) {
+    if !may_set_nice(nice) {
+        static WARN: Once = Once::new();
+        WARN.call_once(|| {
+            warn!("Not allowed to set nice value {nice}, runs keep the default priority")
+        });
+    }
+
+    // only a single syscall, which is safe to do between fork and exec, failing
+    // to set the priority is not fatal
+    unsafe {
+        command.pre_exec(move || {
+            libc::setpriority(libc::PRIO_PROCESS as _, 0, nice);
+            Ok(())
+        });
+    }
+}
+
+/// Returns `true` if the current process may set its nice value to `nice`.
+///
+/// Lowering the priority is always allowed. Raising it requires root or a
+/// sufficient `RLIMIT_NICE`.
+fn may_set_nice(nice: i32) -> bool {
+    let current = unsafe { libc::getpriority(libc::PRIO_PROCESS as _, 0) };
+    if nice >= current || unsafe { libc::geteuid() } == 0 {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // the limit is given as 20 - nice, see setrlimit(2)
+        if unsafe { libc::getrlimit(libc::RLIMIT_NICE, &mut limit) } == 0 {
+            return limit.rlim_cur >= (20 - nice) as libc::rlim_t;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn niced_command() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "cut -d ' ' -f 19 /proc/self/stat"]);
+        nice_command(&mut command, 7);
+
+        let output = command.output().unwrap();
+        #[cfg(target_os = "linux")]
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "7");
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn lower_priority_is_allowed() {
+        assert!(may_set_nice(*NICE_RANGE.end()));
+    }
+}
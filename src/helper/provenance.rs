@@ -0,0 +1,115 @@
+//! Git commit an Experiment Source was executed from
+
+use log::debug;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// State of the git repository containing an Experiment Source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// hash of the checked out commit
+    pub commit: String,
+    /// `true` if files inside of the Experiment Source have uncommitted changes
+    pub dirty: bool,
+}
+
+impl Provenance {
+    /// Returns the state of the git repository that contains `exp_source_dir`.
+    ///
+    /// Returns `None` if `exp_source_dir` is not inside of a git repository, the
+    /// repository has no commits or `git` is not installed.
+    pub fn of(exp_source_dir: &Path) -> Option<Self> {
+        let commit = git(exp_source_dir, &["rev-parse", "HEAD"])?;
+        let changes = git(exp_source_dir, &["status", "--porcelain", "--", "."])?;
+
+        Some(Provenance {
+            commit: commit.trim().to_string(),
+            dirty: !changes.trim().is_empty(),
+        })
+    }
+
+    /// Returns the provenance as `GIT_COMMIT` and `GIT_DIRTY` in .env format.
+    pub fn to_env(&self) -> String {
+        format!("GIT_COMMIT={}\nGIT_DIRTY={}\n", self.commit, self.dirty)
+    }
+}
+
+/// Executes `git args` in `dir`, returns stdout if it succeeded.
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!("cannot execute git: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git_in(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args([
+                "-c",
+                "user.name=exomat",
+                "-c",
+                "user.email=exomat@example.com",
+            ])
+            .args(args)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn provenance_of_repository() {
+        let tmpdir = TempDir::new().unwrap();
+        let repo = tmpdir.path();
+        let source = repo.join("exp");
+        std::fs::create_dir(&source).unwrap();
+
+        // not a repository (yet)
+        assert_eq!(Provenance::of(&source), None);
+
+        git_in(repo, &["init", "--quiet"]);
+        // no commit yet
+        assert_eq!(Provenance::of(&source), None);
+
+        std::fs::write(source.join("run.sh"), "echo 1").unwrap();
+        git_in(repo, &["add", "."]);
+        git_in(repo, &["commit", "--quiet", "-m", "add exp"]);
+
+        let clean = Provenance::of(&source).unwrap();
+        assert_eq!(clean.commit.len(), 40);
+        assert!(!clean.dirty);
+
+        // changes outside of the source do not matter
+        std::fs::write(repo.join("notes"), "").unwrap();
+        assert!(!Provenance::of(&source).unwrap().dirty);
+
+        std::fs::write(source.join("run.sh"), "echo 2").unwrap();
+        let dirty = Provenance::of(&source).unwrap();
+        assert!(dirty.dirty);
+        assert_eq!(
+            dirty.to_env(),
+            format!("GIT_COMMIT={}\nGIT_DIRTY=true\n", clean.commit)
+        );
+    }
+}
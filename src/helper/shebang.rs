@@ -0,0 +1,66 @@
+//! Inspection of the interpreter line (`#!...`) of run scripts
+
+use std::path::Path;
+
+/// Interpreters that understand POSIX shell options like `-x` and `-n`
+pub const POSIX_SHELLS: [&str; 6] = ["sh", "bash", "dash", "zsh", "ksh", "ash"];
+
+/// Returns the interpreter named in the shebang of `script`.
+///
+/// For `#!/usr/bin/env [interpreter]` the interpreter passed to `env` is returned
+/// instead of `env` itself. Options given to the interpreter are ignored.
+///
+/// Returns `None` if `script` does not start with a shebang.
+pub fn interpreter(script: &str) -> Option<String> {
+    let line = script.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let program = words.next()?;
+
+    match Path::new(program).file_name()?.to_str()? {
+        "env" => words.find(|w| !w.starts_with('-')).map(|w| w.to_string()),
+        _ => Some(program.to_string()),
+    }
+}
+
+/// Returns the interpreter of `script` if it is a POSIX compatible shell.
+///
+/// Returns `None` if there is no shebang or if the interpreter is not listed
+/// in [POSIX_SHELLS].
+pub fn posix_shell(script: &str) -> Option<String> {
+    interpreter(script).filter(|program| {
+        Path::new(program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| POSIX_SHELLS.contains(&name))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("#!/bin/bash\necho foo", Some("/bin/bash"))]
+    #[case("#!/usr/bin/env bash\nset -euo pipefail", Some("bash"))]
+    #[case("#!/usr/bin/env -S zsh -e\n", Some("zsh"))]
+    #[case("#! /bin/sh -e\n", Some("/bin/sh"))]
+    #[case("#!/usr/bin/env python3\nprint()", None)]
+    #[case("echo no shebang", None)]
+    #[case("", None)]
+    fn detect_posix_shell(#[case] script: &str, #[case] expected: Option<&str>) {
+        assert_eq!(posix_shell(script), expected.map(|s| s.to_string()));
+    }
+
+    #[test]
+    fn detect_any_interpreter() {
+        assert_eq!(
+            interpreter("#!/usr/bin/env python3\n"),
+            Some("python3".to_string())
+        );
+        assert_eq!(
+            interpreter("#!/usr/bin/fish"),
+            Some("/usr/bin/fish".to_string())
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Splitting of strings into words, following the quoting rules of POSIX shells
+
+/// Splits `line` into words, like a POSIX shell would.
+///
+/// Words are separated by unquoted whitespace. Within single quotes every
+/// character is taken literally, within double quotes `\` only escapes `"`, `\`,
+/// `$` and `` ` ``. Outside of quotes `\` escapes any character.
+///
+/// No expansion (variables, globs, ...) is performed.
+///
+/// ## Example
+/// ```
+/// use exomat::helper::shell_words::split;
+///
+/// let words = split(r#"--name "foo bar" 'it''s' a\ b"#).unwrap();
+/// assert_eq!(words, vec!["--name", "foo bar", "its", "a b"]);
+/// ```
+///
+/// ## Errors
+/// - Returns an error message if a quote is not closed or `line` ends with `\`
+pub fn split(line: &str) -> Result<Vec<String>, String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+
+    let unterminated = |what: &str| format!("{what} in \"{line}\"");
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            }
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => w.push(c),
+                        None => return Err(unterminated("unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => w.push(c),
+                            Some(c) => {
+                                w.push('\\');
+                                w.push(c);
+                            }
+                            None => return Err(unterminated("unterminated double quote")),
+                        },
+                        Some(c) => w.push(c),
+                        None => return Err(unterminated("unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err(unterminated("trailing backslash")),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(w) = word {
+        words.push(w);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", vec![])]
+    #[case("   ", vec![])]
+    #[case("-n 4 --verbose", vec!["-n", "4", "--verbose"])]
+    #[case("  a\tb  ", vec!["a", "b"])]
+    #[case("'' \"\"", vec!["", ""])]
+    #[case("--msg='hello world'", vec!["--msg=hello world"])]
+    #[case(r#""say \"hi\"" 'back\slash'"#, vec![r#"say "hi""#, r"back\slash"])]
+    #[case(r#""\n" \n"#, vec![r"\n", "n"])]
+    fn split_words(#[case] line: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(split(line).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("'open")]
+    #[case("\"open")]
+    #[case("end\\")]
+    fn split_invalid(#[case] line: &str) {
+        assert!(split(line).is_err());
+    }
+}
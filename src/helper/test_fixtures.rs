@@ -430,26 +430,26 @@ pub fn setup_series_dir() -> TempDir {
     std::fs::create_dir_all(&empty_run).unwrap();
 
     // Create simple run script
-    std::fs::write(&unequal_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
-    std::fs::write(&equal_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
-    std::fs::write(&empty_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(unequal_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(equal_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(empty_run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
 
     // Create env file for all runs
-    std::fs::write(&unequal_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
-    std::fs::write(&equal_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
-    std::fs::write(&empty_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(unequal_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(equal_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(empty_run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
 
     // Create out_ files (equal)
-    std::fs::write(&equal_run.join("out_number"), "1\n2").unwrap();
-    std::fs::write(&equal_run.join("out_word"), "one\ntwo").unwrap();
+    std::fs::write(equal_run.join("out_number"), "1\n2").unwrap();
+    std::fs::write(equal_run.join("out_word"), "one\ntwo").unwrap();
 
     // Create out_ files (unequal)
-    std::fs::write(&unequal_run.join("out_number"), "1\n2\n3").unwrap();
-    std::fs::write(&unequal_run.join("out_word"), "NA").unwrap();
+    std::fs::write(unequal_run.join("out_number"), "1\n2\n3").unwrap();
+    std::fs::write(unequal_run.join("out_word"), "NA").unwrap();
 
     // Create out_ files (empty)
-    std::fs::write(&unequal_run.join("out_number"), "1\n2\n").unwrap();
-    std::fs::File::create(&unequal_run.join("out_word")).unwrap();
+    std::fs::write(unequal_run.join("out_number"), "1\n2\n").unwrap();
+    std::fs::File::create(unequal_run.join("out_word")).unwrap();
 
     tmp_run
 }
@@ -471,10 +471,10 @@ pub fn setup_series_no_out() -> TempDir {
     std::fs::create_dir_all(&run).unwrap();
 
     // Create simple run script
-    std::fs::write(&run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
 
     // Create env file
-    std::fs::write(&run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
 
     tmp_run
 }
@@ -497,13 +497,13 @@ pub fn setup_series_empty_out() -> TempDir {
     std::fs::create_dir_all(&run).unwrap();
 
     // Create simple run script
-    std::fs::write(&run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
 
     // Create env file
-    std::fs::write(&run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
 
     // create empty out files
-    std::fs::File::create(&run.join("out_empty")).unwrap();
+    std::fs::File::create(run.join("out_empty")).unwrap();
 
     tmp_run
 }
@@ -523,14 +523,14 @@ pub fn setup_run_dir_shadow() -> TempDir {
     let run = tmp_run.path().to_path_buf();
 
     // Create simple run script
-    std::fs::write(&run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
 
     // Create env file for both runs
-    std::fs::write(&run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
 
     // Create out_ files (equal)
-    std::fs::write(&run.join("out_VAR1"), "1").unwrap();
-    std::fs::write(&run.join("out_word"), "one").unwrap();
+    std::fs::write(run.join("out_VAR1"), "1").unwrap();
+    std::fs::write(run.join("out_word"), "one").unwrap();
 
     tmp_run
 }
@@ -550,14 +550,14 @@ pub fn setup_run_dir() -> TempDir {
     let run = tmp_run.path().to_path_buf();
 
     // Create simple run script
-    std::fs::write(&run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
+    std::fs::write(run.join(RUN_RUN_FILE), "echo $VAR1").unwrap();
 
     // Create env file for both runs
-    std::fs::write(&run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
+    std::fs::write(run.join(RUN_ENV_FILE), "VAR1=foo\nVAR2=bar").unwrap();
 
     // Create out_ files (equal)
-    std::fs::write(&run.join("out_number"), "1\n2").unwrap();
-    std::fs::write(&run.join("out_word"), "one\ntwo").unwrap();
+    std::fs::write(run.join("out_number"), "1\n2").unwrap();
+    std::fs::write(run.join("out_word"), "one\ntwo").unwrap();
 
     tmp_run
 }
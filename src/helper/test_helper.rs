@@ -1,6 +1,7 @@
 use super::fs_names::*;
 use std::fs::OpenOptions;
-use std::{io::Write, path::PathBuf};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use crate::experiment::{ExperimentSeries, ExperimentSource, FileWriter};
 use crate::harness::env::ExomatEnvironment;
@@ -8,12 +9,12 @@ use crate::harness::env::ExomatEnvironment;
 /// helper to create a `run.sh` file in an experiment source directory.
 ///
 /// When executed, it will write the content of `${out_env}` to stdout and in `out_file`
-pub fn place_filled_run_in(exp_src: &PathBuf, out_env: &str) {
+pub fn place_filled_run_in(exp_src: &Path, out_env: &str) {
     let run_sh_path = exp_src.join(SRC_TEMPLATE_DIR).join(SRC_RUN_FILE);
     let content = format!("echo ${out_env}\necho ${out_env} >> out_file");
 
     let mut run_sh = OpenOptions::new().append(true).open(&run_sh_path).unwrap();
-    run_sh.write(content.as_bytes()).unwrap();
+    run_sh.write_all(content.as_bytes()).unwrap();
 }
 
 /// helper that reads a file at `[location]/[SERIES_RUNS_DIR]/[log_name]`
@@ -31,7 +32,7 @@ pub fn create_file_at(location: &PathBuf, content: &str) {
 ///
 /// Only the source gets serialized.
 pub fn populate_src_with_series(
-    base: &PathBuf,
+    base: &Path,
     src_name: &str,
     series_name: &str,
 ) -> (ExperimentSource, ExperimentSeries) {
@@ -47,16 +48,12 @@ pub fn populate_src_with_series(
 
     (src, ser)
 }
-/// Checks if the given string contains either one or the other
-pub fn contains_either(string: &String, one: &str, other: &str) -> bool {
-    string.contains(one) || string.contains(other)
-}
 
 /// Creates a file called `name` in `series_dir/[SERIES_RUNS_DIR]/rep_name/` with the content `content`
 ///
 /// If `rep_name` is `None`, [TEST_RUN_REP_DIR0] is used.
 pub fn create_out_file(
-    series_dir: &PathBuf,
+    series_dir: &Path,
     rep_name: Option<&str>,
     name: &str,
     content: &str,
@@ -69,11 +66,27 @@ pub fn create_out_file(
         std::fs::create_dir_all(&rundir).unwrap();
     }
 
-    std::fs::File::create(&rundir.join(name)).unwrap();
+    std::fs::File::create(rundir.join(name)).unwrap();
 
     if !content.is_empty() {
-        std::fs::write(&rundir.join(name), content).unwrap();
+        std::fs::write(rundir.join(name), content).unwrap();
     }
 
     rundir.join(name)
 }
+
+/// Creates the run directory `series_dir/[SERIES_RUNS_DIR]/name/` with an empty
+/// [RUN_RUN_FILE], `env` as [RUN_ENV_FILE] and one file per entry in `outs`
+/// (file name, content).
+pub fn create_run(series_dir: &Path, name: &str, env: &str, outs: &[(&str, &str)]) -> PathBuf {
+    let rundir = series_dir.join(SERIES_RUNS_DIR).join(name);
+    std::fs::create_dir_all(&rundir).unwrap();
+
+    std::fs::write(rundir.join(RUN_RUN_FILE), "").unwrap();
+    std::fs::write(rundir.join(RUN_ENV_FILE), env).unwrap();
+    for (out_name, content) in outs {
+        std::fs::write(rundir.join(out_name), content).unwrap();
+    }
+
+    rundir
+}
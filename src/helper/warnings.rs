@@ -0,0 +1,106 @@
+//! Collection of all warnings logged while exomat is running
+
+use spdlog::formatter::Formatter;
+use spdlog::sink::Sink;
+use spdlog::{ErrorHandler, Level, LevelFilter, Record};
+use std::sync::Mutex;
+
+/// All warnings recorded since the last call of [take()]
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+/// Records `warning`, so it is included in the next [summary()].
+///
+/// Messages logged with `warn!()` are recorded automatically, once logging has
+/// been activated (see [activate_logging](crate::activate_logging)).
+pub fn record(warning: &str) {
+    WARNINGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(warning.to_string());
+}
+
+/// Returns all recorded warnings and clears the list.
+pub fn take() -> Vec<String> {
+    std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Returns the message shown after exomat has finished.
+///
+/// Points to `log` if the warnings have been written to a file.
+pub fn summary(warnings: &[String], log: Option<&str>) -> String {
+    let plural = match warnings.len() {
+        1 => "",
+        _ => "s",
+    };
+
+    match (warnings.len(), log) {
+        (0, _) => "Completed without warnings.".to_string(),
+        (n, Some(log)) => format!("Completed with {n} warning{plural} (see {log})."),
+        (n, None) => format!("Completed with {n} warning{plural}."),
+    }
+}
+
+/// Sink that records every warning logged via spdlog (or the log crate)
+pub struct WarningSink;
+
+impl Sink for WarningSink {
+    fn log(&self, log_record: &Record) -> spdlog::Result<()> {
+        record(log_record.payload());
+        Ok(())
+    }
+
+    fn flush(&self) -> spdlog::Result<()> {
+        Ok(())
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        LevelFilter::Equal(Level::Warn)
+    }
+
+    // always records warnings, output is not formatted
+    fn set_level_filter(&self, _level_filter: LevelFilter) {}
+    fn set_formatter(&self, _formatter: Box<dyn Formatter>) {}
+    fn set_error_handler(&self, _handler: Option<ErrorHandler>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::{error, info, warn};
+    use rusty_fork::rusty_fork_test;
+
+    #[test]
+    fn summary_message() {
+        assert_eq!(
+            summary(&[], Some("warnings.log")),
+            "Completed without warnings."
+        );
+        assert_eq!(
+            summary(&["a".to_string()], None),
+            "Completed with 1 warning."
+        );
+        assert_eq!(
+            summary(&vec!["a".to_string(); 3], Some("warnings.log")),
+            "Completed with 3 warnings (see warnings.log)."
+        );
+    }
+
+    rusty_fork_test! {
+        #[test]
+        fn collect_logged_warnings() {
+            crate::activate_logging(log::LevelFilter::Info);
+            take();
+
+            warn!("first");
+            info!("not a warning");
+            warn!("second");
+            error!("not a warning either");
+            record("third");
+
+            let warnings = take();
+            assert_eq!(warnings, vec!["first", "second", "third"]);
+            assert!(summary(&warnings, Some("warnings.log")).starts_with("Completed with 3 warnings"));
+            assert!(take().is_empty());
+        }
+    }
+}
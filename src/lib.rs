@@ -4,28 +4,47 @@
 #![doc=include_str!("../docs/build.md")]
 
 pub mod harness {
+    pub mod archive;
+    pub mod clean;
     pub mod env;
     pub mod run;
     pub mod skeleton;
+    pub mod status;
     pub mod table;
+    pub mod validate;
 }
 
 pub mod experiment {
+    pub mod cache;
+    pub mod checkpoint;
     pub mod experiment_run;
     pub mod experiment_series;
     pub mod experiment_source;
     pub mod experiment_traits;
+    pub mod markers;
     pub mod out_file;
+    pub mod run_options;
+    pub mod table_options;
 
     pub use experiment_run::ExperimentRun;
     pub use experiment_series::ExperimentSeries;
     pub use experiment_source::ExperimentSource;
     pub use experiment_traits::*;
+    pub use run_options::RunOptions;
+    pub use table_options::TableOptions;
 }
 pub mod helper {
+    pub mod affinity;
     pub mod archivist;
     pub mod errors;
     pub mod fs_names;
+    pub mod glob;
+    pub mod host_info;
+    pub mod priority;
+    pub mod provenance;
+    pub mod shebang;
+    pub mod shell_words;
+    pub mod warnings;
 
     #[cfg(test)]
     pub mod test_fixtures;
@@ -37,15 +56,85 @@ pub mod helper {
 use indicatif::MultiProgress;
 use indicatif_log_bridge::LogWrapper;
 use log::info;
-use spdlog::formatter::{pattern, PatternFormatter};
+use spdlog::formatter::{pattern, Formatter, FormatterContext, PatternFormatter};
 use spdlog::sink::WriteSink;
 use std::io::{pipe, PipeReader};
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 use helper::archivist::find_marker_pwd;
 use helper::errors::{Error, Result};
 use helper::fs_names::*;
 
+/// Format of log messages, see `set_log_format()`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LogFormat {
+    /// `[date time] [level] message`
+    #[default]
+    Human,
+    /// one JSON object per line with `timestamp`, `level` and `message`
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "human" => Ok(LogFormat::Human),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(Error::LoggerFormatError {
+                reason: format!("unknown log format \"{s}\", use human or json"),
+            }),
+        }
+    }
+}
+
+/// Format used by all loggers created afterwards
+static LOG_FORMAT: Mutex<LogFormat> = Mutex::new(LogFormat::Human);
+
+/// Sets the format of all log messages.
+///
+/// Has to be called before `activate_logging()` to take effect.
+pub fn set_log_format(format: LogFormat) {
+    *LOG_FORMAT.lock().unwrap() = format;
+}
+
+/// Formats log messages as one JSON object per line.
+#[derive(Clone)]
+struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn format(
+        &self,
+        record: &spdlog::Record,
+        dest: &mut spdlog::StringBuf,
+        _ctx: &mut FormatterContext,
+    ) -> spdlog::Result<()> {
+        let timestamp = chrono::DateTime::<chrono::Local>::from(record.time())
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, false);
+        let line = serde_json::json!({
+            "timestamp": timestamp,
+            "level": record.level().as_str(),
+            "message": record.payload(),
+        });
+
+        dest.push_str(&line.to_string());
+        dest.push('\n');
+        Ok(())
+    }
+}
+
+/// Returns a formatter for the currently set `LogFormat`.
+fn log_formatter() -> Box<dyn Formatter> {
+    match *LOG_FORMAT.lock().unwrap() {
+        LogFormat::Human => Box::new(PatternFormatter::new(pattern!(
+            "[{date} {time}.{millisecond}] [{level}] {payload}{eol}"
+        ))),
+        LogFormat::Json => Box::new(JsonFormatter),
+    }
+}
+
 /// Initializes logging for all severity levels from info and up.
 ///
 /// Logging will still work if this function is not called, however, only
@@ -78,8 +167,21 @@ use helper::fs_names::*;
 /// prog_bar.finish();
 /// ```
 pub fn activate_logging(verbosity: log::LevelFilter) -> MultiProgress {
+    activate_logging_on(verbosity, spdlog::sink::StdStream::Stdout)
+}
+
+/// Initializes logging like `activate_logging()`, but prints log messages to
+/// stderr, so stdout only contains the results of a command.
+pub fn activate_logging_on_stderr(verbosity: log::LevelFilter) -> MultiProgress {
+    activate_logging_on(verbosity, spdlog::sink::StdStream::Stderr)
+}
+
+fn activate_logging_on(
+    verbosity: log::LevelFilter,
+    stream: spdlog::sink::StdStream,
+) -> MultiProgress {
     // configure the logger, init spdlog, log and log_bridge
-    reset_logger(spdlog::LevelFilter::from(verbosity));
+    reset_logger_on(spdlog::LevelFilter::from(verbosity), stream);
 
     let multi = MultiProgress::new();
     let log_wrapper = LogWrapper::new(multi.clone(), spdlog::log_crate_proxy());
@@ -93,12 +195,11 @@ pub fn activate_logging(verbosity: log::LevelFilter) -> MultiProgress {
 /// warning: will reset the effect of duplicate_log_to_file()!
 fn disable_console_log() {
     // create logger that logs to log_file
-    let pattern = pattern!("[{date} {time}.{millisecond}] [{level}] {payload}{eol}");
     let new_logger = spdlog::Logger::builder()
         .level_filter(spdlog::LevelFilter::All)
         .sink(Arc::new(
             spdlog::sink::StdStreamSink::builder()
-                .formatter(Box::new(PatternFormatter::new(pattern)))
+                .formatter(log_formatter())
                 .level_filter(spdlog::LevelFilter::Off)
                 .std_stream(spdlog::sink::StdStream::Stdout)
                 .build()
@@ -121,14 +222,13 @@ fn disable_console_log() {
 pub fn duplicate_log_to_pipe() -> Result<PipeReader> {
     info!("Started capture of exomat log");
     let (rdr, wtr) = pipe()?;
-    let pattern = pattern!("[{date} {time}.{millisecond}] [{level}] {payload}{eol}");
 
     // create logger that logs to log_file
     let new_logger = spdlog::default_logger()
         .fork_with(|new| {
             let pipe_sink = Arc::new(
                 WriteSink::builder()
-                    .formatter(Box::new(PatternFormatter::new(pattern)))
+                    .formatter(log_formatter())
                     .level_filter(spdlog::LevelFilter::All)
                     .target(wtr)
                     .build()?,
@@ -149,9 +249,13 @@ pub fn duplicate_log_to_pipe() -> Result<PipeReader> {
 
 /// Resets logger settings to the default logger used in exomat.
 pub fn reset_logger(verbosity: spdlog::LevelFilter) {
-    info!("Resetting logger; Exomat log may no longer be captured in file");
+    reset_logger_on(verbosity, spdlog::sink::StdStream::Stdout)
+}
 
-    let pattern = pattern!("[{date} {time}.{millisecond}] [{level}] {payload}{eol}");
+/// Resets logger settings to the default logger used in exomat, printing log
+/// messages to `stream`.
+fn reset_logger_on(verbosity: spdlog::LevelFilter, stream: spdlog::sink::StdStream) {
+    info!("Resetting logger; Exomat log may no longer be captured in file");
 
     // configure the logger, default logger does not work because it gets messed up
     // when having multiple sinks with different level filters
@@ -159,12 +263,13 @@ pub fn reset_logger(verbosity: spdlog::LevelFilter) {
         .level_filter(spdlog::LevelFilter::All)
         .sink(Arc::new(
             spdlog::sink::StdStreamSink::builder()
-                .formatter(Box::new(PatternFormatter::new(pattern)))
+                .formatter(log_formatter())
                 .level_filter(verbosity)
-                .std_stream(spdlog::sink::StdStream::Stdout)
+                .std_stream(stream)
                 .build()
                 .unwrap(),
         ))
+        .sink(Arc::new(helper::warnings::WarningSink))
         .build()
         .unwrap();
 
@@ -213,5 +318,27 @@ mod tests {
             assert!(log.contains("Error in file"));
             assert!(!log.contains("on console"));
         }
+
+        #[test]
+        fn log_as_json() {
+            set_log_format("json".parse().unwrap());
+            activate_logging(log::LevelFilter::Info);
+
+            let mut rdr = duplicate_log_to_pipe().unwrap();
+            warn!("Warn with \"quotes\"");
+
+            spdlog::default_logger().flush();
+            reset_logger(spdlog::default_logger().level_filter());
+
+            let mut log = String::new();
+            rdr.read_to_string(&mut log).unwrap();
+
+            let line = log.lines().find(|l| l.contains("quotes")).unwrap();
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(record["level"], "warn");
+            assert_eq!(record["message"], "Warn with \"quotes\"");
+            assert!(record["timestamp"].is_string());
+            assert!(log.lines().all(|l| serde_json::from_str::<serde_json::Value>(l).is_ok()));
+        }
     }
 }
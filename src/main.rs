@@ -1,36 +1,238 @@
 use clap::Parser;
 use spdlog::prelude::error;
+use std::path::Path;
 use std::process::ExitCode;
+use std::time::Duration;
 
 pub mod bin {
     pub mod cli_structure;
     pub mod completion;
     pub mod run;
+    pub mod version;
 }
 
 use bin::cli_structure::{Cli, Commands};
+use bin::run::RunMode;
+use exomat::experiment::{RunOptions, TableOptions};
+use exomat::harness::env::EnvEdits;
 use exomat::helper::errors::{Error, Result};
 
 fn main() -> ExitCode {
     let args = Cli::parse();
 
-    let log_handler = exomat::activate_logging(args.verbose.log_level_filter());
+    exomat::set_log_format(args.log_format);
+    let log_handler = match args.subcommand {
+        Commands::MakeTable { stdout: true, .. } => {
+            exomat::activate_logging_on_stderr(args.verbose.log_level_filter())
+        }
+        _ => exomat::activate_logging(args.verbose.log_level_filter()),
+    };
 
     let res = match args.subcommand {
         Commands::Run {
             experiment,
             trial,
+            trial_dir,
+            syntax_check,
+            verify_reproducible,
+            include_timing,
+            no_exec,
+            resume,
+            dry_run,
             output,
             repetitions,
-        } => bin::run::main(experiment, trial, output, repetitions, log_handler),
-        Commands::Skeleton { experiment } => exomat::harness::skeleton::main(&experiment),
+            shell_trace,
+            list_outputs,
+            checkpoint_interval,
+            quiet_runs,
+            notify,
+            symlinks,
+            isolate_tmp,
+            keep_tmp,
+            skip_cached,
+            timeout,
+            keep_going,
+            max_failures,
+            seed,
+            ordered,
+            env_filter,
+            retries,
+            retry_on_code,
+            warmup,
+            capture_command_metadata,
+            redact,
+            compress_logs,
+            notify_url,
+            notify_command,
+            cpu_affinity,
+            nice,
+            workdir,
+        } => bin::run::main(
+            experiment,
+            match (
+                trial,
+                syntax_check,
+                verify_reproducible,
+                no_exec,
+                dry_run,
+                resume,
+            ) {
+                (true, _, _, _, _, _) => RunMode::Trial { dir: trial_dir },
+                (_, true, _, _, _, _) => RunMode::SyntaxCheck,
+                (_, _, true, _, _, _) => RunMode::VerifyReproducible { include_timing },
+                (_, _, _, true, _, _) => RunMode::NoExec,
+                (_, _, _, _, true, _) => RunMode::DryRun,
+                (_, _, _, _, _, Some(series)) => RunMode::Resume { series },
+                _ => RunMode::Full,
+            },
+            output,
+            repetitions,
+            env_filter,
+            RunOptions {
+                shell_trace,
+                list_outputs,
+                checkpoint_interval,
+                quiet_runs,
+                notify,
+                symlinks,
+                isolate_tmp,
+                keep_tmp,
+                skip_cached,
+                timeout: timeout.map(Duration::from_secs),
+                keep_going,
+                max_failures,
+                seed,
+                ordered,
+                retries,
+                retry_on_codes: retry_on_code,
+                warmup,
+                capture_command: capture_command_metadata,
+                redact,
+                compress_logs,
+                notify_url,
+                notify_command,
+                cpu_affinity,
+                nice,
+                workdir,
+            },
+            log_handler,
+        ),
+        Commands::Skeleton {
+            experiment,
+            template_var,
+            force,
+            runner,
+        } => exomat::harness::skeleton::main(&experiment, &template_var, force, runner),
+        Commands::Env {
+            clear: true, yes, ..
+        } => exomat::harness::env::clear(yes),
+        Commands::Env { dedup: true, .. } => exomat::harness::env::dedup(),
+        Commands::Env { from_table, .. } if !from_table.is_empty() => {
+            exomat::harness::env::import_table(Path::new(&from_table[0]), &from_table[1..])
+        }
+        Commands::Env {
+            grid_file: Some(grid),
+            ..
+        } => exomat::harness::env::import_grid(&grid),
         Commands::Env {
             add,
             append,
             remove,
-        } => exomat::harness::env::main(add, append, remove),
-        Commands::MakeTable {} => exomat::harness::table::main(),
+            zip,
+            exclude,
+            reorder,
+            continue_on_error,
+            max_matrix,
+            preserve_order,
+            count,
+            allow_lowercase,
+            ..
+        } => exomat::harness::env::main(
+            EnvEdits {
+                add,
+                append,
+                remove,
+                zip,
+                exclude,
+                allow_lowercase,
+            },
+            reorder,
+            continue_on_error,
+            max_matrix,
+            preserve_order,
+            count,
+        ),
+        Commands::MergeEnvs {
+            src1,
+            src2,
+            into,
+            fill,
+        } => exomat::harness::env::merge(&src1, &src2, &into, fill),
+        Commands::MakeTable {
+            merge,
+            baseline,
+            gate,
+            pivot,
+            agg,
+            wide_per_rep,
+            aggregate,
+            na_value,
+            na_empty,
+            explode_json,
+            json_depth,
+            from_markers,
+            keep_extension,
+            strip_extension: _,
+            column_case,
+            delimiter,
+            with_env: _,
+            outputs_only,
+            streaming,
+            stdout,
+            recursive,
+        } => {
+            let options = TableOptions {
+                na_value: match na_empty {
+                    true => String::new(),
+                    false => na_value,
+                },
+                explode_json,
+                json_depth,
+                markers: from_markers,
+                strip_extension: !keep_extension,
+                column_case,
+                delimiter,
+                with_env: !outputs_only,
+                streaming,
+                to_stdout: stdout,
+                recursive,
+            };
+
+            match merge.is_empty() {
+                true => exomat::harness::table::main(
+                    baseline,
+                    gate,
+                    pivot,
+                    agg,
+                    wide_per_rep,
+                    aggregate,
+                    options,
+                ),
+                false => exomat::harness::table::merge_series(merge, options),
+            }
+        }
+        Commands::Validate { experiment } => exomat::harness::validate::main(experiment.as_deref()),
+        Commands::Archive {
+            series,
+            compression,
+            remove,
+        } => exomat::harness::archive::main(series.as_deref(), compression, remove),
+        Commands::Clean { dry_run, yes } => exomat::harness::clean::main(dry_run, yes),
+        Commands::Status { series } => exomat::harness::status::main(series.as_deref()),
         Commands::Completion { shell } => bin::completion::main(shell),
+        Commands::Version => {
+            bin::version::main(args.verbose.log_level_filter() > log::LevelFilter::Info)
+        }
     };
 
     match res {